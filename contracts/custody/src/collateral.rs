@@ -6,12 +6,14 @@ use crate::state::{
 
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{
-    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut,
+    MessageInfo, Response, StdResult, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::liquidation_queue::ExecuteMsg as LiquidationQueueExecuteMsg;
+use moneymarket::tokens::native_denom;
 use terra_cosmwasm::TerraMsgWrapper;
 
 /// Deposit new collateral
@@ -37,6 +39,31 @@ pub fn deposit_collateral(
     ]))
 }
 
+/// Deposit new native collateral sent with the message
+/// Executor: borrower
+pub fn deposit_native_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let denom =
+        native_denom(&config.collateral_token).ok_or(ContractError::NotNativeCollateral {})?;
+
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // Cannot deposit zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroDeposit(denom));
+    }
+
+    deposit_collateral(deps, info.sender, amount)
+}
+
 /// Withdraw spendable collateral or a specified amount of collateral
 /// Executor: borrower
 pub fn withdraw_collateral(
@@ -68,8 +95,15 @@ pub fn withdraw_collateral(
         store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
     }
 
-    Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+    let withdraw_msg = match native_denom(&config.collateral_token) {
+        Some(denom) => CosmosMsg::Bank(BankMsg::Send {
+            to_address: borrower.to_string(),
+            amount: vec![Coin {
+                denom,
+                amount: amount.into(),
+            }],
+        }),
+        None => CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
                 .addr_humanize(&config.collateral_token)?
@@ -79,7 +113,11 @@ pub fn withdraw_collateral(
                 recipient: borrower.to_string(),
                 amount: amount.into(),
             })?,
-        }))
+        }),
+    };
+
+    Ok(Response::new()
+        .add_message(withdraw_msg)
         .add_attributes(vec![
             attr("action", "withdraw_collateral"),
             attr("borrower", borrower.as_str()),
@@ -175,32 +213,50 @@ pub fn liquidate_collateral(
     borrower_info.balance = borrower_info.balance - amount;
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
 
-    Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+    let liquidation_contract = deps
+        .api
+        .addr_humanize(&config.liquidation_contract)?
+        .to_string();
+    let fee_address = Some(
+        deps.api
+            .addr_humanize(&config.overseer_contract)?
+            .to_string(),
+    );
+    let repay_address = Some(deps.api.addr_humanize(&config.market_contract)?.to_string());
+
+    let liquidation_msg = match native_denom(&config.collateral_token) {
+        Some(denom) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: liquidation_contract,
+            funds: vec![Coin {
+                denom,
+                amount: amount.into(),
+            }],
+            msg: to_binary(&LiquidationQueueExecuteMsg::ExecuteBid {
+                liquidator: liquidator.to_string(),
+                fee_address,
+                repay_address,
+            })?,
+        }),
+        None => CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
                 .addr_humanize(&config.collateral_token)?
                 .to_string(),
             funds: vec![],
             msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: deps
-                    .api
-                    .addr_humanize(&config.liquidation_contract)?
-                    .to_string(),
+                contract: liquidation_contract,
                 amount: amount.into(),
                 msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
                     liquidator: liquidator.to_string(),
-                    fee_address: Some(
-                        deps.api
-                            .addr_humanize(&config.overseer_contract)?
-                            .to_string(),
-                    ),
-                    repay_address: Some(
-                        deps.api.addr_humanize(&config.market_contract)?.to_string(),
-                    ),
+                    fee_address,
+                    repay_address,
                 })?,
             })?,
-        }))
+        }),
+    };
+
+    Ok(Response::new()
+        .add_message(liquidation_msg)
         .add_attributes(vec![
             attr("action", "liquidate_collateral"),
             attr("liquidator", liquidator),
@@ -209,6 +265,64 @@ pub fn liquidate_collateral(
         ]))
 }
 
+/// Send locked collateral to the swap contract,
+/// the borrower repayment is done by the overseer
+/// Executor: overseer
+pub fn swap_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+    swap_contract: Addr,
+    swap_msg: Binary,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let borrowed_amt = borrower_info.balance - borrower_info.spendable;
+    if amount > borrowed_amt {
+        return Err(ContractError::SwapAmountExceedsLocked(borrowed_amt.into()));
+    }
+
+    borrower_info.balance = borrower_info.balance - amount;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    // native collateral is sent along with the swap message
+    let swap_msg = match native_denom(&config.collateral_token) {
+        Some(denom) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: swap_contract.to_string(),
+            funds: vec![Coin {
+                denom,
+                amount: amount.into(),
+            }],
+            msg: swap_msg,
+        }),
+        None => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.collateral_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: swap_contract.to_string(),
+                amount: amount.into(),
+                msg: swap_msg,
+            })?,
+        }),
+    };
+
+    Ok(Response::new().add_message(swap_msg).add_attributes(vec![
+        attr("action", "swap_collateral"),
+        attr("borrower", borrower),
+        attr("swap_contract", swap_contract),
+        attr("amount", amount),
+    ]))
+}
+
 pub fn query_borrower(deps: Deps, borrower: Addr) -> StdResult<BorrowerResponse> {
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
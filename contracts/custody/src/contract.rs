@@ -0,0 +1,351 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult,
+};
+
+use crate::collateral::{
+    deposit_collateral, deposit_native_collateral, liquidate_collateral, lock_collateral,
+    query_borrower, query_borrowers, swap_collateral, unlock_collateral, withdraw_collateral,
+};
+use crate::distribution::{distribute_hook, distribute_rewards, swap_to_stable_denom};
+use crate::error::ContractError;
+use crate::state::{migrate_state, read_config, store_config, Config};
+
+use cw20::Cw20ReceiveMsg;
+use moneymarket::common::optional_addr_validate;
+use moneymarket::custody::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RewardAdapter,
+};
+use moneymarket::ownership;
+use moneymarket::pause::{
+    assert_not_paused, guardian_to_raw, read_pause_status, update_pause_status_with_market,
+    Operation,
+};
+use moneymarket::tokens::{collateral_to_human, collateral_to_raw};
+use moneymarket::version::{assert_migrate_version, get_contract_version, set_contract_version};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-custody";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Names of the bAsset specific custody contracts this contract replaces
+pub const BLUNA_CONTRACT_NAME: &str = "crates.io:moneymarket-custody-bluna";
+pub const BETH_CONTRACT_NAME: &str = "crates.io:moneymarket-custody-beth";
+
+pub const CLAIM_REWARDS_OPERATION: u64 = 1u64;
+pub const SWAP_TO_STABLE_OPERATION: u64 = 2u64;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+        overseer_contract: deps.api.addr_canonicalize(&msg.overseer_contract)?,
+        collateral_token: collateral_to_raw(deps.api, &msg.collateral_token)?,
+        market_contract: deps.api.addr_canonicalize(&msg.market_contract)?,
+        reward_contract: deps.api.addr_canonicalize(&msg.reward_contract)?,
+        liquidation_contract: deps.api.addr_canonicalize(&msg.liquidation_contract)?,
+        stable_denom: msg.stable_denom,
+        basset_info: msg.basset_info,
+        reward_adapter: msg.reward_adapter,
+        guardian: None,
+    };
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if let Some(operation) = pausable_operation(&msg) {
+        assert_not_paused(deps.storage, operation)?;
+    }
+
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::UpdateConfig {
+            liquidation_contract,
+            guardian,
+        } => {
+            let api = deps.api;
+            update_config(
+                deps,
+                info,
+                optional_addr_validate(api, liquidation_contract)?,
+                guardian,
+            )
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::Pause { operations } => update_pause(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => update_pause(deps, info, operations, false),
+        ExecuteMsg::LockCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            lock_collateral(deps, info, borrower_addr, amount)
+        }
+        ExecuteMsg::UnlockCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            unlock_collateral(deps, info, borrower_addr, amount)
+        }
+        ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, env, info),
+        ExecuteMsg::DepositCollateral {} => deposit_native_collateral(deps, info),
+        ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
+        ExecuteMsg::LiquidateCollateral {
+            liquidator,
+            borrower,
+            amount,
+        } => {
+            let liquidator_addr = deps.api.addr_validate(&liquidator)?;
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+        }
+        ExecuteMsg::SwapCollateral {
+            borrower,
+            amount,
+            swap_contract,
+            swap_msg,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            let swap_contract_addr = deps.api.addr_validate(&swap_contract)?;
+            swap_collateral(
+                deps,
+                info,
+                borrower_addr,
+                amount,
+                swap_contract_addr,
+                swap_msg,
+            )
+        }
+    }
+}
+
+/// Operation of the message the guardian can pause
+fn pausable_operation(msg: &ExecuteMsg) -> Option<Operation> {
+    match msg {
+        ExecuteMsg::DepositCollateral {} => Some(Operation::Deposit),
+        ExecuteMsg::WithdrawCollateral { .. } => Some(Operation::Redeem),
+        ExecuteMsg::LockCollateral { .. } => Some(Operation::Lock),
+        ExecuteMsg::UnlockCollateral { .. } => Some(Operation::Unlock),
+        ExecuteMsg::LiquidateCollateral { .. } => Some(Operation::Liquidate),
+        ExecuteMsg::SwapCollateral { .. } => Some(Operation::Repay),
+        _ => None,
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    match msg.id {
+        // ClaimRewards callback
+        CLAIM_REWARDS_OPERATION => swap_to_stable_denom(deps, env),
+        // Swap to stable callback
+        SWAP_TO_STABLE_OPERATION => distribute_hook(deps, env),
+        _ => Err(ContractError::InvalidReplyId {}),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let contract_addr = info.sender;
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::DepositCollateral { borrower }) => {
+            assert_not_paused(deps.storage, Operation::Deposit)?;
+
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.collateral_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let borrower = deps
+                .api
+                .addr_validate(&borrower.unwrap_or(cw20_msg.sender))?;
+            deposit_collateral(deps, borrower, cw20_msg.amount.into())
+        }
+        _ => Err(ContractError::MissingDepositCollateralHook {}),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidation_contract: Option<Addr>,
+    guardian: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(liquidation_contract) = liquidation_contract {
+        config.liquidation_contract = deps.api.addr_canonicalize(liquidation_contract.as_str())?;
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = guardian_to_raw(deps.api, &guardian)?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<Operation>,
+    paused: bool,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner && Some(sender_raw) != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?.to_string();
+    update_pause_status_with_market(deps, market_contract, &operations, paused)?;
+
+    let operation_logs: Vec<String> = operations.iter().map(|o| o.to_string()).collect();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", if paused { "pause" } else { "unpause" }),
+        attr("operations", operation_logs.join(",")),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // bLuna and bETH custody deployments migrate with the adapter of their collateral
+    let legacy_adapter = match get_contract_version(deps.storage).ok() {
+        Some(stored) if stored.contract == BLUNA_CONTRACT_NAME => Some(RewardAdapter::BLuna {}),
+        Some(stored) if stored.contract == BETH_CONTRACT_NAME => Some(RewardAdapter::BEth {}),
+        Some(_) => {
+            assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+            None
+        }
+        None => Some(msg.reward_adapter.ok_or_else(|| {
+            StdError::generic_err("Reward adapter of the legacy custody contract is required")
+        })?),
+    };
+
+    if let Some(reward_adapter) = legacy_adapter {
+        migrate_state(deps.storage, reward_adapter)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PauseStatus {} => to_binary(&read_pause_status(deps.storage)?),
+        QueryMsg::Borrower { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_borrower(deps, addr)?)
+        }
+        QueryMsg::Borrowers { start_after, limit } => to_binary(&query_borrowers(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        collateral_token: collateral_to_human(deps.api, &config.collateral_token)?,
+        overseer_contract: deps
+            .api
+            .addr_humanize(&config.overseer_contract)?
+            .to_string(),
+        market_contract: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+        reward_contract: deps.api.addr_humanize(&config.reward_contract)?.to_string(),
+        liquidation_contract: deps
+            .api
+            .addr_humanize(&config.liquidation_contract)?
+            .to_string(),
+        stable_denom: config.stable_denom,
+        basset_info: config.basset_info,
+        reward_adapter: config.reward_adapter,
+        guardian: match config.guardian {
+            Some(guardian) => Some(deps.api.addr_humanize(&guardian)?.to_string()),
+            None => None,
+        },
+    })
+}
@@ -6,10 +6,15 @@ use cosmwasm_std::{
 
 use crate::contract::{CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION};
 use crate::error::ContractError;
-use crate::external::handle::{RewardContractExecuteMsg, RewardContractQueryMsg};
-use crate::state::{read_config, BLunaAccruedRewardsResponse, Config};
+use crate::external::handle::{
+    BETHAccruedRewardsResponse, BLunaAccruedRewardsResponse, RewardContractExecuteMsg,
+    RewardContractQueryMsg,
+};
+use crate::state::{read_config, Config};
 
+use moneymarket::custody::RewardAdapter;
 use moneymarket::querier::{deduct_tax, query_all_balances, query_balance};
+use moneymarket::tokens::native_denom;
 use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
 
 // REWARD_THRESHOLD
@@ -30,11 +35,19 @@ pub fn distribute_rewards(
         return Err(ContractError::Unauthorized {});
     }
 
+    if config.reward_adapter == (RewardAdapter::None {}) {
+        return Ok(Response::default());
+    }
+
     let contract_addr = env.contract.address;
     let reward_contract = deps.api.addr_humanize(&config.reward_contract)?;
 
-    let accrued_rewards =
-        get_accrued_rewards(deps.as_ref(), reward_contract.clone(), contract_addr)?;
+    let accrued_rewards = get_accrued_rewards(
+        deps.as_ref(),
+        &config.reward_adapter,
+        reward_contract.clone(),
+        contract_addr,
+    )?;
     if accrued_rewards < REWARDS_THRESHOLD {
         return Ok(Response::default());
     }
@@ -98,11 +111,14 @@ pub fn swap_to_stable_denom(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
+    // native collateral is never swapped
+    let collateral_denom = native_denom(&config.collateral_token);
+
     let contract_addr = env.contract.address;
     let balances: Vec<Coin> = query_all_balances(deps.as_ref(), contract_addr)?;
     let mut messages: Vec<SubMsg<TerraMsgWrapper>> = balances
         .iter()
-        .filter(|x| x.denom != config.stable_denom)
+        .filter(|x| x.denom != config.stable_denom && Some(&x.denom) != collateral_denom.as_ref())
         .map(|coin: &Coin| SubMsg::new(create_swap_msg(coin.clone(), config.stable_denom.clone())))
         .collect();
 
@@ -116,16 +132,26 @@ pub fn swap_to_stable_denom(
 
 pub(crate) fn get_accrued_rewards(
     deps: Deps,
+    reward_adapter: &RewardAdapter,
     reward_contract_addr: Addr,
     contract_addr: Addr,
 ) -> StdResult<Uint128> {
-    let rewards: BLunaAccruedRewardsResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: reward_contract_addr.to_string(),
-            msg: to_binary(&RewardContractQueryMsg::AccruedRewards {
-                address: contract_addr.to_string(),
-            })?,
-        }))?;
-
-    Ok(rewards.rewards)
+    let query = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: reward_contract_addr.to_string(),
+        msg: to_binary(&RewardContractQueryMsg::AccruedRewards {
+            address: contract_addr.to_string(),
+        })?,
+    });
+
+    match reward_adapter {
+        RewardAdapter::BLuna {} => {
+            let rewards: BLunaAccruedRewardsResponse = deps.querier.query(&query)?;
+            Ok(rewards.rewards)
+        }
+        RewardAdapter::BEth {} => {
+            let rewards: BETHAccruedRewardsResponse = deps.querier.query(&query)?;
+            Ok(rewards.rewards)
+        }
+        RewardAdapter::None {} => Ok(Uint128::zero()),
+    }
 }
@@ -24,9 +24,18 @@ pub enum ContractError {
     #[error("Invalid request: \"deposit collateral\" message not included in request")]
     MissingDepositCollateralHook {},
 
+    #[error("Collateral is not a native token; deposit it through its cw20 contract")]
+    NotNativeCollateral {},
+
+    #[error("Swap amount cannot exceed locked amount: {0}")]
+    SwapAmountExceedsLocked(u128),
+
     #[error("Unlock amount cannot exceed locked amount: {0}")]
     UnlockAmountExceedsLocked(u128),
 
     #[error("Withdraw amount cannot exceed the user's spendable amount: {0}")]
     WithdrawAmountExceedsSpendable(u128),
+
+    #[error("Deposit amount must be greater than 0 {0}")]
+    ZeroDeposit(String),
 }
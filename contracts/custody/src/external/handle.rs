@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cosmwasm_std::Uint128;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RewardContractExecuteMsg {
@@ -14,3 +16,15 @@ pub enum RewardContractQueryMsg {
     /// Request bAsset reward amount
     AccruedRewards { address: String },
 }
+
+//BLunaAccruedRewardsResponse the struct that shows the result of accrued_rewards query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BLunaAccruedRewardsResponse {
+    pub rewards: Uint128,
+}
+
+//BETHAccruedRewardsResponse the struct that shows the result of accrued_rewards query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BETHAccruedRewardsResponse {
+    pub rewards: Uint128,
+}
@@ -2,15 +2,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage, Uint128};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
-use moneymarket::custody::{BAssetInfo, BorrowerResponse};
-
-//BETHAccruedRewardsResponse the struct that shows the result of accrued_rewards query
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
-pub struct BETHAccruedRewardsResponse {
-    pub rewards: Uint128,
-}
+use moneymarket::custody::{BAssetInfo, BorrowerResponse, RewardAdapter};
 
 const KEY_CONFIG: &[u8] = b"config";
 const PREFIX_BORROWER: &[u8] = b"borrower";
@@ -25,6 +19,10 @@ pub struct Config {
     pub liquidation_contract: CanonicalAddr,
     pub stable_denom: String,
     pub basset_info: BAssetInfo,
+    pub reward_adapter: RewardAdapter,
+    /// Address allowed to pause operations besides the owner
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,6 +39,38 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub collateral_token: CanonicalAddr,
+    pub overseer_contract: CanonicalAddr,
+    pub market_contract: CanonicalAddr,
+    pub reward_contract: CanonicalAddr,
+    pub liquidation_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub basset_info: BAssetInfo,
+}
+
+/// Legacy bLuna and bETH custody configs have no reward adapter
+pub fn migrate_state(storage: &mut dyn Storage, reward_adapter: RewardAdapter) -> StdResult<()> {
+    let legacy_config: LegacyConfig = ReadonlySingleton::new(storage, KEY_CONFIG).load()?;
+    store_config(
+        storage,
+        &Config {
+            owner: legacy_config.owner,
+            collateral_token: legacy_config.collateral_token,
+            overseer_contract: legacy_config.overseer_contract,
+            market_contract: legacy_config.market_contract,
+            reward_contract: legacy_config.reward_contract,
+            liquidation_contract: legacy_config.liquidation_contract,
+            stable_denom: legacy_config.stable_denom,
+            basset_info: legacy_config.basset_info,
+            reward_adapter,
+            guardian: None,
+        },
+    )
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
@@ -1,23 +1,29 @@
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Api, Attribute, BankMsg, Coin, ContractResult, CosmosMsg,
-    Decimal, Reply, Response, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Api, Attribute, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, Reply, Response, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 
 use crate::contract::{
-    execute, instantiate, query, reply, CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION,
+    execute, instantiate, migrate, query, reply, BETH_CONTRACT_NAME, CLAIM_REWARDS_OPERATION,
+    CONTRACT_NAME, CONTRACT_VERSION, SWAP_TO_STABLE_OPERATION,
 };
 use crate::error::ContractError;
-use crate::external::handle::RewardContractExecuteMsg;
-use crate::state::{read_borrower_info, BLunaAccruedRewardsResponse};
+use crate::external::handle::{BLunaAccruedRewardsResponse, RewardContractExecuteMsg};
+use crate::state::{read_borrower_info, read_config, LegacyConfig};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_storage::singleton;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::custody::{
-    BAssetInfo, BorrowerResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BAssetInfo, BorrowerResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, QueryMsg, RewardAdapter,
 };
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::liquidation_queue::ExecuteMsg as LiquidationQueueExecuteMsg;
+use moneymarket::pause::{Operation, PauseStatus};
+use moneymarket::version::{get_contract_version, set_contract_version, ContractVersion};
 use terra_cosmwasm::create_swap_msg;
 
 #[test]
@@ -37,6 +43,7 @@ fn proper_initialization() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -53,6 +60,7 @@ fn proper_initialization() {
     assert_eq!("reward".to_string(), config_res.reward_contract);
     assert_eq!("liquidation".to_string(), config_res.liquidation_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
+    assert_eq!(RewardAdapter::BLuna {}, config_res.reward_adapter);
 }
 
 #[test]
@@ -72,6 +80,7 @@ fn update_config() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -80,11 +89,25 @@ fn update_config() {
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner2".to_string()),
         liquidation_contract: Some("liquidation2".to_string()),
+        guardian: Some("guardian".to_string()),
     };
     let info = mock_info("owner", &[]);
-    execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner2".to_string(),
+        expires_in: 100u64,
+    };
+    execute(deps.as_mut(), mock_env(), info, propose_msg).unwrap();
+    let info = mock_info("owner2", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
 
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
@@ -95,6 +118,7 @@ fn update_config() {
     assert_eq!("reward".to_string(), config_res.reward_contract);
     assert_eq!("liquidation2".to_string(), config_res.liquidation_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
+    assert_eq!(Some("guardian".to_string()), config_res.guardian);
 
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -102,6 +126,155 @@ fn update_config() {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    // an empty address removes the guardian
+    let msg = ExecuteMsg::UpdateConfig {
+        liquidation_contract: None,
+        guardian: Some("".to_string()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(None, config_res.guardian);
+}
+
+#[test]
+fn pause_operations() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "bluna".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "bluna".to_string(),
+            symbol: "bluna".to_string(),
+            decimals: 6,
+        },
+        reward_adapter: RewardAdapter::BLuna {},
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        liquidation_contract: None,
+        guardian: Some("guardian".to_string()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Deposit, Operation::Redeem],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause"),
+            attr("operations", "deposit,redeem")
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatus = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            deposit: true,
+            redeem: true,
+            ..PauseStatus::default()
+        }
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bluna", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: deposit")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::WithdrawCollateral { amount: None },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: redeem")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the owner can resume operations as well
+    let unpause_msg = ExecuteMsg::Unpause {
+        operations: vec![Operation::Deposit],
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        unpause_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("bluna", &[]), msg).unwrap();
+
+    // liquidations cannot be paused while the market lends
+    let pause_msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Liquidate],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        pause_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "Market borrow must be paused while repay or liquidate is paused"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_market_pause_status(PauseStatus {
+        borrow: true,
+        ..PauseStatus::default()
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        pause_msg,
+    )
+    .unwrap();
 }
 
 #[test]
@@ -121,6 +294,7 @@ fn deposit_collateral() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -129,7 +303,7 @@ fn deposit_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     // failed; cannot directly execute receive message
@@ -211,6 +385,26 @@ fn deposit_collateral() {
             spendable: Uint256::from(200u128),
         }
     );
+
+    // Deposit for another borrower
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "overseer".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {
+            borrower: Some("addr0001".to_string()),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("bluna", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0001"),
+            attr("amount", "100"),
+        ]
+    );
 }
 
 #[test]
@@ -230,6 +424,7 @@ fn withdraw_collateral() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -238,7 +433,7 @@ fn withdraw_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("bluna", &[]);
@@ -367,6 +562,7 @@ fn lock_collateral() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -375,7 +571,7 @@ fn lock_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("bluna", &[]);
@@ -569,6 +765,7 @@ fn distribute_rewards() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -607,6 +804,88 @@ fn distribute_rewards() {
     );
 }
 
+#[test]
+fn distribute_rewards_with_reward_adapters() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+        reward_adapter: RewardAdapter::BEth {},
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+    deps.querier
+        .set_accrued_rewards(BLunaAccruedRewardsResponse {
+            rewards: Uint128::new(10000000),
+        });
+
+    // bETH rewards are claimed like bLuna rewards
+    let info = mock_info("overseer", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::DistributeRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            CLAIM_REWARDS_OPERATION
+        )]
+    );
+
+    // collateral without rewards never queries the reward contract
+    let mut deps = mock_dependencies(&[]);
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        InstantiateMsg {
+            reward_adapter: RewardAdapter::None {},
+            ..msg
+        },
+    )
+    .unwrap();
+
+    deps.querier
+        .set_accrued_rewards(BLunaAccruedRewardsResponse {
+            rewards: Uint128::new(10000000),
+        });
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DistributeRewards {},
+    )
+    .unwrap();
+    assert_eq!(res, Response::default());
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(RewardAdapter::None {}, config_res.reward_adapter);
+}
+
 #[test]
 fn distribute_hook() {
     let mut deps = mock_dependencies(&[Coin {
@@ -637,6 +916,7 @@ fn distribute_hook() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -691,6 +971,7 @@ fn distribution_hook_zero_rewards() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -748,6 +1029,7 @@ fn swap_to_stable_denom() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -804,6 +1086,7 @@ fn liquidate_collateral() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -812,7 +1095,7 @@ fn liquidate_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("bluna", &[]);
@@ -895,6 +1178,300 @@ fn liquidate_collateral() {
     );
 }
 
+#[test]
+fn swap_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "bluna".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "bluna".to_string(),
+            symbol: "bluna".to_string(),
+            decimals: 6,
+        },
+        reward_adapter: RewardAdapter::BLuna {},
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+    });
+
+    let info = mock_info("bluna", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "100"),
+        ]
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+    };
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "50"),
+        ]
+    );
+
+    let msg = ExecuteMsg::SwapCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(100u64),
+        swap_contract: "pair".to_string(),
+        swap_msg: Binary::from(b"swap"),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::SwapAmountExceedsLocked(50)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SwapCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        swap_contract: "pair".to_string(),
+        swap_msg: Binary::from(b"swap"),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_collateral"),
+            attr("borrower", "addr0000"),
+            attr("swap_contract", "pair"),
+            attr("amount", "10"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "pair".to_string(),
+                amount: Uint128::from(10u128),
+                msg: Binary::from(b"swap"),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn native_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
+    let instantiate_msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: ibc_denom.to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "atom".to_string(),
+            symbol: "ATOM".to_string(),
+            decimals: 6,
+        },
+        reward_adapter: RewardAdapter::None {},
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg.clone()).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(ibc_denom.to_string(), config_res.collateral_token);
+
+    // native collateral cannot be deposited through a cw20 hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("ibctoken", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositCollateral {},
+    );
+    match res {
+        Err(ContractError::ZeroDeposit(denom)) => assert_eq!(denom, ibc_denom),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: ibc_denom.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositCollateral {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "100"),
+        ]
+    );
+
+    let msg = ExecuteMsg::WithdrawCollateral {
+        amount: Some(Uint256::from(10u64)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: ibc_denom.to_string(),
+                amount: Uint128::from(10u128),
+            }],
+        }))]
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+
+    // native collateral is sent along with the bid execution
+    let msg = ExecuteMsg::LiquidateCollateral {
+        liquidator: "liquidator".to_string(),
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidation".to_string(),
+            funds: vec![Coin {
+                denom: ibc_denom.to_string(),
+                amount: Uint128::from(10u128),
+            }],
+            msg: to_binary(&LiquidationQueueExecuteMsg::ExecuteBid {
+                liquidator: "liquidator".to_string(),
+                fee_address: Some("overseer".to_string()),
+                repay_address: Some("market".to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::SwapCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        swap_contract: "pair".to_string(),
+        swap_msg: Binary::from(b"{}".to_vec()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair".to_string(),
+            funds: vec![Coin {
+                denom: ibc_denom.to_string(),
+                amount: Uint128::from(10u128),
+            }],
+            msg: Binary::from(b"{}".to_vec()),
+        }))]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(70u64),
+            spendable: Uint256::from(40u64),
+        }
+    );
+
+    // cw20 collateral cannot be deposited with funds
+    let mut deps = mock_dependencies(&[]);
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        InstantiateMsg {
+            collateral_token: "bluna".to_string(),
+            ..instantiate_msg
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::DepositCollateral {},
+    );
+    match res {
+        Err(ContractError::NotNativeCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn proper_distribute_rewards_with_no_rewards() {
     let mut deps = mock_dependencies(&[Coin {
@@ -915,6 +1492,7 @@ fn proper_distribute_rewards_with_no_rewards() {
             symbol: "bluna".to_string(),
             decimals: 6,
         },
+        reward_adapter: RewardAdapter::BLuna {},
     };
 
     let info = mock_info("addr0000", &[]);
@@ -968,3 +1546,82 @@ fn proper_distribute_rewards_with_no_rewards() {
         ),]
     );
 }
+
+#[test]
+fn migrate_legacy_deployment() {
+    let mut deps = mock_dependencies(&[]);
+
+    // legacy bETH custody takes the bETH adapter
+    let legacy_config = LegacyConfig {
+        owner: deps.api.addr_canonicalize("owner").unwrap(),
+        collateral_token: deps.api.addr_canonicalize("beth").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        market_contract: deps.api.addr_canonicalize("market").unwrap(),
+        reward_contract: deps.api.addr_canonicalize("reward").unwrap(),
+        liquidation_contract: deps.api.addr_canonicalize("liquidation").unwrap(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    set_contract_version(deps.as_mut().storage, BETH_CONTRACT_NAME, "0.3.1").unwrap();
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            reward_adapter: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        read_config(deps.as_ref().storage).unwrap().reward_adapter,
+        RewardAdapter::BEth {}
+    );
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap(),
+        ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+        }
+    );
+
+    // legacy custody without version record needs its adapter
+    let mut deps = mock_dependencies(&[]);
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            reward_adapter: None,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Reward adapter of the legacy custody contract is required"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            reward_adapter: Some(RewardAdapter::BLuna {}),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        read_config(deps.as_ref().storage).unwrap().reward_adapter,
+        RewardAdapter::BLuna {}
+    );
+}
@@ -1,10 +0,0 @@
-pub mod collateral;
-pub mod contract;
-pub mod distribution;
-pub mod error;
-pub mod state;
-
-mod external;
-
-#[cfg(test)]
-mod testing;
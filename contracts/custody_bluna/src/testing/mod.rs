@@ -1,2 +0,0 @@
-pub mod mock_querier;
-pub mod tests;
@@ -1,10 +1,11 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
 use moneymarket::interest_model::{
-    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PendingChangesResponse, QueryMsg, StateResponse,
 };
 
 fn main() {
@@ -16,6 +17,13 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(PendingChangesResponse),
+        &out_dir,
+        "PendingChangesResponse",
+    );
+    export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(BorrowRateResponse), &out_dir);
 }
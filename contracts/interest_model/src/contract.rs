@@ -1,29 +1,60 @@
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    migrate_config, read_config, read_state, store_config, store_state, Config, State,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult,
+};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::{
-    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BorrowRateResponse, ConfigChange, ConfigResponse, ExecuteMsg, InstantiateMsg, InterestModel,
+    MigrateMsg, QueryMsg, StateResponse,
 };
+use moneymarket::ownership;
+use moneymarket::timelock;
+use moneymarket::version::{assert_migrate_version, is_older_version, set_contract_version};
+
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-interest-model";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    let market_contract = match msg.market_contract {
+        Some(market_contract) => Some(deps.api.addr_canonicalize(&market_contract)?),
+        None => None,
+    };
+    assert_valid_model(&msg.model, &market_contract)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     store_config(
         deps.storage,
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             base_rate: msg.base_rate,
             interest_multiplier: msg.interest_multiplier,
+            model: msg.model,
+            market_contract,
+        },
+    )?;
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+
+    store_state(
+        deps.storage,
+        &State {
+            interest_multiplier: msg.interest_multiplier,
+            utilization_ratio: Decimal256::zero(),
+            last_updated: env.block.height,
         },
     )?;
 
@@ -33,53 +64,307 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig {
-            owner,
+        ExecuteMsg::ScheduleChange { change, eta } => schedule_change(deps, env, info, change, eta),
+        ExecuteMsg::ExecuteChange { id } => execute_change(deps, env, id),
+        ExecuteMsg::CancelChange { id } => cancel_change(deps, info, id),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::ExecuteEpochOperations {
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        } => execute_epoch_operations(
+            deps,
+            env,
+            info,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        ),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    base_rate: Option<Decimal256>,
+    interest_multiplier: Option<Decimal256>,
+    model: Option<InterestModel>,
+    market_contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if let Some(base_rate) = base_rate {
+        config.base_rate = base_rate;
+    }
+
+    if let Some(market_contract) = market_contract {
+        config.market_contract = Some(deps.api.addr_canonicalize(market_contract.as_str())?);
+    }
+
+    // adaptive model restarts from the configured multiplier
+    // whenever the multiplier or the model is changed
+    if interest_multiplier.is_some() || model.is_some() {
+        if let Some(interest_multiplier) = interest_multiplier {
+            config.interest_multiplier = interest_multiplier;
+        }
+
+        if let Some(model) = model {
+            assert_valid_model(&model, &config.market_contract)?;
+            config.model = model;
+        }
+
+        let mut state: State = read_state(deps.storage)?;
+        state.interest_multiplier = config.interest_multiplier;
+        store_state(deps.storage, &state)?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::default())
+}
+
+pub fn schedule_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    change: ConfigChange,
+    eta: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let id = timelock::schedule_change(deps.storage, &env, change, eta)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "schedule_change"),
+        attr("id", id.to_string()),
+        attr("eta", eta.to_string()),
+    ]))
+}
+
+/// Anyone can apply a queued change once its ETA has passed
+pub fn execute_change(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let res = match timelock::take_ready_change(deps.storage, &env, id)? {
+        ConfigChange::UpdateConfig {
             base_rate,
             interest_multiplier,
+            model,
+            market_contract,
         } => {
             let api = deps.api;
             update_config(
                 deps,
-                info,
-                optional_addr_validate(api, owner)?,
                 base_rate,
                 interest_multiplier,
-            )
+                model,
+                optional_addr_validate(api, market_contract)?,
+            )?
+        }
+        ConfigChange::UpdateMinDelay { min_delay } => {
+            timelock::store_min_delay(deps.storage, min_delay)?;
+            Response::new()
         }
+    };
+
+    Ok(res.add_attributes(vec![
+        attr("action", "execute_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+pub fn cancel_change(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
+
+    timelock::cancel_change::<ConfigChange>(deps.storage, id)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_change"),
+        attr("id", id.to_string()),
+    ]))
 }
 
-pub fn update_config(
+pub fn propose_new_owner(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner: Option<Addr>,
-    base_rate: Option<Decimal256>,
-    interest_multiplier: Option<Decimal256>,
+    owner: String,
+    expires_in: u64,
 ) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(base_rate) = base_rate {
-        config.base_rate = base_rate;
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+/// The adaptive model is driven by the epoch operations of the market,
+/// so the market has to be known before the model is switched to it
+fn assert_valid_model(
+    model: &InterestModel,
+    market_contract: &Option<CanonicalAddr>,
+) -> Result<(), ContractError> {
+    match model {
+        InterestModel::Linear {} => Ok(()),
+        InterestModel::Kinked {
+            kink_utilization, ..
+        } => {
+            if kink_utilization.is_zero() || *kink_utilization > Decimal256::one() {
+                return Err(ContractError::InvalidKinkUtilization {});
+            }
+
+            Ok(())
+        }
+        InterestModel::Adaptive {
+            target_utilization,
+            min_rate,
+            max_rate,
+            ..
+        } => {
+            if target_utilization.is_zero() || *target_utilization > Decimal256::one() {
+                return Err(ContractError::InvalidTargetUtilization {});
+            }
+
+            if min_rate > max_rate {
+                return Err(ContractError::InvalidRateBounds {});
+            }
+
+            if market_contract.is_none() {
+                return Err(ContractError::MarketContractNotSet {});
+            }
+
+            Ok(())
+        }
     }
+}
+
+/// Only the adaptive model keeps state, other models
+/// accept the call without doing anything
+pub fn execute_epoch_operations(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let (target_utilization, min_rate, max_rate, integral_gain) = match config.model {
+        InterestModel::Adaptive {
+            target_utilization,
+            min_rate,
+            max_rate,
+            integral_gain,
+            ..
+        } => (target_utilization, min_rate, max_rate, integral_gain),
+        _ => return Ok(Response::default()),
+    };
 
-    if let Some(interest_multiplier) = interest_multiplier {
-        config.interest_multiplier = interest_multiplier;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if config.market_contract != Some(sender_raw) {
+        return Err(ContractError::Unauthorized {});
     }
 
-    store_config(deps.storage, &config)?;
+    let utilization_ratio =
+        compute_utilization_ratio(market_balance, total_liabilities, total_reserves);
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut interest_multiplier = if utilization_ratio > target_utilization {
+        state.interest_multiplier + (utilization_ratio - target_utilization) * integral_gain
+    } else {
+        let decrease = (target_utilization - utilization_ratio) * integral_gain;
+        if state.interest_multiplier > decrease {
+            state.interest_multiplier - decrease
+        } else {
+            Decimal256::zero()
+        }
+    };
+
+    // Prevent the multiplier from winding up beyond the
+    // rate bounds at the target utilization
+    let max_multiplier = if max_rate > config.base_rate {
+        (max_rate - config.base_rate) / target_utilization
+    } else {
+        Decimal256::zero()
+    };
+    let min_multiplier = if min_rate > config.base_rate {
+        (min_rate - config.base_rate) / target_utilization
+    } else {
+        Decimal256::zero()
+    };
+
+    if interest_multiplier > max_multiplier {
+        interest_multiplier = max_multiplier;
+    } else if interest_multiplier < min_multiplier {
+        interest_multiplier = min_multiplier;
+    }
+
+    state.interest_multiplier = interest_multiplier;
+    state.utilization_ratio = utilization_ratio;
+    state.last_updated = env.block.height;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "execute_epoch_operations"),
+        attr("utilization_ratio", utilization_ratio.to_string()),
+        attr("interest_multiplier", interest_multiplier.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if is_older_version(&stored_version, "0.4.0")? {
+        migrate_config(deps.storage)?;
+    }
+
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
@@ -87,6 +372,10 @@ pub fn update_config(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingChanges { start_after, limit } => to_binary(
+            &timelock::read_pending_changes::<ConfigChange>(deps.storage, start_after, limit)?,
+        ),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
         QueryMsg::BorrowRate {
             market_balance,
             total_liabilities,
@@ -106,30 +395,102 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         base_rate: state.base_rate,
         interest_multiplier: state.interest_multiplier,
+        model: state.model,
+        market_contract: if let Some(market_contract) = state.market_contract {
+            Some(deps.api.addr_humanize(&market_contract)?.to_string())
+        } else {
+            None
+        },
+        min_delay: timelock::read_min_delay(deps.storage)?,
     };
 
     Ok(resp)
 }
 
-fn query_borrow_rate(
-    deps: Deps,
+fn query_state(deps: Deps) -> StdResult<StateResponse> {
+    let state = read_state(deps.storage)?;
+    let resp = StateResponse {
+        interest_multiplier: state.interest_multiplier,
+        utilization_ratio: state.utilization_ratio,
+        last_updated: state.last_updated,
+    };
+
+    Ok(resp)
+}
+
+fn compute_utilization_ratio(
     market_balance: Uint256,
     total_liabilities: Decimal256,
     total_reserves: Decimal256,
-) -> StdResult<BorrowRateResponse> {
-    let config: Config = read_config(deps.storage)?;
-
+) -> Decimal256 {
     // ignore decimal parts
     let total_value_in_market =
         Decimal256::from_uint256(market_balance) + total_liabilities - total_reserves;
 
-    let utilization_ratio = if total_value_in_market.is_zero() {
+    if total_value_in_market.is_zero() {
         Decimal256::zero()
     } else {
         total_liabilities / total_value_in_market
+    }
+}
+
+fn query_borrow_rate(
+    deps: Deps,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<BorrowRateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let utilization_ratio =
+        compute_utilization_ratio(market_balance, total_liabilities, total_reserves);
+
+    let rate = match config.model {
+        InterestModel::Linear {} => {
+            utilization_ratio * config.interest_multiplier + config.base_rate
+        }
+        InterestModel::Kinked {
+            kink_utilization,
+            jump_multiplier,
+        } => {
+            if utilization_ratio <= kink_utilization {
+                utilization_ratio * config.interest_multiplier + config.base_rate
+            } else {
+                kink_utilization * config.interest_multiplier
+                    + (utilization_ratio - kink_utilization) * jump_multiplier
+                    + config.base_rate
+            }
+        }
+        InterestModel::Adaptive {
+            target_utilization,
+            min_rate,
+            max_rate,
+            proportional_gain,
+            ..
+        } => {
+            let state: State = read_state(deps.storage)?;
+            let rate = utilization_ratio * state.interest_multiplier + config.base_rate;
+
+            // proportional term reacts to the current utilization error
+            let rate = if utilization_ratio > target_utilization {
+                rate + (utilization_ratio - target_utilization) * proportional_gain
+            } else {
+                let decrease = (target_utilization - utilization_ratio) * proportional_gain;
+                if rate > decrease {
+                    rate - decrease
+                } else {
+                    Decimal256::zero()
+                }
+            };
+
+            if rate > max_rate {
+                max_rate
+            } else if rate < min_rate {
+                min_rate
+            } else {
+                rate
+            }
+        }
     };
 
-    Ok(BorrowRateResponse {
-        rate: utilization_ratio * config.interest_multiplier + config.base_rate,
-    })
+    Ok(BorrowRateResponse { rate })
 }
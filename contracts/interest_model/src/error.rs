@@ -11,4 +11,16 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Kink utilization must be between 0 and 1")]
+    InvalidKinkUtilization {},
+
+    #[error("Target utilization must be between 0 and 1")]
+    InvalidTargetUtilization {},
+
+    #[error("Min rate cannot be bigger than max rate")]
+    InvalidRateBounds {},
+
+    #[error("Adaptive model requires the market contract to be set")]
+    MarketContractNotSet {},
 }
@@ -5,13 +5,26 @@ use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
 use cosmwasm_storage::{singleton, singleton_read};
 
+use moneymarket::interest_model::InterestModel;
+
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
+    pub model: InterestModel,
+    pub market_contract: Option<CanonicalAddr>,
+}
+
+/// Adaptive model state, updated every epoch by the market
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub interest_multiplier: Decimal256,
+    pub utilization_ratio: Decimal256,
+    pub last_updated: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -21,3 +34,43 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
+    singleton_read(storage, KEY_STATE).load()
+}
+
+/// Config layout of 0.3.x deployments
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub base_rate: Decimal256,
+    pub interest_multiplier: Decimal256,
+}
+
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = singleton_read(storage, KEY_CONFIG).load()?;
+
+    store_config(
+        storage,
+        &Config {
+            owner: legacy_config.owner,
+            base_rate: legacy_config.base_rate,
+            interest_multiplier: legacy_config.interest_multiplier,
+            model: InterestModel::Linear {},
+            market_contract: None,
+        },
+    )?;
+
+    store_state(
+        storage,
+        &State {
+            interest_multiplier: legacy_config.interest_multiplier,
+            utilization_ratio: Decimal256::zero(),
+            last_updated: 0,
+        },
+    )
+}
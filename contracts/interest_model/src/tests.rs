@@ -1,11 +1,16 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, CONTRACT_VERSION};
 use crate::error::ContractError;
+use crate::state::{read_config, LegacyConfig};
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::from_binary;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, Api, StdError};
+use cosmwasm_storage::singleton;
 use moneymarket::interest_model::{
-    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BorrowRateResponse, ConfigChange, ConfigResponse, ExecuteMsg, InstantiateMsg, InterestModel,
+    MigrateMsg, PendingChangesResponse, QueryMsg, StateResponse,
 };
+use moneymarket::timelock::PendingChange;
+use moneymarket::version::get_contract_version;
 
 #[test]
 fn proper_initialization() {
@@ -15,6 +20,9 @@ fn proper_initialization() {
         owner: "owner0000".to_string(),
         base_rate: Decimal256::percent(10),
         interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Linear {},
+        market_contract: None,
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -29,6 +37,7 @@ fn proper_initialization() {
     assert_eq!("owner0000", value.owner.as_str());
     assert_eq!("0.1", &value.base_rate.to_string());
     assert_eq!("0.1", &value.interest_multiplier.to_string());
+    assert_eq!(InterestModel::Linear {}, value.model);
 
     let query_msg = QueryMsg::BorrowRate {
         market_balance: Uint256::from(1000000u128),
@@ -59,20 +68,62 @@ fn update_config() {
         owner: "owner0000".to_string(),
         base_rate: Decimal256::percent(10),
         interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Linear {},
+        market_contract: None,
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose a new owner, then drop the proposal
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
-        base_rate: None,
-        interest_multiplier: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: 100u64,
     };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::DropOwnershipProposal {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::DropOwnershipProposal {},
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Ownership proposal not found")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // propose again and claim the ownership
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
     assert_eq!(0, res.messages.len());
 
     // it worked, let's query the state
@@ -84,10 +135,14 @@ fn update_config() {
 
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        base_rate: Some(Decimal256::percent(1)),
-        interest_multiplier: Some(Decimal256::percent(1)),
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            base_rate: Some(Decimal256::percent(1)),
+            interest_multiplier: Some(Decimal256::percent(1)),
+            model: None,
+            market_contract: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -95,4 +150,578 @@ fn update_config() {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("Must return unauthorized error"),
     }
+
+    // the adaptive model needs the market, which can be set in the same change
+    let adaptive_change = |market_contract: Option<String>| ConfigChange::UpdateConfig {
+        base_rate: None,
+        interest_multiplier: None,
+        model: Some(InterestModel::Adaptive {
+            target_utilization: Decimal256::percent(80),
+            min_rate: Decimal256::percent(1),
+            max_rate: Decimal256::percent(50),
+            proportional_gain: Decimal256::percent(50),
+            integral_gain: Decimal256::one(),
+        }),
+        market_contract,
+    };
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    for (id, market_contract) in [(1u64, None), (2u64, Some("market0000".to_string()))] {
+        let msg = ExecuteMsg::ScheduleChange {
+            change: adaptive_change(market_contract),
+            eta: mock_env().block.time.seconds() + 1u64,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0001", &[]), msg).unwrap();
+        let res = execute(
+            deps.as_mut(),
+            delayed_env.clone(),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::ExecuteChange { id },
+        );
+        match (id, res) {
+            (1u64, Err(ContractError::MarketContractNotSet {})) => (),
+            (2u64, Ok(_)) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some("market0000".to_string()), value.market_contract);
+}
+
+#[test]
+fn timelocked_changes() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(10),
+        interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Linear {},
+        market_contract: None,
+        min_delay: 0u64,
+    };
+
+    // changes cannot skip the delay
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Minimum delay must be positive")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = InstantiateMsg {
+        min_delay: 86400u64,
+        ..msg
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+    let change = ConfigChange::UpdateConfig {
+        base_rate: Some(Decimal256::percent(20)),
+        interest_multiplier: None,
+        model: None,
+        market_contract: None,
+    };
+
+    // the ETA must leave borrowers the minimum delay
+    let msg = ExecuteMsg::ScheduleChange {
+        change: change.clone(),
+        eta: now + 86399u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "ETA must be at least 86400 seconds ahead")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ScheduleChange {
+        change: change.clone(),
+        eta: now + 86400u64,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "schedule_change"),
+            attr("id", "1"),
+            attr("eta", (now + 86400u64).to_string()),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingChanges {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending: PendingChangesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending.changes,
+        vec![PendingChange {
+            id: 1u64,
+            eta: now + 86400u64,
+            change,
+        }]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            format!("Change is not executable before {}", now + 86400u64)
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // anyone can apply the change after the ETA
+    env.block.time = env.block.time.plus_seconds(86400u64);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Decimal256::percent(20), value.base_rate);
+
+    // only the owner cancels queued changes
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateMinDelay { min_delay: 3600u64 },
+        eta: env.block.time.seconds() + 86400u64,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelChange { id: 2u64 },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::CancelChange { id: 2u64 },
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingChanges {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending: PendingChangesResponse = from_binary(&res).unwrap();
+    assert!(pending.changes.is_empty());
+
+    // the delay cannot be removed either
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateMinDelay { min_delay: 0u64 },
+        eta: env.block.time.seconds() + 86400u64,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(86400u64);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 3u64 },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Minimum delay must be positive")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn migrate_legacy_deployment() {
+    let mut deps = mock_dependencies(&[]);
+
+    // 0.3.x deployment without version record
+    let legacy_config = LegacyConfig {
+        owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+        base_rate: Decimal256::percent(10),
+        interest_multiplier: Decimal256::percent(10),
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    let _res = get_contract_version(deps.as_ref().storage).unwrap_err();
+    let _res = read_config(deps.as_ref().storage).unwrap_err();
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            min_delay: 86400u64,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        CONTRACT_VERSION.to_string()
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            base_rate: Decimal256::percent(10),
+            interest_multiplier: Decimal256::percent(10),
+            model: InterestModel::Linear {},
+            market_contract: None,
+            min_delay: 86400u64,
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let value: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(value.interest_multiplier, Decimal256::percent(10));
+}
+
+#[test]
+fn kinked_borrow_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    // kink utilization cannot exceed 1
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(2),
+        interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Kinked {
+            kink_utilization: Decimal256::percent(101),
+            jump_multiplier: Decimal256::percent(300),
+        },
+        market_contract: None,
+        min_delay: 1u64,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidKinkUtilization {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(2),
+        interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Kinked {
+            kink_utilization: Decimal256::percent(80),
+            jump_multiplier: Decimal256::percent(300),
+        },
+        market_contract: None,
+        min_delay: 1u64,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        InterestModel::Kinked {
+            kink_utilization: Decimal256::percent(80),
+            jump_multiplier: Decimal256::percent(300),
+        },
+        value.model
+    );
+
+    // below the kink, utilization_ratio = 0.5
+    // borrow_rate = 0.5 * 0.1 + 0.02
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(500000u128),
+        total_liabilities: Decimal256::from_uint256(500000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.07", &value.rate.to_string());
+
+    // above the kink, utilization_ratio = 0.9
+    // borrow_rate = 0.8 * 0.1 + 0.1 * 3 + 0.02
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.4", &value.rate.to_string());
+
+    // switch back to the linear model
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            base_rate: None,
+            interest_multiplier: None,
+            model: Some(InterestModel::Linear {}),
+            market_contract: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+
+    // borrow_rate = 0.9 * 0.1 + 0.02
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.11", &value.rate.to_string());
+}
+
+#[test]
+fn adaptive_borrow_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let model = InterestModel::Adaptive {
+        target_utilization: Decimal256::percent(80),
+        min_rate: Decimal256::percent(1),
+        max_rate: Decimal256::percent(50),
+        proportional_gain: Decimal256::percent(50),
+        integral_gain: Decimal256::one(),
+    };
+
+    // min rate cannot exceed max rate
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(2),
+        interest_multiplier: Decimal256::percent(10),
+        model: InterestModel::Adaptive {
+            target_utilization: Decimal256::percent(80),
+            min_rate: Decimal256::percent(60),
+            max_rate: Decimal256::percent(50),
+            proportional_gain: Decimal256::percent(50),
+            integral_gain: Decimal256::one(),
+        },
+        market_contract: None,
+        min_delay: 1u64,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidRateBounds {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the adaptive model is driven by the market
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(2),
+        interest_multiplier: Decimal256::percent(10),
+        model,
+        market_contract: None,
+        min_delay: 1u64,
+    };
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::MarketContractNotSet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = InstantiateMsg {
+        market_contract: Some("market0000".to_string()),
+        ..msg
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // at the target, utilization_ratio = 0.8
+    // borrow_rate = 0.8 * 0.1 + 0.02
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(200000u128),
+        total_liabilities: Decimal256::from_uint256(800000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.1", &value.rate.to_string());
+
+    // above the target, utilization_ratio = 0.9
+    // borrow_rate = 0.9 * 0.1 + 0.02 + 0.1 * 0.5
+    let high_utilization = ExecuteMsg::ExecuteEpochOperations {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRate {
+            market_balance: Uint256::from(100000u128),
+            total_liabilities: Decimal256::from_uint256(900000u128),
+            total_reserves: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.16", &value.rate.to_string());
+
+    // only the market runs the epoch operations
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        high_utilization.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("market0000", &[]);
+
+    // interest_multiplier = 0.1 + (0.9 - 0.8) * 1
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        high_utilization.clone(),
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let value: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        StateResponse {
+            interest_multiplier: Decimal256::percent(20),
+            utilization_ratio: Decimal256::percent(90),
+            last_updated: mock_env().block.height,
+        }
+    );
+
+    // borrow_rate = 0.8 * 0.2 + 0.02
+    let res = query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.18", &value.rate.to_string());
+
+    // interest_multiplier is capped to (0.5 - 0.02) / 0.8
+    for _ in 0..5 {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            high_utilization.clone(),
+        )
+        .unwrap();
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let value: StateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.6", &value.interest_multiplier.to_string());
+
+    // borrow_rate = 0.9 * 0.6 + 0.02 + 0.05, bounded by max_rate
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRate {
+            market_balance: Uint256::from(100000u128),
+            total_liabilities: Decimal256::from_uint256(900000u128),
+            total_reserves: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.5", &value.rate.to_string());
+
+    // empty market pulls the multiplier down to zero
+    let low_utilization = ExecuteMsg::ExecuteEpochOperations {
+        market_balance: Uint256::from(1000000u128),
+        total_liabilities: Decimal256::zero(),
+        total_reserves: Decimal256::zero(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, low_utilization).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let value: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(Decimal256::zero(), value.interest_multiplier);
+
+    // borrow_rate = 0.02 - 0.8 * 0.5, bounded by min_rate
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRate {
+            market_balance: Uint256::from(1000000u128),
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.01", &value.rate.to_string());
+
+    // non adaptive models ignore epoch operations
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            base_rate: None,
+            interest_multiplier: None,
+            model: Some(InterestModel::Linear {}),
+            market_contract: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        high_utilization,
+    )
+    .unwrap();
+    assert_eq!(0, res.attributes.len());
 }
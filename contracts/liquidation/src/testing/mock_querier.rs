@@ -146,6 +146,7 @@ impl WasmMockQuerier {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                frozen: false,
                             })))
                         }
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -5,7 +5,8 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
+    WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation::{
@@ -70,20 +71,45 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose a new owner
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
-        oracle_contract: None,
-        stable_denom: None,
-        safe_ratio: None,
-        bid_fee: None,
-        max_premium_rate: None,
-        liquidation_threshold: None,
-        price_timeframe: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: 100u64,
     };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("owner", "owner0001"),
+        ]
+    );
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // expired proposals can not be claimed
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(101u64);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("owner0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Ownership proposal expired")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
     assert_eq!(0, res.messages.len());
 
     // it worked, let's query the state
@@ -106,7 +132,6 @@ fn update_config() {
     // Update left items
     let info = mock_info("owner0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         stable_denom: Some("ukrw".to_string()),
         safe_ratio: Some(Decimal256::percent(15)),
@@ -139,7 +164,6 @@ fn update_config() {
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         stable_denom: Some("ukrw".to_string()),
         safe_ratio: Some(Decimal256::percent(1)),
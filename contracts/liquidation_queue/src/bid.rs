@@ -1,5 +1,5 @@
 use crate::asserts::{assert_activate_status, assert_withdraw_amount};
-use crate::querier::query_collateral_whitelist_info;
+use crate::querier::{query_collateral_whitelist_info, query_overseer_price_mode};
 use crate::state::{
     pop_bid_idx, read_bid, read_bid_pool, read_bids_by_user, read_collateral_info, read_config,
     read_epoch_scale_sum, read_or_create_bid_pool, read_total_bids, remove_bid, store_bid,
@@ -13,7 +13,8 @@ use cosmwasm_std::{
 };
 use cw20::Cw20ExecuteMsg;
 use moneymarket::oracle::PriceResponse;
-use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use moneymarket::querier::{deduct_tax, query_price_with_mode, TimeConstraints};
+use moneymarket::tokens::{collateral_to_raw, native_denom};
 
 /// Stable asset is submitted to create a bid record. If available bids for the collateral is under
 /// the threshold, the bid is activated. Bids are not used for liquidations until activated
@@ -25,7 +26,7 @@ pub fn submit_bid(
     premium_slot: u8,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
-    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw: CanonicalAddr = collateral_to_raw(deps.api, &collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
     let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
@@ -109,7 +110,7 @@ pub fn activate_bids(
     bids_idx: Option<Vec<Uint128>>,
 ) -> StdResult<Response> {
     let sender_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw: CanonicalAddr = collateral_to_raw(deps.api, &collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
     let mut available_bids: Uint256 =
@@ -300,7 +301,7 @@ pub fn execute_liquidation(
     amount: Uint256,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
     let available_bids: Uint256 = read_total_bids(deps.storage, &collateral_token_raw)?;
@@ -319,12 +320,14 @@ pub fn execute_liquidation(
         ));
     }
 
+    let price_mode = query_overseer_price_mode(&deps.querier, overseer.to_string())?;
     let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
-    let price: PriceResponse = query_price(
+    let price: PriceResponse = query_price_with_mode(
         deps.as_ref(),
         oracle_contract,
         collateral_token.to_string(),
         config.stable_denom.clone(),
+        &price_mode,
         Some(TimeConstraints {
             block_time: env.block.time.seconds(),
             valid_timeframe: config.price_timeframe,
@@ -439,7 +442,7 @@ pub fn claim_liquidations(
     bids_idx: Option<Vec<Uint128>>,
 ) -> StdResult<Response> {
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
 
     let bids: Vec<Bid> = if let Some(bids_idx) = bids_idx {
         bids_idx
@@ -511,14 +514,23 @@ pub fn claim_liquidations(
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if !claim_amount.is_zero() {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: collateral_token.clone(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: claim_amount.into(),
-            })?,
-        }));
+        messages.push(match native_denom(&collateral_token_raw) {
+            Some(denom) => CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom,
+                    amount: claim_amount.into(),
+                }],
+            }),
+            None => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: claim_amount.into(),
+                })?,
+            }),
+        });
     }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
@@ -3,21 +3,36 @@ use cosmwasm_std::entry_point;
 
 use crate::asserts::{assert_fees, assert_max_slot, assert_max_slot_premium};
 use crate::bid::{activate_bids, claim_liquidations, execute_liquidation, retract_bid, submit_bid};
-use crate::querier::query_collateral_whitelist_info;
+use crate::querier::{query_collateral_whitelist_info, query_overseer_market_contract};
 use crate::query::{
     query_bid, query_bid_pool, query_bid_pools, query_bids_by_user, query_collateral_info,
     query_config, query_liquidation_amount,
 };
 use crate::state::{
-    read_collateral_info, read_config, store_collateral_info, store_config, CollateralInfo, Config,
+    migrate_config, read_collateral_info, read_config, store_collateral_info, store_config,
+    CollateralInfo, Config,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    attr, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
 };
 use cw20::Cw20ReceiveMsg;
-use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::liquidation_queue::{
+    ConfigChange, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+};
+use moneymarket::ownership;
+use moneymarket::pause::{
+    assert_not_paused, guardian_to_raw, read_pause_status, update_pause_status_with_market,
+    Operation,
+};
+use moneymarket::timelock;
+use moneymarket::tokens::collateral_to_raw;
+use moneymarket::version::{assert_migrate_version, is_older_version, set_contract_version};
+
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-liquidation-queue";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -26,6 +41,8 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     assert_fees(msg.liquidator_fee + msg.bid_fee)?;
 
     store_config(
@@ -41,39 +58,33 @@ pub fn instantiate(
             price_timeframe: msg.price_timeframe,
             waiting_period: msg.waiting_period,
             overseer: deps.api.addr_canonicalize(&msg.overseer)?,
+            guardian: None,
         },
     )?;
 
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+
     Ok(Response::new())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    if let Some(operation) = pausable_operation(&msg) {
+        assert_not_paused(deps.storage, operation)?;
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
-        ExecuteMsg::UpdateConfig {
-            owner,
-            oracle_contract,
-            safe_ratio,
-            bid_fee,
-            liquidator_fee,
-            liquidation_threshold,
-            price_timeframe,
-            waiting_period,
-            overseer,
-        } => update_config(
-            deps,
-            info,
-            owner,
-            oracle_contract,
-            safe_ratio,
-            bid_fee,
-            liquidator_fee,
-            liquidation_threshold,
-            price_timeframe,
-            waiting_period,
-            overseer,
-        ),
+        ExecuteMsg::ScheduleChange { change, eta } => schedule_change(deps, env, info, change, eta),
+        ExecuteMsg::ExecuteChange { id } => execute_change(deps, env, id),
+        ExecuteMsg::CancelChange { id } => cancel_change(deps, info, id),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::Pause { operations } => update_pause(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => update_pause(deps, info, operations, false),
         ExecuteMsg::WhitelistCollateral {
             collateral_token,
             bid_threshold,
@@ -105,6 +116,45 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             collateral_token,
             bids_idx,
         } => claim_liquidations(deps, env, info, collateral_token, bids_idx),
+        ExecuteMsg::ExecuteBid {
+            liquidator,
+            repay_address,
+            fee_address,
+        } => {
+            // only one native collateral can be liquidated at once
+            let collateral = match info.funds.as_slice() {
+                [collateral] => collateral,
+                _ => {
+                    return Err(StdError::generic_err(
+                        "Must send exactly one native collateral",
+                    ))
+                }
+            };
+
+            let sender = info.sender.to_string();
+            let repay_address = repay_address.unwrap_or_else(|| sender.clone());
+            let fee_address = fee_address.unwrap_or_else(|| sender.clone());
+
+            execute_liquidation(
+                deps,
+                env,
+                sender,
+                liquidator,
+                repay_address,
+                fee_address,
+                collateral.denom.to_string(),
+                collateral.amount.into(),
+            )
+        }
+    }
+}
+
+/// Operation of the message the guardian can pause
+fn pausable_operation(msg: &ExecuteMsg) -> Option<Operation> {
+    match msg {
+        ExecuteMsg::SubmitBid { .. } => Some(Operation::SubmitBid),
+        ExecuteMsg::ExecuteBid { .. } => Some(Operation::Liquidate),
+        _ => None,
     }
 }
 
@@ -121,6 +171,8 @@ pub fn receive_cw20(
             repay_address,
             fee_address,
         } => {
+            assert_not_paused(deps.storage, Operation::Liquidate)?;
+
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
             let fee_address = fee_address.unwrap_or_else(|| cw20_msg.sender.clone());
@@ -142,8 +194,6 @@ pub fn receive_cw20(
 #[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
-    info: MessageInfo,
-    owner: Option<String>,
     oracle_contract: Option<String>,
     safe_ratio: Option<Decimal256>,
     bid_fee: Option<Decimal256>,
@@ -152,15 +202,9 @@ pub fn update_config(
     price_timeframe: Option<u64>,
     waiting_period: Option<u64>,
     overseer: Option<String>,
+    guardian: Option<String>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
-
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
-    }
 
     if let Some(oracle_contract) = oracle_contract {
         config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract)?;
@@ -196,10 +240,149 @@ pub fn update_config(
         config.overseer = deps.api.addr_canonicalize(&overseer)?;
     }
 
+    if let Some(guardian) = guardian {
+        config.guardian = guardian_to_raw(deps.api, &guardian)?;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new())
 }
 
+pub fn schedule_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    change: ConfigChange,
+    eta: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let id = timelock::schedule_change(deps.storage, &env, change, eta)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "schedule_change"),
+        attr("id", id.to_string()),
+        attr("eta", eta.to_string()),
+    ]))
+}
+
+/// Anyone can apply a queued change once its ETA has passed
+pub fn execute_change(deps: DepsMut, env: Env, id: u64) -> StdResult<Response> {
+    let res = match timelock::take_ready_change(deps.storage, &env, id)? {
+        ConfigChange::UpdateConfig {
+            oracle_contract,
+            safe_ratio,
+            bid_fee,
+            liquidator_fee,
+            liquidation_threshold,
+            price_timeframe,
+            waiting_period,
+            overseer,
+            guardian,
+        } => update_config(
+            deps,
+            oracle_contract,
+            safe_ratio,
+            bid_fee,
+            liquidator_fee,
+            liquidation_threshold,
+            price_timeframe,
+            waiting_period,
+            overseer,
+            guardian,
+        )?,
+        ConfigChange::UpdateMinDelay { min_delay } => {
+            timelock::store_min_delay(deps.storage, min_delay)?;
+            Response::new()
+        }
+    };
+
+    Ok(res.add_attributes(vec![
+        attr("action", "execute_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+pub fn cancel_change(deps: DepsMut, info: MessageInfo, id: u64) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    timelock::cancel_change::<ConfigChange>(deps.storage, id)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<Operation>,
+    paused: bool,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner && Some(sender_raw) != config.guardian {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let market_contract = query_overseer_market_contract(
+        &deps.querier,
+        deps.api.addr_humanize(&config.overseer)?.to_string(),
+    )?;
+    update_pause_status_with_market(deps, market_contract, &operations, paused)?;
+
+    let operation_logs: Vec<String> = operations.iter().map(|o| o.to_string()).collect();
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("operations", operation_logs.join(",")))
+}
+
 pub fn whitelist_collateral(
     deps: DepsMut,
     info: MessageInfo,
@@ -209,7 +392,7 @@ pub fn whitelist_collateral(
     premium_rate_per_slot: Decimal256,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
         return Err(StdError::generic_err("unauthorized"));
     }
@@ -253,7 +436,7 @@ pub fn update_collateral_info(
     max_slot: Option<u8>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
         return Err(StdError::generic_err("unauthorized"));
     }
@@ -279,10 +462,27 @@ pub fn update_collateral_info(
     Ok(Response::new().add_attribute("action", "update_collateral_info"))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if is_older_version(&stored_version, "0.4.0")? {
+        migrate_config(deps.storage)?;
+    }
+
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingChanges { start_after, limit } => to_binary(
+            &timelock::read_pending_changes::<ConfigChange>(deps.storage, start_after, limit)?,
+        ),
+        QueryMsg::PauseStatus {} => to_binary(&read_pause_status(deps.storage)?),
         QueryMsg::LiquidationAmount {
             borrow_amount,
             borrow_limit,
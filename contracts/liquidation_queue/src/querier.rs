@@ -1,6 +1,8 @@
 use cosmwasm_std::{to_binary, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
+use moneymarket::oracle::PriceMode;
 use moneymarket::overseer::{
-    QueryMsg as OverseerQueryMsg, WhitelistResponse, WhitelistResponseElem,
+    ConfigResponse as OverseerConfigResponse, QueryMsg as OverseerQueryMsg, WhitelistResponse,
+    WhitelistResponseElem,
 };
 
 pub fn query_collateral_whitelist_info(
@@ -20,3 +22,31 @@ pub fn query_collateral_whitelist_info(
 
     Ok(whitelist_res.elems[0].clone())
 }
+
+/// Liquidations value collaterals with the same oracle price mode as the overseer
+pub fn query_overseer_price_mode(
+    querier: &QuerierWrapper,
+    overseer: String,
+) -> StdResult<PriceMode> {
+    let config_res: OverseerConfigResponse =
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer,
+            msg: to_binary(&OverseerQueryMsg::Config {})?,
+        }))?;
+
+    Ok(config_res.price_mode)
+}
+
+/// Market whose loans are repaid by the liquidations
+pub fn query_overseer_market_contract(
+    querier: &QuerierWrapper,
+    overseer: String,
+) -> StdResult<String> {
+    let config_res: OverseerConfigResponse =
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer,
+            msg: to_binary(&OverseerQueryMsg::Config {})?,
+        }))?;
+
+    Ok(config_res.market_contract)
+}
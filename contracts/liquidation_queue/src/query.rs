@@ -11,7 +11,8 @@ use moneymarket::liquidation_queue::{
     ConfigResponse, LiquidationAmountResponse,
 };
 use moneymarket::querier::query_tax_rate_and_cap;
-use moneymarket::tokens::TokensHuman;
+use moneymarket::timelock;
+use moneymarket::tokens::{collateral_to_human, collateral_to_raw, TokensHuman};
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = read_config(deps.storage)?;
@@ -26,6 +27,11 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         price_timeframe: config.price_timeframe,
         waiting_period: config.waiting_period,
         overseer: deps.api.addr_humanize(&config.overseer)?.to_string(),
+        guardian: match config.guardian {
+            Some(guardian) => Some(deps.api.addr_humanize(&guardian)?.to_string()),
+            None => None,
+        },
+        min_delay: timelock::read_min_delay(deps.storage)?,
     };
 
     Ok(resp)
@@ -82,7 +88,7 @@ pub fn query_liquidation_amount(
     for (i, collateral) in collaterals.iter().enumerate() {
         let (price, weight, max_ltv) = (collateral_prices[i], collateral_weights[i], max_ltvs[i]);
 
-        let collateral_token_raw = deps.api.addr_canonicalize(&collateral.0)?;
+        let collateral_token_raw = collateral_to_raw(deps.api, &collateral.0)?;
         let collateral_info = read_collateral_info(deps.storage, &collateral_token_raw)?;
 
         // calculate borrow amount and limit portion
@@ -165,7 +171,7 @@ fn compute_collateral_weights(
 
     for (collateral, price) in collaterals.iter().zip(collateral_prices.iter()) {
         let collateral_available_bids =
-            read_total_bids(deps.storage, &deps.api.addr_canonicalize(&collateral.0)?)
+            read_total_bids(deps.storage, &collateral_to_raw(deps.api, &collateral.0)?)
                 .unwrap_or_default();
         let max_ltv = query_collateral_whitelist_info(
             &deps.querier,
@@ -212,7 +218,7 @@ pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
 
     Ok(BidResponse {
         idx: bid.idx,
-        collateral_token: deps.api.addr_humanize(&bid.collateral_token)?.to_string(),
+        collateral_token: collateral_to_human(deps.api, &bid.collateral_token)?,
         bidder: deps.api.addr_humanize(&bid.bidder)?.to_string(),
         amount: bid_amount,
         premium_slot: bid.premium_slot,
@@ -232,7 +238,7 @@ pub fn query_bids_by_user(
     start_after: Option<Uint128>,
     limit: Option<u8>,
 ) -> StdResult<BidsResponse> {
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     let bidder_raw = deps.api.addr_canonicalize(&bidder)?;
 
     let bids: Vec<BidResponse> = read_bids_by_user(
@@ -262,7 +268,7 @@ pub fn query_bids_by_user(
         };
         let res = BidResponse {
             idx: bid.idx,
-            collateral_token: deps.api.addr_humanize(&bid.collateral_token)?.to_string(),
+            collateral_token: collateral_to_human(deps.api, &bid.collateral_token)?,
             bidder: deps.api.addr_humanize(&bid.bidder)?.to_string(),
             amount: bid_amount,
             premium_slot: bid.premium_slot,
@@ -285,7 +291,7 @@ pub fn query_bid_pool(
     collateral_token: String,
     bid_slot: u8,
 ) -> StdResult<BidPoolResponse> {
-    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw: CanonicalAddr = collateral_to_raw(deps.api, &collateral_token)?;
     let bid_pool: BidPool = read_bid_pool(deps.storage, &collateral_token_raw, bid_slot)?;
 
     Ok(BidPoolResponse {
@@ -304,7 +310,7 @@ pub fn query_bid_pools(
     start_after: Option<u8>,
     limit: Option<u8>,
 ) -> StdResult<BidPoolsResponse> {
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
 
     let bid_pools: Vec<BidPoolResponse> =
         read_bid_pools(deps.storage, &collateral_token_raw, start_after, limit)?
@@ -326,12 +332,12 @@ pub fn query_collateral_info(
     deps: Deps,
     collateral_token: String,
 ) -> StdResult<CollateralInfoResponse> {
-    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
 
     Ok(CollateralInfoResponse {
-        collateral_token: deps.api.addr_humanize(&collateral_token_raw)?.to_string(),
+        collateral_token: collateral_to_human(deps.api, &collateral_token_raw)?,
         bid_threshold: collateral_info.bid_threshold,
         max_slot: collateral_info.max_slot,
         premium_rate_per_slot: collateral_info.premium_rate_per_slot,
@@ -30,6 +30,9 @@ pub struct Config {
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: CanonicalAddr,
+    /// Address allowed to pause operations besides the owner
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -40,6 +43,42 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+/// Config layout of deployments without version record
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub oracle_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub safe_ratio: Decimal256,
+    pub bid_fee: Decimal256,
+    pub liquidator_fee: Decimal256,
+    pub liquidation_threshold: Uint256,
+    pub price_timeframe: u64,
+    pub waiting_period: u64,
+    pub overseer: CanonicalAddr,
+}
+
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = singleton_read(storage, KEY_CONFIG).load()?;
+
+    store_config(
+        storage,
+        &Config {
+            owner: legacy_config.owner,
+            oracle_contract: legacy_config.oracle_contract,
+            stable_denom: legacy_config.stable_denom,
+            safe_ratio: legacy_config.safe_ratio,
+            bid_fee: legacy_config.bid_fee,
+            liquidator_fee: legacy_config.liquidator_fee,
+            liquidation_threshold: legacy_config.liquidation_threshold,
+            price_timeframe: legacy_config.price_timeframe,
+            waiting_period: legacy_config.waiting_period,
+            overseer: legacy_config.overseer,
+            guardian: None,
+        },
+    )
+}
+
 pub fn pop_bid_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
     let mut idx_store = singleton(storage, KEY_BID_IDX);
     let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
@@ -29,6 +29,7 @@ fn one_bidder_distribution() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -144,6 +145,7 @@ fn two_bidder_distribution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -330,6 +332,7 @@ fn two_bidder_distribution_big_numbers() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -516,6 +519,7 @@ fn one_user_two_bid_slots() {
         price_timeframe: 10u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -682,6 +686,7 @@ fn partial_withdraw_after_execution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -883,6 +888,7 @@ fn completely_empty_pool() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1061,6 +1067,7 @@ fn product_truncated_to_zero() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1183,6 +1190,7 @@ fn two_bidder_reward_distribution_common_slot() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     // 10 ust/col
@@ -1360,6 +1368,7 @@ fn two_bidder_distribution_multiple_common_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1675,6 +1684,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     // 2 ust/col
@@ -1859,6 +1869,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2114,6 +2125,7 @@ fn not_enough_bid_for_collateral() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2230,6 +2242,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     // 1000 ust/col
@@ -1,8 +1,10 @@
-use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
+use moneymarket::overseer::{
+    ConfigResponse as OverseerConfigResponse, SwapPath, WhitelistResponse, WhitelistResponseElem,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
@@ -10,7 +12,8 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
-use moneymarket::oracle::PriceResponse;
+use moneymarket::oracle::{PriceMode, PriceResponse};
+use moneymarket::pause::PauseStatus;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,11 +21,21 @@ use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrap
 pub enum QueryMsg {
     /// Query oracle price to oracle contract
     Price { base: String, quote: String },
+    /// Query oracle TWAP price to oracle contract
+    TwapPrice {
+        base: String,
+        quote: String,
+        window_seconds: u64,
+    },
+    /// Query config to overseer contract
+    Config {},
     Whitelist {
         collateral_token: Option<String>,
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Query pause status to market contract
+    PauseStatus {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -46,6 +59,8 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     oracle_price_querier: OraclePriceQuerier,
     collateral_querier: CollateralQuerier,
+    price_mode: PriceMode,
+    market_pause_status: PauseStatus,
 }
 
 #[derive(Clone, Default)]
@@ -99,6 +114,7 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
     oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    twap_price: HashMap<(String, String), (Decimal256, u64, u64)>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -106,6 +122,7 @@ impl OraclePriceQuerier {
     pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
         OraclePriceQuerier {
             oracle_price: oracle_price_to_map(oracle_price),
+            twap_price: HashMap::new(),
         }
     }
 }
@@ -177,6 +194,7 @@ impl WasmMockQuerier {
                                 rate: v.0,
                                 last_updated_base: v.1,
                                 last_updated_quote: v.2,
+                                frozen: false,
                             })))
                         }
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -185,6 +203,41 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::TwapPrice {
+                    base,
+                    quote,
+                    window_seconds: _,
+                } => match self.oracle_price_querier.twap_price.get(&(base, quote)) {
+                    Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                        rate: v.0,
+                        last_updated_base: v.1,
+                        last_updated_quote: v.2,
+                        frozen: false,
+                    }))),
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "No oracle twap price exists".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                },
+                QueryMsg::Config {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&OverseerConfigResponse {
+                        owner_addr: "owner0000".to_string(),
+                        oracle_contract: "oracle0000".to_string(),
+                        market_contract: "market0000".to_string(),
+                        liquidation_contract: MOCK_CONTRACT_ADDR.to_string(),
+                        collector_contract: "collector0000".to_string(),
+                        threshold_deposit_rate: Decimal256::zero(),
+                        target_deposit_rate: Decimal256::zero(),
+                        buffer_distribution_factor: Decimal256::zero(),
+                        anc_purchase_factor: Decimal256::zero(),
+                        stable_denom: "uusd".to_string(),
+                        epoch_period: 0u64,
+                        price_timeframe: 0u64,
+                        price_mode: self.price_mode.clone(),
+                        guardian: None,
+                        min_delay: 0u64,
+                    })))
+                }
                 QueryMsg::Whitelist {
                     collateral_token,
                     start_after: _,
@@ -201,8 +254,15 @@ impl WasmMockQuerier {
                                     name: "name".to_string(),
                                     symbol: "symbol".to_string(),
                                     max_ltv: *v,
+                                    liquidation_threshold: *v,
                                     custody_contract: "custody0000".to_string(),
                                     collateral_token: "token0000".to_string(),
+                                    swap_path: SwapPath::LiquidationQueue {},
+                                    max_total_locked: None,
+                                    max_borrower_locked: None,
+                                    total_locked: Uint256::zero(),
+                                    debt_ceiling: None,
+                                    isolated_debt: Uint256::zero(),
                                 }],
                             })))
                         }
@@ -212,6 +272,9 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::PauseStatus {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&self.market_pause_status)))
+                }
             },
             _ => self.base.handle_query(request),
         }
@@ -225,6 +288,8 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             collateral_querier: CollateralQuerier::default(),
+            price_mode: PriceMode::Spot {},
+            market_pause_status: PauseStatus::default(),
         }
     }
 
@@ -241,7 +306,20 @@ impl WasmMockQuerier {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn with_twap_price(&mut self, twap_price: &[(&(String, String), &(Decimal256, u64, u64))]) {
+        self.oracle_price_querier.twap_price = oracle_price_to_map(twap_price);
+    }
+
+    pub fn with_price_mode(&mut self, price_mode: PriceMode) {
+        self.price_mode = price_mode;
+    }
+
     pub fn with_collateral_max_ltv(&mut self, collaterals: &[(&String, &Decimal256)]) {
         self.collateral_querier = CollateralQuerier::new(collaterals);
     }
+
+    pub fn with_market_pause_status(&mut self, market_pause_status: PauseStatus) {
+        self.market_pause_status = market_pause_status;
+    }
 }
@@ -113,6 +113,7 @@ fn instantiate_and_whitelist(deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMo
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
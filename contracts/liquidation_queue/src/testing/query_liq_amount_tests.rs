@@ -29,6 +29,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -135,6 +136,7 @@ fn partial_one_collateral_one_slot() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -241,6 +243,7 @@ fn partial_one_collateral_one_slot_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -356,6 +359,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -478,6 +482,7 @@ fn partial_one_collateral_two_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -591,6 +596,7 @@ fn partial_one_collateral_two_slots_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -713,6 +719,7 @@ fn non_partial_liquidation() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -812,6 +819,7 @@ fn non_partial_liquidation_two_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -916,6 +924,7 @@ fn non_partial_liquidation_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1024,6 +1033,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1137,6 +1147,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1252,6 +1263,7 @@ fn partial_two_collaterals_ltv_diff() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1417,6 +1429,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1592,6 +1605,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1758,6 +1772,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1976,6 +1991,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2200,6 +2216,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2423,6 +2440,7 @@ fn not_enough_bids_for_one_of_two_col() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2613,6 +2631,7 @@ fn integration_test_simul() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
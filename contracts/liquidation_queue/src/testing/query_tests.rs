@@ -32,6 +32,7 @@ fn query_liquidation_amount() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -169,6 +170,7 @@ fn query_bids() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -350,6 +352,7 @@ fn query_bid_pools() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -505,6 +508,7 @@ fn query_collateral_info() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
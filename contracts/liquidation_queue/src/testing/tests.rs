@@ -1,16 +1,21 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, CONTRACT_NAME, CONTRACT_VERSION};
+use crate::state::{read_config, LegacyConfig};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
+    attr, from_binary, to_binary, Api, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
 };
+use cosmwasm_storage::singleton;
 use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{
-    BidResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    BidResponse, CollateralInfoResponse, ConfigChange, ConfigResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg,
 };
+use moneymarket::oracle::PriceMode;
+use moneymarket::pause::{Operation, PauseStatus};
+use moneymarket::version::{get_contract_version, set_contract_version};
 
 #[test]
 fn proper_initialization() {
@@ -27,6 +32,7 @@ fn proper_initialization() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -51,6 +57,8 @@ fn proper_initialization() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            guardian: None,
+            min_delay: 1u64,
         }
     );
 }
@@ -70,27 +78,41 @@ fn update_config() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
-    let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
-        oracle_contract: None,
-        safe_ratio: None,
-        bid_fee: None,
-        liquidator_fee: None,
-        liquidation_threshold: None,
-        price_timeframe: None,
-        waiting_period: None,
-        overseer: None,
+    // propose a new owner
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: 100u64,
     };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_ownership"),
+            attr("owner", "owner0001"),
+        ]
+    );
 
     // it worked, let's query the state
     let value: ConfigResponse =
@@ -108,24 +130,38 @@ fn update_config() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            guardian: None,
+            min_delay: 1u64,
         }
     );
 
     // Update left items
     let info = mock_info("owner0001", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        oracle_contract: Some("oracle0001".to_string()),
-        safe_ratio: Some(Decimal256::percent(15)),
-        bid_fee: Some(Decimal256::percent(2)),
-        liquidator_fee: Some(Decimal256::percent(1)),
-        liquidation_threshold: Some(Uint256::from(150000000u64)),
-        price_timeframe: Some(120u64),
-        waiting_period: Some(100u64),
-        overseer: Some("overseer0001".to_string()),
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            oracle_contract: Some("oracle0001".to_string()),
+            safe_ratio: Some(Decimal256::percent(15)),
+            bid_fee: Some(Decimal256::percent(2)),
+            liquidator_fee: Some(Decimal256::percent(1)),
+            liquidation_threshold: Some(Uint256::from(150000000u64)),
+            price_timeframe: Some(120u64),
+            waiting_period: Some(100u64),
+            overseer: Some("overseer0001".to_string()),
+            guardian: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
     assert_eq!(0, res.messages.len());
 
     // it worked, let's query the state
@@ -144,21 +180,26 @@ fn update_config() {
             price_timeframe: 120u64,
             waiting_period: 100u64,
             overseer: "overseer0001".to_string(),
+            guardian: None,
+            min_delay: 1u64,
         }
     );
 
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        oracle_contract: Some("oracle0001".to_string()),
-        safe_ratio: Some(Decimal256::percent(1)),
-        bid_fee: Some(Decimal256::percent(2)),
-        liquidator_fee: Some(Decimal256::percent(1)),
-        liquidation_threshold: Some(Uint256::from(150000000u64)),
-        price_timeframe: Some(100u64),
-        waiting_period: Some(100u64),
-        overseer: Some("overseer0001".to_string()),
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            oracle_contract: Some("oracle0001".to_string()),
+            safe_ratio: Some(Decimal256::percent(1)),
+            bid_fee: Some(Decimal256::percent(2)),
+            liquidator_fee: Some(Decimal256::percent(1)),
+            liquidation_threshold: Some(Uint256::from(150000000u64)),
+            price_timeframe: Some(100u64),
+            waiting_period: Some(100u64),
+            overseer: Some("overseer0001".to_string()),
+            guardian: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
     };
 
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
@@ -182,6 +223,7 @@ fn submit_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -296,6 +338,7 @@ fn activate_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -411,6 +454,7 @@ fn retract_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -484,6 +528,7 @@ fn retract_unactive_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -563,6 +608,7 @@ fn execute_bid() {
         price_timeframe: 100000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -712,6 +758,37 @@ fn execute_bid() {
         ]
     );
 
+    // collateral is valued at the TWAP price when the overseer is in TWAP mode
+    deps.querier.with_twap_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(25),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_price_mode(PriceMode::Twap {
+        window_seconds: 600u64,
+    });
+
+    // required_stable 9,900
+    // bid_fee         99
+    // liquidator_fee  99
+    // repay_amount    9,702
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(40000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: None,
+            repay_address: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert!(res.attributes.contains(&attr("repay_amount", "9702")));
+    deps.querier.with_price_mode(PriceMode::Spot {});
+
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(),
         amount: Uint128::from(2020206u128),
@@ -749,6 +826,7 @@ fn claim_liquidations() {
         price_timeframe: 1000000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -828,6 +906,163 @@ fn claim_liquidations() {
     );
 }
 
+#[test]
+fn native_collateral_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&ibc_denom.to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &(ibc_denom.to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // native collaterals are whitelisted by their denom
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: ibc_denom.to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: ibc_denom.to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: ibc_denom.to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::ExecuteBid {
+        liquidator: "liquidator00000".to_string(),
+        fee_address: Some("fee0000".to_string()),
+        repay_address: Some("repay0000".to_string()),
+    };
+
+    // the collateral must be sent alone
+    let info = mock_info(
+        "custody0000",
+        &[
+            Coin {
+                denom: ibc_denom.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Must send exactly one native collateral")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the custody contract of the collateral can liquidate
+    let collateral = [Coin {
+        denom: ibc_denom.to_string(),
+        amount: Uint128::from(1000000u128),
+    }];
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &collateral),
+        msg.clone(),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Unauthorized: only custody contract can execute liquidations"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("custody0000", &collateral),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "repay0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(485198u128),
+            }],
+        }))
+    );
+
+    // liquidated native collateral is claimed with a bank send
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: ibc_denom.to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: ibc_denom.to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        }))]
+    );
+}
+
 #[test]
 fn update_collateral_info() {
     let mut deps = mock_dependencies(&[]);
@@ -845,6 +1080,7 @@ fn update_collateral_info() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -896,3 +1132,275 @@ fn update_collateral_info() {
         }
     );
 }
+
+#[test]
+fn pause_operations() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        min_delay: 1u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            oracle_contract: None,
+            safe_ratio: None,
+            bid_fee: None,
+            liquidator_fee: None,
+            liquidation_threshold: None,
+            price_timeframe: None,
+            waiting_period: None,
+            overseer: None,
+            guardian: Some("guardian0000".to_string()),
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+
+    let value: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(Some("guardian0000".to_string()), value.guardian);
+
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::SubmitBid],
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("guardian0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause"), attr("operations", "submit_bid")]
+    );
+
+    let pause_status: PauseStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            submit_bid: true,
+            ..PauseStatus::default()
+        }
+    );
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Operation is paused: submit_bid")
+    );
+
+    // liquidations cannot be paused while the market lends
+    let pause_msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Liquidate],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        pause_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Market borrow must be paused while repay or liquidate is paused")
+    );
+
+    deps.querier.with_market_pause_status(PauseStatus {
+        borrow: true,
+        ..PauseStatus::default()
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        pause_msg,
+    )
+    .unwrap();
+
+    let liquidate_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: None,
+            repay_address: None,
+        })
+        .unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        liquidate_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Operation is paused: liquidate"));
+
+    // the owner can resume operations as well
+    let unpause_msg = ExecuteMsg::Unpause {
+        operations: vec![Operation::SubmitBid],
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        unpause_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // an empty address removes the guardian
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateConfig {
+            oracle_contract: None,
+            safe_ratio: None,
+            bid_fee: None,
+            liquidator_fee: None,
+            liquidation_threshold: None,
+            price_timeframe: None,
+            waiting_period: None,
+            overseer: None,
+            guardian: Some("".to_string()),
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 2u64 },
+    )
+    .unwrap();
+
+    let value: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(None, value.guardian);
+}
+
+#[test]
+fn migrate_legacy_deployment() {
+    let mut deps = mock_dependencies(&[]);
+
+    // deployment without version record, guardian and timelock
+    let legacy_config = LegacyConfig {
+        owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+        oracle_contract: deps.api.addr_canonicalize("oracle0000").unwrap(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: deps.api.addr_canonicalize("overseer0000").unwrap(),
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    let _res = get_contract_version(deps.as_ref().storage).unwrap_err();
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            min_delay: 86400u64,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        CONTRACT_VERSION.to_string()
+    );
+    let _res = read_config(deps.as_ref().storage).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            oracle_contract: "oracle0000".to_string(),
+            stable_denom: "uusd".to_string(),
+            safe_ratio: Decimal256::percent(10),
+            bid_fee: Decimal256::percent(1),
+            liquidator_fee: Decimal256::percent(0),
+            liquidation_threshold: Uint256::from(100000000u64),
+            price_timeframe: 60u64,
+            waiting_period: 60u64,
+            overseer: "overseer0000".to_string(),
+            guardian: None,
+            min_delay: 86400u64,
+        }
+    );
+
+    // 0.3.1 deployments with version record store the same layout
+    let mut deps = mock_dependencies(&[]);
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.3.1").unwrap();
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            min_delay: 86400u64,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        CONTRACT_VERSION.to_string()
+    );
+    assert_eq!(read_config(deps.as_ref().storage).unwrap().guardian, None);
+}
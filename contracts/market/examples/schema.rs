@@ -4,8 +4,9 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::market::{
-    BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    AverageBorrowRateResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    BorrowerLoanValueResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg, RateHistoryResponse, StablesResponse,
 };
 use moneymarket_market::state::State;
 
@@ -19,9 +20,14 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StablesResponse), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(EpochStateResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfoResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfosResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerLoanValueResponse), &out_dir);
+    export_schema(&schema_for!(RateHistoryResponse), &out_dir);
+    export_schema(&schema_for!(AverageBorrowRateResponse), &out_dir);
 }
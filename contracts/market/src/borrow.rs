@@ -1,20 +1,33 @@
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, WasmMsg,
 };
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
-use moneymarket::overseer::BorrowLimitResponse;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::{
+    AverageBorrowRateResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    BorrowerLoanValueResponse, ExecuteMsg, RateHistoryResponse, RateMode, RateSnapshotResponse,
+};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ConfigResponse as OverseerConfigResponse,
+    ExecuteMsg as OverseerExecuteMsg, IsolatedDebtResponse,
+};
+use moneymarket::querier::{deduct_tax, query_balance, query_price, query_supply, TimeConstraints};
+use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
 
 use crate::deposit::compute_exchange_rate_raw;
 use crate::error::ContractError;
-use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
+use crate::flash_loan::assert_no_flash_loan;
+use crate::querier::{
+    query_borrow_limit, query_borrow_rate, query_isolated_debt, query_overseer_config,
+    query_target_deposit_rate,
+};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    find_rate_snapshot_seq, read_borrower_info, read_borrower_infos, read_config,
+    read_rate_history, read_rate_history_count, read_rate_snapshot, read_stable,
+    read_stable_config, read_stable_configs, read_state, store_borrower_info, store_rate_snapshot,
+    store_state, BorrowerInfo, Config, RateSnapshot, StableConfig, State, RATE_HISTORY_SIZE,
 };
 
 pub fn borrow_stable(
@@ -23,76 +36,201 @@ pub fn borrow_stable(
     info: MessageInfo,
     borrow_amount: Uint256,
     to: Option<Addr>,
-) -> Result<Response, ContractError> {
+    denom: Option<String>,
+    rate_mode: Option<RateMode>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
 
-    let mut state: State = read_state(deps.storage)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
-    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut liability: BorrowerInfo =
+        read_borrower_info(deps.storage, &stable.denom, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
+    // Rate mode can only be changed with an empty loan,
+    // otherwise the loan must be swapped first
+    if let Some(rate_mode) = rate_mode {
+        if rate_mode != liability.rate_mode {
+            if !liability.loan_amount.is_zero() {
+                return Err(ContractError::RateModeMismatch(
+                    liability.rate_mode.to_string(),
+                ));
+            }
+
+            liability.rate_mode = rate_mode;
+            liability.stable_rate = Decimal256::zero();
+        }
+    }
+
     let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
         deps.as_ref(),
-        overseer,
+        overseer.clone(),
         borrower.clone(),
         Some(env.block.time.seconds()),
     )?;
 
-    if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
+    // Liabilities of all stables are compared in the base stable denom
+    let overseer_config: OverseerConfigResponse =
+        query_overseer_config(deps.as_ref(), overseer.clone())?;
+    let loan_value = compute_loan_value(
+        deps.as_ref(),
+        &config,
+        &overseer_config,
+        &borrower_raw,
+        env.block.height,
+        env.block.time.seconds(),
+    )?;
+    let borrow_value = borrow_amount
+        * query_stable_price(
+            deps.as_ref(),
+            &config,
+            &overseer_config,
+            &stable.denom,
+            env.block.time.seconds(),
+        )?;
+
+    if borrow_limit_res.borrow_limit < borrow_value + loan_value {
         return Err(ContractError::BorrowExceedsLimit(
             borrow_limit_res.borrow_limit.into(),
         ));
     }
 
+    if let Some(max_borrower_debt) = config.max_borrower_debt {
+        if max_borrower_debt < borrow_value + loan_value {
+            return Err(ContractError::MaxBorrowerDebtReached(
+                max_borrower_debt.into(),
+            ));
+        }
+    }
+
+    if let Some(max_total_debt) = config.max_total_debt {
+        let total_debt = compute_total_debt(
+            deps.as_ref(),
+            &config,
+            &overseer_config,
+            env.block.height,
+            env.block.time.seconds(),
+        )?;
+        if Decimal256::from_uint256(max_total_debt)
+            < Decimal256::from_uint256(borrow_value) + total_debt
+        {
+            return Err(ContractError::MaxTotalDebtReached(max_total_debt.into()));
+        }
+    }
+
     let current_balance = query_balance(
         deps.as_ref(),
         env.contract.address,
-        config.stable_denom.to_string(),
+        stable.denom.to_string(),
     )?;
 
     // Assert borrow amount
-    assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
+    assert_max_borrow_factor(&config, &stable, &state, current_balance, borrow_amount)?;
+
+    // The new amount is locked at the current stable rate and
+    // blended into the rate of the existing stable loan
+    if liability.rate_mode == RateMode::Stable && !borrow_amount.is_zero() {
+        let stable_rate =
+            compute_stable_rate(deps.as_ref(), &config, &stable, &state, current_balance)?;
+        let loan_amount = Decimal256::from_uint256(liability.loan_amount);
+        let amount = Decimal256::from_uint256(borrow_amount);
+        liability.stable_rate =
+            (liability.stable_rate * loan_amount + stable_rate * amount) / (loan_amount + amount);
+        increase_stable_liabilities(&mut state, amount, stable_rate);
+    }
 
     liability.loan_amount += borrow_amount;
     state.total_liabilities += Decimal256::from_uint256(borrow_amount);
-    store_state(deps.storage, &state)?;
-    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &stable.denom, &state)?;
+    store_borrower_info(deps.storage, &stable.denom, &borrower_raw, &liability)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
+        amount: vec![deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: stable.denom.clone(),
+                amount: borrow_amount.into(),
+            },
+        )?],
+    })];
+
+    // The overseer rejects the loan once it exceeds the
+    // debt ceiling of an isolated collateral of the borrower
+    let isolated_debt: IsolatedDebtResponse =
+        query_isolated_debt(deps.as_ref(), overseer.clone(), borrower.clone())?;
+    if !isolated_debt.isolated_collaterals.is_empty() && !borrow_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer.to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::IncreaseIsolatedDebt {
+                borrower: borrower.to_string(),
+            })?,
+        }));
+    }
 
-    Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: borrow_amount.into(),
-                },
-            )?],
-        }))
-        .add_attributes(vec![
-            attr("action", "borrow_stable"),
-            attr("borrower", borrower),
-            attr("borrow_amount", borrow_amount),
-        ]))
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "borrow_stable"),
+        attr("borrower", borrower),
+        attr("denom", stable.denom),
+        attr("borrow_amount", borrow_amount),
+        attr("rate_mode", liability.rate_mode.to_string()),
+    ]))
+}
+
+pub fn borrow_stable_from_overseer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    borrow_amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // override env
+    let overseer = info.sender.clone();
+    let mut info = info;
+
+    info.sender = borrower;
+    borrow_stable(deps, env, info, borrow_amount, Some(overseer), None, None)
 }
 
+/// Liquidations are settled in the base stable denom, which repays the
+/// base liability first. The rest is swapped into the other stables the
+/// borrower owes at the oracle price and repaid by `repay_stable_from_swap`,
+/// any amount left is refunded to the borrower
 pub fn repay_stable_from_liquidation(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     borrower: Addr,
     prev_balance: Uint256,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
         return Err(ContractError::Unauthorized {});
@@ -103,6 +241,81 @@ pub fn repay_stable_from_liquidation(
         env.contract.address.clone(),
         config.stable_denom.to_string(),
     )?;
+    let amount = cur_balance - prev_balance;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let base_loan = compute_borrower_loan(
+        deps.as_ref(),
+        &config,
+        &read_stable_config(deps.storage, &config.stable_denom)?,
+        &borrower_raw,
+        env.block.height,
+    )?;
+    let base_repay_amount = std::cmp::min(amount, base_loan);
+    let mut swap_amount = amount - base_repay_amount;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    if !swap_amount.is_zero() {
+        let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+        let overseer_config: OverseerConfigResponse =
+            query_overseer_config(deps.as_ref(), overseer)?;
+        for stable in read_stable_configs(deps.storage)? {
+            if stable.denom == config.stable_denom || swap_amount.is_zero() {
+                continue;
+            }
+
+            let loan_amount = compute_borrower_loan(
+                deps.as_ref(),
+                &config,
+                &stable,
+                &borrower_raw,
+                env.block.height,
+            )?;
+            if loan_amount.is_zero() {
+                continue;
+            }
+
+            let loan_value = loan_amount
+                * query_stable_price(
+                    deps.as_ref(),
+                    &config,
+                    &overseer_config,
+                    &stable.denom,
+                    env.block.time.seconds(),
+                )?;
+            let offer_amount = std::cmp::min(swap_amount, loan_value);
+            swap_amount = swap_amount - offer_amount;
+
+            let prev_balance: Uint256 = query_balance(
+                deps.as_ref(),
+                env.contract.address.clone(),
+                stable.denom.to_string(),
+            )?;
+            messages.push(create_swap_msg(
+                Coin {
+                    denom: config.stable_denom.clone(),
+                    amount: offer_amount.into(),
+                },
+                stable.denom.clone(),
+            ));
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayStableFromSwap {
+                    borrower: borrower.to_string(),
+                    denom: stable.denom,
+                    prev_balance,
+                })?,
+            }));
+        }
+    }
+
+    // The base stable which is not swapped repays the base
+    // liability and the excess is refunded to the borrower
+    let repay_amount = base_repay_amount + swap_amount;
+    if repay_amount.is_zero() && !messages.is_empty() {
+        return Ok(Response::new().add_messages(messages));
+    }
 
     // override env
     let mut info = info;
@@ -110,42 +323,85 @@ pub fn repay_stable_from_liquidation(
     info.sender = borrower;
     info.funds = vec![Coin {
         denom: config.stable_denom,
+        amount: repay_amount.into(),
+    }];
+
+    Ok(repay_stable(deps, env, info, None)?.add_messages(messages))
+}
+
+/// Repay the borrower liability of `denom` with the
+/// base stable swapped in `repay_stable_from_liquidation`
+/// Executor: itself
+pub fn repay_stable_from_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    denom: String,
+    prev_balance: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cur_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        denom.to_string(),
+    )?;
+
+    // override env
+    let mut info = info;
+
+    info.sender = borrower;
+    info.funds = vec![Coin {
+        denom: denom.clone(),
         amount: (cur_balance - prev_balance).into(),
     }];
 
-    repay_stable(deps, env, info)
+    repay_stable(deps, env, info, Some(denom))
 }
 
-pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn repay_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
 
     // Check stable denom deposit
     let amount: Uint256 = info
         .funds
         .iter()
-        .find(|c| c.denom == config.stable_denom)
+        .find(|c| c.denom == stable.denom)
         .map(|c| Uint256::from(c.amount))
         .unwrap_or_else(Uint256::zero);
 
     // Cannot deposit zero amount
     if amount.is_zero() {
-        return Err(ContractError::ZeroRepay(config.stable_denom));
+        return Err(ContractError::ZeroRepay(stable.denom));
     }
 
-    let mut state: State = read_state(deps.storage)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
-    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut liability: BorrowerInfo =
+        read_borrower_info(deps.storage, &stable.denom, &borrower_raw);
 
     // Compute interest
-    compute_interest(
+    if let Some(rate_snapshot) = compute_interest(
         deps.as_ref(),
         &config,
+        &stable,
         &mut state,
         env.block.height,
         Some(amount),
-    )?;
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -153,7 +409,7 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     compute_borrower_reward(&state, &mut liability);
 
     let repay_amount: Uint256;
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
     if liability.loan_amount < amount {
         repay_amount = liability.loan_amount;
         liability.loan_amount = Uint256::zero();
@@ -164,7 +420,7 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
-                    denom: config.stable_denom,
+                    denom: stable.denom.clone(),
                     amount: (amount - repay_amount).into(),
                 },
             )?],
@@ -174,33 +430,210 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
         liability.loan_amount = liability.loan_amount - repay_amount;
     }
 
+    if liability.rate_mode == RateMode::Stable {
+        decrease_stable_liabilities(
+            &mut state,
+            Decimal256::from_uint256(repay_amount),
+            liability.stable_rate,
+        );
+    }
+
     state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
 
-    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
-    store_state(deps.storage, &state)?;
+    store_borrower_info(deps.storage, &stable.denom, &borrower_raw, &liability)?;
+    store_state(deps.storage, &stable.denom, &state)?;
+
+    // Charge the remaining loan against the debt ceilings of the borrower
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let isolated_debt: IsolatedDebtResponse =
+        query_isolated_debt(deps.as_ref(), overseer.clone(), borrower.clone())?;
+    if !isolated_debt.debt.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer.to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::DecreaseIsolatedDebt {
+                borrower: borrower.to_string(),
+            })?,
+        }));
+    }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "repay_stable"),
         attr("borrower", borrower),
+        attr("denom", stable.denom),
         attr("repay_amount", repay_amount),
     ]))
 }
 
+pub fn swap_rate_mode(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo =
+        read_borrower_info(deps.storage, &stable.denom, &borrower_raw);
+    if liability.loan_amount.is_zero() {
+        return Err(ContractError::NoLoan(stable.denom));
+    }
+
+    // Compute interest
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let loan_amount = Decimal256::from_uint256(liability.loan_amount);
+    match liability.rate_mode {
+        RateMode::Variable => {
+            let balance = query_balance(
+                deps.as_ref(),
+                env.contract.address,
+                stable.denom.to_string(),
+            )?;
+            let stable_rate =
+                compute_stable_rate(deps.as_ref(), &config, &stable, &state, balance)?;
+
+            increase_stable_liabilities(&mut state, loan_amount, stable_rate);
+            liability.rate_mode = RateMode::Stable;
+            liability.stable_rate = stable_rate;
+        }
+        RateMode::Stable => {
+            decrease_stable_liabilities(&mut state, loan_amount, liability.stable_rate);
+            liability.rate_mode = RateMode::Variable;
+            liability.stable_rate = Decimal256::zero();
+        }
+    }
+
+    store_state(deps.storage, &stable.denom, &state)?;
+    store_borrower_info(deps.storage, &stable.denom, &borrower_raw, &liability)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "swap_rate_mode"),
+        attr("borrower", borrower),
+        attr("denom", stable.denom),
+        attr("rate_mode", liability.rate_mode.to_string()),
+        attr("stable_rate", liability.stable_rate.to_string()),
+    ]))
+}
+
+/// Anyone can relock a stable rate which drifted beyond the
+/// rebalance threshold from the current stable rate, so the
+/// locked rates follow large moves of the variable rate
+pub fn rebalance_stable_rate(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    denom: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo =
+        read_borrower_info(deps.storage, &stable.denom, &borrower_raw);
+    if liability.rate_mode != RateMode::Stable || liability.loan_amount.is_zero() {
+        return Err(ContractError::RebalanceNotAllowed {});
+    }
+
+    // Compute interest
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let balance = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        stable.denom.to_string(),
+    )?;
+    let stable_rate = compute_stable_rate(deps.as_ref(), &config, &stable, &state, balance)?;
+    let drift = if stable_rate > liability.stable_rate {
+        stable_rate - liability.stable_rate
+    } else {
+        liability.stable_rate - stable_rate
+    };
+
+    if drift <= config.stable_rate_rebalance_threshold {
+        return Err(ContractError::RebalanceNotAllowed {});
+    }
+
+    let loan_amount = Decimal256::from_uint256(liability.loan_amount);
+    decrease_stable_liabilities(&mut state, loan_amount, liability.stable_rate);
+    increase_stable_liabilities(&mut state, loan_amount, stable_rate);
+    liability.stable_rate = stable_rate;
+
+    store_state(deps.storage, &stable.denom, &state)?;
+    store_borrower_info(deps.storage, &stable.denom, &borrower_raw, &liability)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "rebalance_stable_rate"),
+        attr("borrower", borrower),
+        attr("denom", stable.denom),
+        attr("stable_rate", stable_rate.to_string()),
+    ]))
+}
+
 pub fn claim_rewards(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     to: Option<Addr>,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    // ANC rewards are only distributed to the base stable borrowers
     let config: Config = read_config(deps.storage)?;
-    let mut state: State = read_state(deps.storage)?;
+    let stable: StableConfig = read_stable_config(deps.storage, &config.stable_denom)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
-    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut liability: BorrowerInfo =
+        read_borrower_info(deps.storage, &stable.denom, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -210,10 +643,10 @@ pub fn claim_rewards(
     let claim_amount = liability.pending_rewards * Uint256::one();
     liability.pending_rewards = liability.pending_rewards - Decimal256::from_uint256(claim_amount);
 
-    store_state(deps.storage, &state)?;
-    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &stable.denom, &state)?;
+    store_borrower_info(deps.storage, &stable.denom, &borrower_raw, &liability)?;
 
-    let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
+    let messages: Vec<CosmosMsg<TerraMsgWrapper>> = if !claim_amount.is_zero() {
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -241,27 +674,29 @@ pub fn claim_rewards(
 
 /// Compute interest and update state
 /// total liabilities and total reserves
+/// Returns the applied rates when interest is accrued
 pub fn compute_interest(
     deps: Deps,
     config: &Config,
+    stable: &StableConfig,
     state: &mut State,
     block_height: u64,
     deposit_amount: Option<Uint256>,
-) -> StdResult<()> {
+) -> StdResult<Option<RateSnapshot>> {
     if state.last_interest_updated >= block_height {
-        return Ok(());
+        return Ok(None);
     }
 
-    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&stable.aterra_contract)?)?;
     let balance: Uint256 = query_balance(
         deps,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
+        stable.denom.to_string(),
     )? - deposit_amount.unwrap_or_else(Uint256::zero);
 
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
         deps,
-        deps.api.addr_humanize(&config.interest_model)?,
+        deps.api.addr_humanize(&stable.interest_model)?,
         balance,
         state.total_liabilities,
         state.total_reserves,
@@ -270,16 +705,14 @@ pub fn compute_interest(
     let target_deposit_rate: Decimal256 =
         query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
 
-    compute_interest_raw(
+    Ok(compute_interest_raw(
         state,
         block_height,
         balance,
         aterra_supply,
         borrow_rate_res.rate,
         target_deposit_rate,
-    );
-
-    Ok(())
+    ))
 }
 
 // CONTRACT: to use this function as state update purpose,
@@ -294,23 +727,29 @@ pub fn compute_interest_raw(
     aterra_supply: Uint256,
     borrow_rate: Decimal256,
     target_deposit_rate: Decimal256,
-) {
+) -> Option<RateSnapshot> {
     if state.last_interest_updated >= block_height {
-        return;
+        return None;
     }
 
     let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
 
+    // Stable rate liabilities accrue with their average locked rate
     let interest_factor = passed_blocks * borrow_rate;
-    let interest_accrued = state.total_liabilities * interest_factor;
+    let stable_interest_accrued =
+        state.total_stable_liabilities * passed_blocks * state.avg_stable_rate;
+    let interest_accrued = (state.total_liabilities - state.total_stable_liabilities)
+        * interest_factor
+        + stable_interest_accrued;
 
     state.global_interest_index =
         state.global_interest_index * (Decimal256::one() + interest_factor);
+    state.total_stable_liabilities += stable_interest_accrued;
     state.total_liabilities += interest_accrued;
 
     let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
     let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
-    let deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
+    let mut deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
 
     if deposit_rate > target_deposit_rate {
         // excess_deposit_rate(_per_block)
@@ -323,18 +762,101 @@ pub fn compute_interest_raw(
 
         state.total_reserves += excess_yield;
         exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
+        deposit_rate = target_deposit_rate;
     }
 
+    let total_value_in_market =
+        Decimal256::from_uint256(balance) + state.total_liabilities - state.total_reserves;
+    let utilization_ratio = if total_value_in_market.is_zero() {
+        Decimal256::zero()
+    } else {
+        state.total_liabilities / total_value_in_market
+    };
+
     state.prev_aterra_supply = aterra_supply;
     state.prev_exchange_rate = exchange_rate;
     state.last_interest_updated = block_height;
+
+    Some(RateSnapshot {
+        block_height,
+        borrow_rate,
+        deposit_rate,
+        exchange_rate,
+        utilization_ratio,
+    })
 }
 
-/// Compute new interest and apply to liability
+/// Compute new interest and apply to liability,
+/// stable rate loans accrue with their locked rate
 pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
-    liability.loan_amount =
-        liability.loan_amount * state.global_interest_index / liability.interest_index;
+    match liability.rate_mode {
+        RateMode::Variable => {
+            liability.loan_amount =
+                liability.loan_amount * state.global_interest_index / liability.interest_index;
+        }
+        RateMode::Stable => {
+            if state.last_interest_updated > liability.last_interest_updated {
+                let passed_blocks = Decimal256::from_uint256(
+                    state.last_interest_updated - liability.last_interest_updated,
+                );
+                liability.loan_amount = liability.loan_amount
+                    * (Decimal256::one() + passed_blocks * liability.stable_rate);
+            }
+        }
+    }
+
     liability.interest_index = state.global_interest_index;
+    liability.last_interest_updated = state.last_interest_updated;
+}
+
+/// Stable rate offered at the current variable borrow rate
+fn compute_stable_rate(
+    deps: Deps,
+    config: &Config,
+    stable: &StableConfig,
+    state: &State,
+    balance: Uint256,
+) -> StdResult<Decimal256> {
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&stable.interest_model)?,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+
+    Ok(borrow_rate_res.rate + config.stable_rate_premium)
+}
+
+fn increase_stable_liabilities(state: &mut State, amount: Decimal256, rate: Decimal256) {
+    let total_stable_liabilities = state.total_stable_liabilities + amount;
+    if total_stable_liabilities.is_zero() {
+        return;
+    }
+
+    state.avg_stable_rate = (state.avg_stable_rate * state.total_stable_liabilities
+        + rate * amount)
+        / total_stable_liabilities;
+    state.total_stable_liabilities = total_stable_liabilities;
+}
+
+/// Borrowers accrue their stable interest separately from the
+/// average rate, so the removed amount can exceed the total
+fn decrease_stable_liabilities(state: &mut State, amount: Decimal256, rate: Decimal256) {
+    if amount >= state.total_stable_liabilities {
+        state.total_stable_liabilities = Decimal256::zero();
+        state.avg_stable_rate = Decimal256::zero();
+        return;
+    }
+
+    let weighted_rate = state.avg_stable_rate * state.total_stable_liabilities;
+    let removed_rate = rate * amount;
+    state.total_stable_liabilities = state.total_stable_liabilities - amount;
+    state.avg_stable_rate = if weighted_rate > removed_rate {
+        (weighted_rate - removed_rate) / state.total_stable_liabilities
+    } else {
+        Decimal256::zero()
+    };
 }
 
 /// Compute distributed reward and update global index
@@ -362,14 +884,112 @@ pub(crate) fn compute_borrower_reward(state: &State, liability: &mut BorrowerInf
     liability.reward_index = state.global_reward_index;
 }
 
+/// Price of the given stable in the base stable denom
+fn query_stable_price(
+    deps: Deps,
+    config: &Config,
+    overseer_config: &OverseerConfigResponse,
+    denom: &str,
+    block_time: u64,
+) -> StdResult<Decimal256> {
+    if denom == config.stable_denom {
+        return Ok(Decimal256::one());
+    }
+
+    Ok(query_price(
+        deps,
+        deps.api.addr_validate(&overseer_config.oracle_contract)?,
+        denom.to_string(),
+        config.stable_denom.to_string(),
+        Some(TimeConstraints {
+            block_time,
+            valid_timeframe: overseer_config.price_timeframe,
+        }),
+    )?
+    .rate)
+}
+
+/// Sum of the borrower liabilities of all stables, with the interest
+/// accrued until `block_height`, priced in the base stable denom
+pub(crate) fn compute_loan_value(
+    deps: Deps,
+    config: &Config,
+    overseer_config: &OverseerConfigResponse,
+    borrower: &CanonicalAddr,
+    block_height: u64,
+    block_time: u64,
+) -> StdResult<Uint256> {
+    let mut loan_value = Uint256::zero();
+    for stable in read_stable_configs(deps.storage)? {
+        let loan_amount = compute_borrower_loan(deps, config, &stable, borrower, block_height)?;
+        if loan_amount.is_zero() {
+            continue;
+        }
+
+        loan_value += loan_amount
+            * query_stable_price(deps, config, overseer_config, &stable.denom, block_time)?;
+    }
+
+    Ok(loan_value)
+}
+
+/// Borrower liability of `stable` with the interest accrued until `block_height`
+fn compute_borrower_loan(
+    deps: Deps,
+    config: &Config,
+    stable: &StableConfig,
+    borrower: &CanonicalAddr,
+    block_height: u64,
+) -> StdResult<Uint256> {
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &stable.denom, borrower);
+    if liability.loan_amount.is_zero() {
+        return Ok(Uint256::zero());
+    }
+
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+    compute_interest(deps, config, stable, &mut state, block_height, None)?;
+    compute_borrower_interest(&state, &mut liability);
+
+    Ok(liability.loan_amount)
+}
+
+/// Sum of the total liabilities of all stables, with the interest
+/// accrued until `block_height`, priced in the base stable denom
+fn compute_total_debt(
+    deps: Deps,
+    config: &Config,
+    overseer_config: &OverseerConfigResponse,
+    block_height: u64,
+    block_time: u64,
+) -> StdResult<Decimal256> {
+    let mut total_debt = Decimal256::zero();
+    for stable in read_stable_configs(deps.storage)? {
+        let mut state: State = read_state(deps.storage, &stable.denom)?;
+        if state.total_liabilities.is_zero() {
+            continue;
+        }
+
+        compute_interest(deps, config, &stable, &mut state, block_height, None)?;
+        total_debt += state.total_liabilities
+            * query_stable_price(deps, config, overseer_config, &stable.denom, block_time)?;
+    }
+
+    Ok(total_debt)
+}
+
 pub fn query_borrower_info(
     deps: Deps,
     env: Env,
     borrower: Addr,
     block_height: Option<u64>,
+    denom: Option<String>,
 ) -> StdResult<BorrowerInfoResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+
     let mut borrower_info: BorrowerInfo = read_borrower_info(
         deps.storage,
+        &stable.denom,
         &deps.api.addr_canonicalize(borrower.as_str())?,
     );
 
@@ -379,10 +999,9 @@ pub fn query_borrower_info(
         env.block.height
     };
 
-    let config: Config = read_config(deps.storage)?;
-    let mut state: State = read_state(deps.storage)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
-    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_interest(deps, &config, &stable, &mut state, block_height, None)?;
     compute_borrower_interest(&state, &mut borrower_info);
 
     compute_reward(&mut state, block_height);
@@ -390,10 +1009,13 @@ pub fn query_borrower_info(
 
     Ok(BorrowerInfoResponse {
         borrower: borrower.to_string(),
+        denom: stable.denom,
         interest_index: borrower_info.interest_index,
         reward_index: borrower_info.reward_index,
         loan_amount: borrower_info.loan_amount,
         pending_rewards: borrower_info.pending_rewards,
+        rate_mode: borrower_info.rate_mode,
+        stable_rate: borrower_info.stable_rate,
     })
 }
 
@@ -401,19 +1023,139 @@ pub fn query_borrower_infos(
     deps: Deps,
     start_after: Option<Addr>,
     limit: Option<u32>,
+    denom: Option<String>,
 ) -> StdResult<BorrowerInfosResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+
     let start_after = if let Some(start_after) = start_after {
         Some(deps.api.addr_canonicalize(start_after.as_str())?)
     } else {
         None
     };
 
-    let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?;
+    let borrower_infos: Vec<BorrowerInfoResponse> =
+        read_borrower_infos(deps, &stable.denom, start_after, limit)?;
     Ok(BorrowerInfosResponse { borrower_infos })
 }
 
+pub fn query_borrower_loan_value(
+    deps: Deps,
+    borrower: Addr,
+    block_height: u64,
+    block_time: u64,
+) -> StdResult<BorrowerLoanValueResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let overseer_config: OverseerConfigResponse =
+        query_overseer_config(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
+
+    let loan_value = compute_loan_value(
+        deps,
+        &config,
+        &overseer_config,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        block_height,
+        block_time,
+    )?;
+
+    Ok(BorrowerLoanValueResponse {
+        borrower: borrower.to_string(),
+        loan_value,
+    })
+}
+
+pub fn query_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    denom: Option<String>,
+) -> StdResult<RateHistoryResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+
+    let rate_history: Vec<RateSnapshotResponse> =
+        read_rate_history(deps.storage, &stable.denom, start_after, limit)?
+            .into_iter()
+            .map(|snapshot| RateSnapshotResponse {
+                block_height: snapshot.block_height,
+                borrow_rate: snapshot.borrow_rate,
+                deposit_rate: snapshot.deposit_rate,
+                exchange_rate: snapshot.exchange_rate,
+                utilization_ratio: snapshot.utilization_ratio,
+            })
+            .collect();
+
+    Ok(RateHistoryResponse { rate_history })
+}
+
+/// Each snapshot borrow rate was applied to the blocks between the
+/// previous snapshot and itself. The blocks before the oldest kept
+/// snapshot and after the latest one are not covered.
+pub fn query_average_borrow_rate(
+    deps: Deps,
+    from_height: u64,
+    to_height: u64,
+    denom: Option<String>,
+) -> StdResult<AverageBorrowRateResponse> {
+    if from_height >= to_height {
+        return Err(StdError::generic_err(
+            "from_height must be smaller than to_height",
+        ));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let denom = read_stable(deps.storage, &config, denom)?.denom;
+
+    let count = read_rate_history_count(deps.storage, &denom);
+    let mut seq = find_rate_snapshot_seq(deps.storage, &denom, from_height)?;
+    let mut prev_height: Option<u64> = if seq > count.saturating_sub(RATE_HISTORY_SIZE) {
+        Some(read_rate_snapshot(deps.storage, &denom, seq - 1)?.block_height)
+    } else {
+        None
+    };
+
+    let mut weighted_rate = Decimal256::zero();
+    let mut covered_from: Option<u64> = None;
+    let mut covered_to: u64 = 0;
+    while seq < count {
+        let snapshot = read_rate_snapshot(deps.storage, &denom, seq)?;
+        if let Some(prev_height) = prev_height {
+            let start = prev_height.max(from_height);
+            let end = snapshot.block_height.min(to_height);
+            if end > start {
+                weighted_rate += snapshot.borrow_rate * Decimal256::from_uint256(end - start);
+                covered_from.get_or_insert(start);
+                covered_to = end;
+            }
+        }
+
+        if snapshot.block_height >= to_height {
+            break;
+        }
+
+        prev_height = Some(snapshot.block_height);
+        seq += 1;
+    }
+
+    let covered_from = match covered_from {
+        Some(v) => v,
+        None => {
+            return Err(StdError::generic_err(
+                "No rate history between the given heights",
+            ))
+        }
+    };
+
+    Ok(AverageBorrowRateResponse {
+        average_borrow_rate: weighted_rate / Decimal256::from_uint256(covered_to - covered_from),
+        from_height: covered_from,
+        to_height: covered_to,
+    })
+}
+
 fn assert_max_borrow_factor(
     config: &Config,
+    stable: &StableConfig,
     state: &State,
     current_balance: Uint256,
     borrow_amount: Uint256,
@@ -426,16 +1168,12 @@ fn assert_max_borrow_factor(
         > (current_balance + state.total_liabilities - state.total_reserves)
             * config.max_borrow_factor
     {
-        return Err(ContractError::MaxBorrowFactorReached(
-            config.stable_denom.clone(),
-        ));
+        return Err(ContractError::MaxBorrowFactorReached(stable.denom.clone()));
     }
 
     // Assert available balance
     if borrow_amount + state.total_reserves > current_balance {
-        return Err(ContractError::NoStableAvailable(
-            config.stable_denom.clone(),
-        ));
+        return Err(ContractError::NoStableAvailable(stable.denom.clone()));
     }
 
     Ok(())
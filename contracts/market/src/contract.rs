@@ -2,32 +2,55 @@
 use cosmwasm_std::entry_point;
 
 use crate::borrow::{
-    borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    borrow_stable, borrow_stable_from_overseer, claim_rewards, compute_interest,
+    compute_interest_raw, compute_reward, query_average_borrow_rate, query_borrower_info,
+    query_borrower_infos, query_borrower_loan_value, query_rate_history, rebalance_stable_rate,
+    repay_stable, repay_stable_from_liquidation, repay_stable_from_swap, swap_rate_mode,
 };
 use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
-use crate::querier::{query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate};
+use crate::flash_loan::{
+    assert_flash_loan_fee, assert_no_flash_loan, flash_loan, flash_loan_callback,
+    FLASH_LOAN_REPLY_ID,
+};
+use crate::querier::{
+    query_anc_emission_rate, query_borrow_rate, query_overseer_config, query_pause_status,
+    query_target_deposit_rate, query_whitelist,
+};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::state::{
+    assert_borrowers_migrated, migrate_borrowers, migrate_state, read_config, read_stable,
+    read_stable_config, read_stable_configs, read_state, store_config, store_rate_snapshot,
+    store_stable_config, store_state, Config, StableConfig, State,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps,
-    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 
 use moneymarket::common::optional_addr_validate;
-use moneymarket::interest_model::BorrowRateResponse;
+use moneymarket::interest_model::{BorrowRateResponse, ExecuteMsg as InterestExecuteMsg};
 use moneymarket::market::{
-    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    QueryMsg, StableResponse, StablesResponse, StateResponse,
+};
+use moneymarket::ownership;
+use moneymarket::pause::{
+    assert_not_paused, guardian_to_raw, read_pause_status, update_pause_status, Operation,
 };
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::version::{assert_migrate_version, is_older_version, set_contract_version};
 use protobuf::Message;
+use terra_cosmwasm::TerraMsgWrapper;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-market";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 1000000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -36,7 +59,7 @@ pub fn instantiate(
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let initial_deposit = info
         .funds
         .iter()
@@ -51,24 +74,42 @@ pub fn instantiate(
         ));
     }
 
+    assert_flash_loan_fee(msg.flash_loan_fee)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     store_config(
         deps.storage,
         &Config {
             contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
             owner_addr: deps.api.addr_canonicalize(&msg.owner_addr)?,
-            aterra_contract: CanonicalAddr::from(vec![]),
             overseer_contract: CanonicalAddr::from(vec![]),
-            interest_model: CanonicalAddr::from(vec![]),
             distribution_model: CanonicalAddr::from(vec![]),
             collector_contract: CanonicalAddr::from(vec![]),
             distributor_contract: CanonicalAddr::from(vec![]),
             stable_denom: msg.stable_denom.clone(),
             max_borrow_factor: msg.max_borrow_factor,
+            flash_loan_fee: msg.flash_loan_fee,
+            stable_rate_premium: msg.stable_rate_premium,
+            stable_rate_rebalance_threshold: msg.stable_rate_rebalance_threshold,
+            max_total_debt: None,
+            max_borrower_debt: None,
+            guardian: None,
+        },
+    )?;
+
+    store_stable_config(
+        deps.storage,
+        &StableConfig {
+            denom: msg.stable_denom.clone(),
+            aterra_contract: CanonicalAddr::from(vec![]),
+            interest_model: CanonicalAddr::from(vec![]),
         },
     )?;
 
     store_state(
         deps.storage,
+        &msg.stable_denom,
         &State {
             total_liabilities: Decimal256::zero(),
             total_reserves: Decimal256::zero(),
@@ -79,36 +120,47 @@ pub fn instantiate(
             anc_emission_rate: msg.anc_emission_rate,
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )?;
 
-    Ok(
-        Response::new().add_submessages(vec![SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Instantiate {
-                admin: None,
-                code_id: msg.aterra_code_id,
-                funds: vec![],
-                label: "".to_string(),
-                msg: to_binary(&TokenInstantiateMsg {
-                    name: format!("Anchor Terra {}", msg.stable_denom[1..].to_uppercase()),
-                    symbol: format!(
-                        "a{}T",
-                        msg.stable_denom[1..(msg.stable_denom.len() - 1)].to_uppercase()
-                    ),
-                    decimals: 6u8,
-                    initial_balances: vec![Cw20Coin {
-                        address: env.contract.address.to_string(),
-                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-                    }],
-                    mint: Some(MinterResponse {
-                        minter: env.contract.address.to_string(),
-                        cap: None,
-                    }),
-                })?,
-            }),
-            1,
-        )]),
-    )
+    Ok(Response::new().add_submessage(instantiate_aterra(
+        &env,
+        msg.aterra_code_id,
+        &msg.stable_denom,
+    )?))
+}
+
+/// Instantiates the aterra token of the given stable denom,
+/// the initial deposit is minted to the market itself
+fn instantiate_aterra(
+    env: &Env,
+    aterra_code_id: u64,
+    denom: &str,
+) -> StdResult<SubMsg<TerraMsgWrapper>> {
+    Ok(SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Instantiate {
+            admin: None,
+            code_id: aterra_code_id,
+            funds: vec![],
+            label: "".to_string(),
+            msg: to_binary(&TokenInstantiateMsg {
+                name: format!("Anchor Terra {}", denom[1..].to_uppercase()),
+                symbol: format!("a{}T", denom[1..(denom.len() - 1)].to_uppercase()),
+                decimals: 6u8,
+                initial_balances: vec![Cw20Coin {
+                    address: env.contract.address.to_string(),
+                    amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+                }],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+        }),
+        1,
+    ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -117,7 +169,14 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    if !matches!(msg, ExecuteMsg::MigrateBorrowers { .. }) {
+        assert_borrowers_migrated(deps.storage)?;
+    }
+    if let Some(operation) = pausable_operation(&msg) {
+        assert_not_paused(deps.storage, operation)?;
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterContracts {
@@ -137,23 +196,62 @@ pub fn execute(
                 api.addr_validate(&distributor_contract)?,
             )
         }
+        ExecuteMsg::RegisterStable {
+            denom,
+            interest_model,
+            aterra_code_id,
+        } => {
+            let api = deps.api;
+            register_stable(
+                deps,
+                env,
+                info,
+                denom,
+                api.addr_validate(&interest_model)?,
+                aterra_code_id,
+            )
+        }
+        ExecuteMsg::UpdateStable {
+            denom,
+            interest_model,
+        } => {
+            let api = deps.api;
+            update_stable(deps, env, info, denom, api.addr_validate(&interest_model)?)
+        }
         ExecuteMsg::UpdateConfig {
-            owner_addr,
             interest_model,
             distribution_model,
             max_borrow_factor,
+            flash_loan_fee,
+            stable_rate_premium,
+            stable_rate_rebalance_threshold,
+            max_total_debt,
+            max_borrower_debt,
+            guardian,
         } => {
             let api = deps.api;
             update_config(
                 deps,
                 env,
                 info,
-                optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, interest_model)?,
                 optional_addr_validate(api, distribution_model)?,
                 max_borrow_factor,
+                flash_loan_fee,
+                stable_rate_premium,
+                stable_rate_rebalance_threshold,
+                max_total_debt,
+                max_borrower_debt,
+                guardian,
             )
         }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::Pause { operations } => update_pause(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => update_pause(deps, info, operations, false),
         ExecuteMsg::ExecuteEpochOperations {
             deposit_rate,
             target_deposit_rate,
@@ -168,8 +266,13 @@ pub fn execute(
             threshold_deposit_rate,
             distributed_interest,
         ),
-        ExecuteMsg::DepositStable {} => deposit_stable(deps, env, info),
-        ExecuteMsg::BorrowStable { borrow_amount, to } => {
+        ExecuteMsg::DepositStable { denom } => deposit_stable(deps, env, info, denom),
+        ExecuteMsg::BorrowStable {
+            borrow_amount,
+            to,
+            denom,
+            rate_mode,
+        } => {
             let api = deps.api;
             borrow_stable(
                 deps,
@@ -177,9 +280,29 @@ pub fn execute(
                 info,
                 borrow_amount,
                 optional_addr_validate(api, to)?,
+                denom,
+                rate_mode,
+            )
+        }
+        ExecuteMsg::RepayStable { denom } => repay_stable(deps, env, info, denom),
+        ExecuteMsg::SwapRateMode { denom } => swap_rate_mode(deps, env, info, denom),
+        ExecuteMsg::RebalanceStableRate { borrower, denom } => {
+            let api = deps.api;
+            rebalance_stable_rate(deps, env, api.addr_validate(&borrower)?, denom)
+        }
+        ExecuteMsg::BorrowStableFromOverseer {
+            borrower,
+            borrow_amount,
+        } => {
+            let api = deps.api;
+            borrow_stable_from_overseer(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                borrow_amount,
             )
         }
-        ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -193,15 +316,64 @@ pub fn execute(
                 prev_balance,
             )
         }
+        ExecuteMsg::RepayStableFromSwap {
+            borrower,
+            denom,
+            prev_balance,
+        } => {
+            let api = deps.api;
+            repay_stable_from_swap(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                denom,
+                prev_balance,
+            )
+        }
         ExecuteMsg::ClaimRewards { to } => {
             let api = deps.api;
             claim_rewards(deps, env, info, optional_addr_validate(api, to)?)
         }
+        ExecuteMsg::MigrateBorrowers { limit } => migrate_legacy_borrowers(deps, limit),
+        ExecuteMsg::FlashLoan {
+            amount,
+            callback_contract,
+            callback_msg,
+            denom,
+        } => {
+            let api = deps.api;
+            flash_loan(
+                deps,
+                env,
+                amount,
+                api.addr_validate(&callback_contract)?,
+                callback_msg,
+                denom,
+            )
+        }
+    }
+}
+
+/// Operation of the message the guardian can pause, redemptions
+/// are checked once the cw20 hook is decoded
+fn pausable_operation(msg: &ExecuteMsg) -> Option<Operation> {
+    match msg {
+        ExecuteMsg::DepositStable { .. } => Some(Operation::Deposit),
+        ExecuteMsg::BorrowStable { .. }
+        | ExecuteMsg::BorrowStableFromOverseer { .. }
+        | ExecuteMsg::FlashLoan { .. } => Some(Operation::Borrow),
+        ExecuteMsg::RepayStable { .. } => Some(Operation::Repay),
+        _ => None,
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     match msg.id {
         1 => {
             // get new token's contract address
@@ -218,6 +390,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
             register_aterra(deps, token_addr)
         }
+        FLASH_LOAN_REPLY_ID => flash_loan_callback(deps, env),
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
@@ -227,31 +400,41 @@ pub fn receive_cw20(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let contract_addr = info.sender;
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::RedeemStable {}) => {
+        Ok(Cw20HookMsg::RedeemStable { denom }) => {
+            assert_not_paused(deps.storage, Operation::Redeem)?;
+
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
-            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+            let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != stable.aterra_contract {
                 return Err(ContractError::Unauthorized {});
             }
 
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
+            redeem_stable(deps, env, &stable, cw20_sender_addr, cw20_msg.amount)
         }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
 }
 
-pub fn register_aterra(deps: DepsMut, token_addr: Addr) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
-    if config.aterra_contract != CanonicalAddr::from(vec![]) {
-        return Err(ContractError::Unauthorized {});
-    }
+pub fn register_aterra(
+    deps: DepsMut,
+    token_addr: Addr,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    // the token belongs to the stable still waiting for its aterra
+    let mut stable: StableConfig = match read_stable_configs(deps.storage)?
+        .into_iter()
+        .find(|stable| stable.aterra_contract == CanonicalAddr::from(vec![]))
+    {
+        Some(v) => v,
+        None => return Err(ContractError::Unauthorized {}),
+    };
 
-    config.aterra_contract = deps.api.addr_canonicalize(token_addr.as_str())?;
-    store_config(deps.storage, &config)?;
+    stable.aterra_contract = deps.api.addr_canonicalize(token_addr.as_str())?;
+    store_stable_config(deps.storage, &stable)?;
 
     Ok(Response::new().add_attributes(vec![attr("aterra", token_addr)]))
 }
@@ -263,10 +446,11 @@ pub fn register_contracts(
     distribution_model: Addr,
     collector_contract: Addr,
     distributor_contract: Addr,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
+    let mut stable: StableConfig = read_stable_config(deps.storage, &config.stable_denom)?;
     if config.overseer_contract != CanonicalAddr::from(vec![])
-        || config.interest_model != CanonicalAddr::from(vec![])
+        || stable.interest_model != CanonicalAddr::from(vec![])
         || config.distribution_model != CanonicalAddr::from(vec![])
         || config.collector_contract != CanonicalAddr::from(vec![])
         || config.distributor_contract != CanonicalAddr::from(vec![])
@@ -275,45 +459,187 @@ pub fn register_contracts(
     }
 
     config.overseer_contract = deps.api.addr_canonicalize(overseer_contract.as_str())?;
-    config.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
     config.distribution_model = deps.api.addr_canonicalize(distribution_model.as_str())?;
     config.collector_contract = deps.api.addr_canonicalize(collector_contract.as_str())?;
     config.distributor_contract = deps.api.addr_canonicalize(distributor_contract.as_str())?;
     store_config(deps.storage, &config)?;
 
+    stable.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+    store_stable_config(deps.storage, &stable)?;
+
     Ok(Response::default())
 }
 
-pub fn update_config(
+pub fn register_stable(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
-    interest_model: Option<Addr>,
-    distribution_model: Option<Addr>,
-    max_borrow_factor: Option<Decimal256>,
-) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
+    denom: String,
+    interest_model: Addr,
+    aterra_code_id: u64,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
 
     // permission check
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
+    if read_stable_config(deps.storage, &denom).is_ok() {
+        return Err(ContractError::StableAlreadyRegistered(denom));
     }
 
-    if interest_model.is_some() {
-        let mut state: State = read_state(deps.storage)?;
-        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-        store_state(deps.storage, &state)?;
+    let initial_deposit = info
+        .funds
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+
+    if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
+        return Err(ContractError::InitialFundsNotDeposited(
+            INITIAL_DEPOSIT_AMOUNT,
+            denom,
+        ));
+    }
+
+    let interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+    assert_unshared_interest_model(deps.storage, &denom, &interest_model)?;
+
+    store_stable_config(
+        deps.storage,
+        &StableConfig {
+            denom: denom.clone(),
+            aterra_contract: CanonicalAddr::from(vec![]),
+            interest_model,
+        },
+    )?;
+
+    // ANC rewards are only distributed to the base stable borrowers
+    store_state(
+        deps.storage,
+        &denom,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::zero(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(instantiate_aterra(&env, aterra_code_id, &denom)?)
+        .add_attributes(vec![
+            attr("action", "register_stable"),
+            attr("denom", denom),
+        ]))
+}
 
-        if let Some(interest_model) = interest_model {
-            config.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+/// Adaptive interest models keep the state of a single
+/// stable, so every stable needs its own interest model
+fn assert_unshared_interest_model(
+    storage: &dyn Storage,
+    denom: &str,
+    interest_model: &CanonicalAddr,
+) -> Result<(), ContractError> {
+    for stable in read_stable_configs(storage)? {
+        if stable.denom != denom && stable.interest_model == *interest_model {
+            return Err(ContractError::SharedInterestModel(stable.denom));
         }
     }
 
+    Ok(())
+}
+
+pub fn update_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    interest_model: Addr,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    switch_interest_model(deps, &env, &config, &denom, interest_model.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_stable"),
+        attr("denom", denom),
+        attr("interest_model", interest_model),
+    ]))
+}
+
+/// Interest is accrued at the rate of the previous
+/// model before the new one applies
+fn switch_interest_model(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    denom: &str,
+    interest_model: Addr,
+) -> Result<(), ContractError> {
+    let interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+    assert_unshared_interest_model(deps.storage, denom, &interest_model)?;
+
+    let mut stable: StableConfig = read_stable_config(deps.storage, denom)?;
+    let mut state: State = read_state(deps.storage, denom)?;
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, denom, &rate_snapshot)?;
+    }
+    store_state(deps.storage, denom, &state)?;
+
+    stable.interest_model = interest_model;
+    store_stable_config(deps.storage, &stable)?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    interest_model: Option<Addr>,
+    distribution_model: Option<Addr>,
+    max_borrow_factor: Option<Decimal256>,
+    flash_loan_fee: Option<Decimal256>,
+    stable_rate_premium: Option<Decimal256>,
+    stable_rate_rebalance_threshold: Option<Decimal256>,
+    max_total_debt: Option<Uint256>,
+    max_borrower_debt: Option<Uint256>,
+    guardian: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(interest_model) = interest_model {
+        let denom = config.stable_denom.clone();
+        switch_interest_model(deps.branch(), &env, &config, &denom, interest_model)?;
+    }
+
     if let Some(distribution_model) = distribution_model {
         config.distribution_model = deps.api.addr_canonicalize(distribution_model.as_str())?;
     }
@@ -322,10 +648,142 @@ pub fn update_config(
         config.max_borrow_factor = max_borrow_factor;
     }
 
+    if let Some(flash_loan_fee) = flash_loan_fee {
+        assert_flash_loan_fee(flash_loan_fee)?;
+        config.flash_loan_fee = flash_loan_fee;
+    }
+
+    if let Some(stable_rate_premium) = stable_rate_premium {
+        config.stable_rate_premium = stable_rate_premium;
+    }
+
+    if let Some(stable_rate_rebalance_threshold) = stable_rate_rebalance_threshold {
+        config.stable_rate_rebalance_threshold = stable_rate_rebalance_threshold;
+    }
+
+    // A zero ceiling removes it
+    if let Some(max_total_debt) = max_total_debt {
+        config.max_total_debt = Some(max_total_debt).filter(|v| !v.is_zero());
+    }
+
+    if let Some(max_borrower_debt) = max_borrower_debt {
+        config.max_borrower_debt = Some(max_borrower_debt).filter(|v| !v.is_zero());
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = guardian_to_raw(deps.api, &guardian)?;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner_addr = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<Operation>,
+    paused: bool,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && Some(sender_raw) != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let status = update_pause_status(deps.storage, &operations, paused)?;
+    if !status.borrow && operations.contains(&Operation::Borrow) {
+        assert_loans_can_exit(deps.as_ref(), &config)?;
+    }
+
+    let operation_logs: Vec<String> = operations.iter().map(|o| o.to_string()).collect();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", if paused { "pause" } else { "unpause" }),
+        attr("operations", operation_logs.join(",")),
+    ]))
+}
+
+/// Loans must not be taken while they cannot be repaid or liquidated,
+/// so borrow can only be resumed while the overseer, the liquidation
+/// contract and every custody have repay and liquidate running
+fn assert_loans_can_exit(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let overseer_config = query_overseer_config(deps, overseer.clone())?;
+
+    let mut contracts: Vec<String> =
+        vec![overseer.to_string(), overseer_config.liquidation_contract];
+    let mut start_after: Option<String> = None;
+    loop {
+        let whitelist = query_whitelist(deps, overseer.clone(), start_after)?;
+        start_after = match whitelist.elems.last() {
+            Some(elem) => Some(elem.collateral_token.clone()),
+            None => break,
+        };
+        contracts.extend(
+            whitelist
+                .elems
+                .into_iter()
+                .map(|elem| elem.custody_contract),
+        );
+    }
+
+    for contract in contracts {
+        let status = query_pause_status(deps, contract.clone())?;
+        if status.repay || status.liquidate {
+            return Err(ContractError::LoanExitPaused(contract));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn execute_epoch_operations(
     deps: DepsMut,
     env: Env,
@@ -334,128 +792,240 @@ pub fn execute_epoch_operations(
     target_deposit_rate: Decimal256,
     threshold_deposit_rate: Decimal256,
     distributed_interest: Uint256,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
     if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
         return Err(ContractError::Unauthorized {});
     }
 
-    let mut state: State = read_state(deps.storage)?;
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    let mut attributes = vec![attr("action", "execute_epoch_operations")];
+    for stable in read_stable_configs(deps.storage)? {
+        let is_base = stable.denom == config.stable_denom;
+        let mut state: State = read_state(deps.storage, &stable.denom)?;
 
-    // Compute interest and reward before updating anc_emission_rate
-    let aterra_supply = query_supply(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.aterra_contract)?,
-    )?;
-    let balance: Uint256 = query_balance(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - distributed_interest;
+        // Deposit rates are only reported for the base stable,
+        // so the interest was only distributed in the base denom
+        let distributed_interest = if is_base {
+            distributed_interest
+        } else {
+            Uint256::zero()
+        };
 
-    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.interest_model)?,
-        balance,
-        state.total_liabilities,
-        state.total_reserves,
-    )?;
+        // Compute interest and reward before updating anc_emission_rate
+        let aterra_supply = query_supply(
+            deps.as_ref(),
+            deps.api.addr_humanize(&stable.aterra_contract)?,
+        )?;
+        let balance: Uint256 = query_balance(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.contract_addr)?,
+            stable.denom.to_string(),
+        )? - distributed_interest;
 
-    compute_interest_raw(
-        &mut state,
-        env.block.height,
-        balance,
-        aterra_supply,
-        borrow_rate_res.rate,
-        target_deposit_rate,
-    );
+        let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+            deps.as_ref(),
+            deps.api.addr_humanize(&stable.interest_model)?,
+            balance,
+            state.total_liabilities,
+            state.total_reserves,
+        )?;
 
-    // recompute prev_exchange_rate with distributed_interest
-    state.prev_exchange_rate =
-        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+        if let Some(rate_snapshot) = compute_interest_raw(
+            &mut state,
+            env.block.height,
+            balance,
+            aterra_supply,
+            borrow_rate_res.rate,
+            target_deposit_rate,
+        ) {
+            store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+        }
+
+        // recompute prev_exchange_rate with distributed_interest
+        state.prev_exchange_rate =
+            compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+
+        compute_reward(&mut state, env.block.height);
+
+        // Compute total_reserves to fund collector contract
+        // Update total_reserves and send it to collector contract
+        // only when there is enough balance
+        let total_reserves = state.total_reserves * Uint256::one();
+        let mut market_balance = balance;
+        if !total_reserves.is_zero() && balance > total_reserves {
+            state.total_reserves = state.total_reserves - Decimal256::from_uint256(total_reserves);
+            market_balance = balance - total_reserves;
 
-    compute_reward(&mut state, env.block.height);
-
-    // Compute total_reserves to fund collector contract
-    // Update total_reserves and send it to collector contract
-    // only when there is enough balance
-    let total_reserves = state.total_reserves * Uint256::one();
-    let messages: Vec<CosmosMsg> = if !total_reserves.is_zero() && balance > total_reserves {
-        state.total_reserves = state.total_reserves - Decimal256::from_uint256(total_reserves);
-
-        vec![CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_contract)?
-                .to_string(),
-            amount: vec![deduct_tax(
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: deps
+                    .api
+                    .addr_humanize(&config.collector_contract)?
+                    .to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: stable.denom.clone(),
+                        amount: total_reserves.into(),
+                    },
+                )?],
+            }));
+        }
+
+        // Feed the epoch market status to the interest model
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&stable.interest_model)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&InterestExecuteMsg::ExecuteEpochOperations {
+                market_balance,
+                total_liabilities: state.total_liabilities,
+                total_reserves: state.total_reserves,
+            })?,
+        }));
+
+        if is_base {
+            // Query updated anc_emission_rate
+            state.anc_emission_rate = query_anc_emission_rate(
                 deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: total_reserves.into(),
-                },
-            )?],
-        })]
-    } else {
-        vec![]
-    };
+                deps.api.addr_humanize(&config.distribution_model)?,
+                deposit_rate,
+                target_deposit_rate,
+                threshold_deposit_rate,
+                state.anc_emission_rate,
+            )?
+            .emission_rate;
 
-    // Query updated anc_emission_rate
-    state.anc_emission_rate = query_anc_emission_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.distribution_model)?,
-        deposit_rate,
-        target_deposit_rate,
-        threshold_deposit_rate,
-        state.anc_emission_rate,
-    )?
-    .emission_rate;
-
-    store_state(deps.storage, &state)?;
-
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_epoch_operations"),
-        attr("total_reserves", total_reserves),
-        attr("anc_emission_rate", state.anc_emission_rate.to_string()),
+            attributes.push(attr("total_reserves", total_reserves));
+            attributes.push(attr(
+                "anc_emission_rate",
+                state.anc_emission_rate.to_string(),
+            ));
+        } else {
+            attributes.push(attr(
+                format!("total_reserves_{}", stable.denom),
+                total_reserves,
+            ));
+        }
+
+        store_state(deps.storage, &stable.denom, &state)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+pub fn migrate_legacy_borrowers(
+    deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let completed = migrate_borrowers(deps.storage, limit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate_borrowers"),
+        attr("completed", completed.to_string()),
     ]))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if is_older_version(&stored_version, "0.4.0")? {
+        migrate_state(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    if matches!(
+        msg,
+        QueryMsg::BorrowerInfo { .. }
+            | QueryMsg::BorrowerInfos { .. }
+            | QueryMsg::BorrowerLoanValue { .. }
+    ) {
+        assert_borrowers_migrated(deps.storage)?;
+    }
+
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::State { block_height } => to_binary(&query_state(deps, env, block_height)?),
+        QueryMsg::PauseStatus {} => to_binary(&read_pause_status(deps.storage)?),
+        QueryMsg::Stables {} => to_binary(&query_stables(deps)?),
+        QueryMsg::State {
+            block_height,
+            denom,
+        } => to_binary(&query_state(deps, env, block_height, denom)?),
         QueryMsg::EpochState {
             block_height,
             distributed_interest,
+            denom,
         } => to_binary(&query_epoch_state(
             deps,
             block_height,
             distributed_interest,
+            denom,
         )?),
         QueryMsg::BorrowerInfo {
             borrower,
             block_height,
+            denom,
         } => to_binary(&query_borrower_info(
             deps,
             env,
             deps.api.addr_validate(&borrower)?,
             block_height,
+            denom,
         )?),
-        QueryMsg::BorrowerInfos { start_after, limit } => to_binary(&query_borrower_infos(
+        QueryMsg::BorrowerInfos {
+            start_after,
+            limit,
+            denom,
+        } => to_binary(&query_borrower_infos(
             deps,
             optional_addr_validate(deps.api, start_after)?,
             limit,
+            denom,
+        )?),
+        QueryMsg::BorrowerLoanValue {
+            borrower,
+            block_height,
+            block_time,
+        } => to_binary(&query_borrower_loan_value(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            block_height,
+            block_time,
+        )?),
+        QueryMsg::RateHistory {
+            start_after,
+            limit,
+            denom,
+        } => to_binary(&query_rate_history(deps, start_after, limit, denom)?),
+        QueryMsg::AverageBorrowRate {
+            from_height,
+            to_height,
+            denom,
+        } => to_binary(&query_average_borrow_rate(
+            deps,
+            from_height,
+            to_height,
+            denom,
         )?),
     }
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable_config(deps.storage, &config.stable_denom)?;
     Ok(ConfigResponse {
         owner_addr: deps.api.addr_humanize(&config.owner_addr)?.to_string(),
-        aterra_contract: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-        interest_model: deps.api.addr_humanize(&config.interest_model)?.to_string(),
+        aterra_contract: deps.api.addr_humanize(&stable.aterra_contract)?.to_string(),
+        interest_model: deps.api.addr_humanize(&stable.interest_model)?.to_string(),
         distribution_model: deps
             .api
             .addr_humanize(&config.distribution_model)?
@@ -474,11 +1044,42 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         max_borrow_factor: config.max_borrow_factor,
+        flash_loan_fee: config.flash_loan_fee,
+        stable_rate_premium: config.stable_rate_premium,
+        stable_rate_rebalance_threshold: config.stable_rate_rebalance_threshold,
+        max_total_debt: config.max_total_debt,
+        max_borrower_debt: config.max_borrower_debt,
+        guardian: match config.guardian {
+            Some(guardian) => Some(deps.api.addr_humanize(&guardian)?.to_string()),
+            None => None,
+        },
     })
 }
 
-pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult<StateResponse> {
-    let mut state: State = read_state(deps.storage)?;
+pub fn query_stables(deps: Deps) -> StdResult<StablesResponse> {
+    let stables: Vec<StableResponse> = read_stable_configs(deps.storage)?
+        .into_iter()
+        .map(|stable| {
+            Ok(StableResponse {
+                denom: stable.denom,
+                aterra_contract: deps.api.addr_humanize(&stable.aterra_contract)?.to_string(),
+                interest_model: deps.api.addr_humanize(&stable.interest_model)?.to_string(),
+            })
+        })
+        .collect::<StdResult<Vec<StableResponse>>>()?;
+
+    Ok(StablesResponse { stables })
+}
+
+pub fn query_state(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+    denom: Option<String>,
+) -> StdResult<StateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
     let block_height = if let Some(block_height) = block_height {
         block_height
@@ -498,10 +1099,8 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         ));
     }
 
-    let config: Config = read_config(deps.storage)?;
-
     // Compute interest rate with given block height
-    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_interest(deps, &config, &stable, &mut state, block_height, None)?;
 
     // Compute reward rate with given block height
     compute_reward(&mut state, block_height);
@@ -516,6 +1115,8 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         anc_emission_rate: state.anc_emission_rate,
         prev_aterra_supply: state.prev_aterra_supply,
         prev_exchange_rate: state.prev_exchange_rate,
+        total_stable_liabilities: state.total_stable_liabilities,
+        avg_stable_rate: state.avg_stable_rate,
     })
 }
 
@@ -523,16 +1124,18 @@ pub fn query_epoch_state(
     deps: Deps,
     block_height: Option<u64>,
     distributed_interest: Option<Uint256>,
+    denom: Option<String>,
 ) -> StdResult<EpochStateResponse> {
     let config: Config = read_config(deps.storage)?;
-    let mut state: State = read_state(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
 
     let distributed_interest = distributed_interest.unwrap_or_else(Uint256::zero);
-    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&stable.aterra_contract)?)?;
     let balance = query_balance(
         deps,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
+        stable.denom.to_string(),
     )? - distributed_interest;
 
     if let Some(block_height) = block_height {
@@ -544,7 +1147,7 @@ pub fn query_epoch_state(
 
         let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
             deps,
-            deps.api.addr_humanize(&config.interest_model)?,
+            deps.api.addr_humanize(&stable.interest_model)?,
             balance,
             state.total_liabilities,
             state.total_reserves,
@@ -6,52 +6,69 @@ use cosmwasm_std::{
 
 use crate::borrow::{compute_interest, compute_reward};
 use crate::error::ContractError;
-use crate::state::{read_config, read_state, store_state, Config, State};
+use crate::flash_loan::assert_no_flash_loan;
+use crate::state::{
+    read_config, read_stable, read_state, store_rate_snapshot, store_state, Config, StableConfig,
+    State,
+};
 
 use cw20::Cw20ExecuteMsg;
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use terra_cosmwasm::TerraMsgWrapper;
 
 pub fn deposit_stable(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-) -> Result<Response, ContractError> {
+    denom: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
 
-    // Check base denom deposit
+    // Check stable denom deposit
     let deposit_amount: Uint256 = info
         .funds
         .iter()
-        .find(|c| c.denom == config.stable_denom)
+        .find(|c| c.denom == stable.denom)
         .map(|c| Uint256::from(c.amount))
         .unwrap_or_else(Uint256::zero);
 
     // Cannot deposit zero amount
     if deposit_amount.is_zero() {
-        return Err(ContractError::ZeroDeposit(config.stable_denom));
+        return Err(ContractError::ZeroDeposit(stable.denom));
     }
 
     // Update interest related state
-    let mut state: State = read_state(deps.storage)?;
-    compute_interest(
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+    if let Some(rate_snapshot) = compute_interest(
         deps.as_ref(),
         &config,
+        &stable,
         &mut state,
         env.block.height,
         Some(deposit_amount),
-    )?;
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
-    let exchange_rate =
-        compute_exchange_rate(deps.as_ref(), &config, &state, Some(deposit_amount))?;
+    let exchange_rate = compute_exchange_rate(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &state,
+        Some(deposit_amount),
+    )?;
     let mint_amount = deposit_amount / exchange_rate;
 
     state.prev_aterra_supply += mint_amount;
-    store_state(deps.storage, &state)?;
+    store_state(deps.storage, &stable.denom, &state)?;
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            contract_addr: deps.api.addr_humanize(&stable.aterra_contract)?.to_string(),
             funds: vec![],
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: info.sender.to_string(),
@@ -69,35 +86,47 @@ pub fn deposit_stable(
 pub fn redeem_stable(
     deps: DepsMut,
     env: Env,
+    stable: &StableConfig,
     sender: Addr,
     burn_amount: Uint128,
-) -> Result<Response, ContractError> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
     let config: Config = read_config(deps.storage)?;
 
     // Update interest related state
-    let mut state: State = read_state(deps.storage)?;
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
-    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, stable, &state, None)?;
     let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
 
     let current_balance = query_balance(
         deps.as_ref(),
         env.contract.address,
-        config.stable_denom.to_string(),
+        stable.denom.to_string(),
     )?;
 
     // Assert redeem amount
-    assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
+    assert_redeem_amount(stable, &state, current_balance, redeem_amount)?;
 
     state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
-    store_state(deps.storage, &state)?;
+    store_state(deps.storage, &stable.denom, &state)?;
     Ok(Response::new()
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+                contract_addr: deps.api.addr_humanize(&stable.aterra_contract)?.to_string(),
                 funds: vec![],
                 msg: to_binary(&Cw20ExecuteMsg::Burn {
                     amount: burn_amount,
@@ -108,7 +137,7 @@ pub fn redeem_stable(
                 amount: vec![deduct_tax(
                     deps.as_ref(),
                     Coin {
-                        denom: config.stable_denom,
+                        denom: stable.denom.clone(),
                         amount: redeem_amount.into(),
                     },
                 )?],
@@ -122,7 +151,7 @@ pub fn redeem_stable(
 }
 
 fn assert_redeem_amount(
-    config: &Config,
+    stable: &StableConfig,
     state: &State,
     current_balance: Uint256,
     redeem_amount: Uint256,
@@ -130,9 +159,7 @@ fn assert_redeem_amount(
     let current_balance = Decimal256::from_uint256(current_balance);
     let redeem_amount = Decimal256::from_uint256(redeem_amount);
     if redeem_amount + state.total_reserves > current_balance {
-        return Err(ContractError::NoStableAvailable(
-            config.stable_denom.clone(),
-        ));
+        return Err(ContractError::NoStableAvailable(stable.denom.clone()));
     }
 
     Ok(())
@@ -141,14 +168,15 @@ fn assert_redeem_amount(
 pub(crate) fn compute_exchange_rate(
     deps: Deps,
     config: &Config,
+    stable: &StableConfig,
     state: &State,
     deposit_amount: Option<Uint256>,
 ) -> StdResult<Decimal256> {
-    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&stable.aterra_contract)?)?;
     let balance = query_balance(
         deps,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
+        stable.denom.to_string(),
     )? - deposit_amount.unwrap_or_else(Uint256::zero);
 
     Ok(compute_exchange_rate_raw(state, aterra_supply, balance))
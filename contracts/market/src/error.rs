@@ -15,21 +15,54 @@ pub enum ContractError {
     #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
     BorrowExceedsLimit(u128),
 
+    #[error("Flash loan in progress; market operations are locked")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan not repaid; {0} must be returned with the fee")]
+    FlashLoanNotRepaid(String),
+
     #[error("Must deposit initial funds {0}{0}")]
     InitialFundsNotDeposited(u128, String),
 
+    #[error("Flash loan fee must be less than 1")]
+    InvalidFlashLoanFee {},
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
+    #[error("Repay or liquidate is paused in {0}; borrow cannot be resumed")]
+    LoanExitPaused(String),
+
+    #[error("Borrow amount too high; Loan liability becomes greater than the debt ceiling of the borrower: {0}")]
+    MaxBorrowerDebtReached(u128),
+
     #[error("Exceeds {0} max borrow factor; borrow demand too high")]
     MaxBorrowFactorReached(String),
 
+    #[error("Borrow amount too high; Total liabilities become greater than the debt ceiling: {0}")]
+    MaxTotalDebtReached(u128),
+
     #[error("Invalid request: \"redeem stable\" message not included in request")]
     MissingRedeemStableHook {},
 
+    #[error("No {0} loan to swap")]
+    NoLoan(String),
+
     #[error("Not enough {0} available; borrow demand too high")]
     NoStableAvailable(String),
 
+    #[error("Rate mode differs from the existing {0} rate loan; swap the rate mode first")]
+    RateModeMismatch(String),
+
+    #[error("Stable rate has not drifted beyond the rebalance threshold")]
+    RebalanceNotAllowed {},
+
+    #[error("Interest model is already used by the {0} stable")]
+    SharedInterestModel(String),
+
+    #[error("Stable denom is already registered: {0}")]
+    StableAlreadyRegistered(String),
+
     #[error("Deposit amount must be greater than 0 {0}")]
     ZeroDeposit(String),
 
@@ -0,0 +1,153 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, BankMsg, Binary, Coin, CosmosMsg, DepsMut, Env, Response, Storage, SubMsg, WasmMsg,
+};
+
+use crate::borrow::compute_interest;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_flash_loan, read_stable, read_state, remove_flash_loan, store_flash_loan,
+    store_rate_snapshot, store_state, Config, FlashLoan, StableConfig, State,
+};
+use moneymarket::querier::{deduct_tax, query_balance};
+use terra_cosmwasm::TerraMsgWrapper;
+
+pub const FLASH_LOAN_REPLY_ID: u64 = 2;
+
+/// Deposit, redeem, borrow and epoch operations change the
+/// market balance, so they are locked while a loan is out
+pub fn assert_no_flash_loan(storage: &dyn Storage) -> Result<(), ContractError> {
+    if read_flash_loan(storage)?.is_some() {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
+    Ok(())
+}
+
+/// The fee is a share of the loaned amount
+pub fn assert_flash_loan_fee(flash_loan_fee: Decimal256) -> Result<(), ContractError> {
+    if flash_loan_fee >= Decimal256::one() {
+        return Err(ContractError::InvalidFlashLoanFee {});
+    }
+
+    Ok(())
+}
+
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    amount: Uint256,
+    callback_contract: Addr,
+    callback_msg: Binary,
+    denom: Option<String>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    assert_no_flash_loan(deps.storage)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let stable: StableConfig = read_stable(deps.storage, &config, denom)?;
+
+    // Accrue interest before recording the liabilities
+    let mut state: State = read_state(deps.storage, &stable.denom)?;
+    if let Some(rate_snapshot) = compute_interest(
+        deps.as_ref(),
+        &config,
+        &stable,
+        &mut state,
+        env.block.height,
+        None,
+    )? {
+        store_rate_snapshot(deps.storage, &stable.denom, &rate_snapshot)?;
+    }
+    store_state(deps.storage, &stable.denom, &state)?;
+
+    let balance = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        stable.denom.to_string(),
+    )?;
+
+    // Reserves cannot be lent
+    if Decimal256::from_uint256(amount) + state.total_reserves > Decimal256::from_uint256(balance) {
+        return Err(ContractError::NoStableAvailable(stable.denom));
+    }
+
+    let fee = amount * config.flash_loan_fee;
+    store_flash_loan(
+        deps.storage,
+        &FlashLoan {
+            denom: stable.denom.clone(),
+            amount,
+            fee,
+            prev_balance: balance,
+            prev_liabilities: state.total_liabilities,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: callback_contract.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: stable.denom.clone(),
+                    amount: amount.into(),
+                },
+            )?],
+        }))
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: callback_contract.to_string(),
+                funds: vec![],
+                msg: callback_msg,
+            }),
+            FLASH_LOAN_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "flash_loan"),
+            attr("callback_contract", callback_contract),
+            attr("denom", stable.denom),
+            attr("amount", amount),
+            attr("fee", fee),
+        ]))
+}
+
+/// Repayments made during the loan move the balance and the
+/// liabilities together, so the loan is returned only when their
+/// sum grew by the fee
+pub fn flash_loan_callback(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let flash_loan: FlashLoan = match read_flash_loan(deps.storage)? {
+        Some(v) => v,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+
+    let mut state: State = read_state(deps.storage, &flash_loan.denom)?;
+    let balance = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        flash_loan.denom.to_string(),
+    )?;
+
+    if Decimal256::from_uint256(balance) + state.total_liabilities
+        < Decimal256::from_uint256(flash_loan.prev_balance + flash_loan.fee)
+            + flash_loan.prev_liabilities
+    {
+        return Err(ContractError::FlashLoanNotRepaid(format!(
+            "{}{}",
+            flash_loan.amount + flash_loan.fee,
+            flash_loan.denom
+        )));
+    }
+
+    state.total_reserves += Decimal256::from_uint256(flash_loan.fee);
+    store_state(deps.storage, &flash_loan.denom, &state)?;
+    remove_flash_loan(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "flash_loan_repaid"),
+        attr("denom", flash_loan.denom),
+        attr("fee", flash_loan.fee),
+    ]))
+}
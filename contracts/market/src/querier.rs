@@ -3,7 +3,11 @@ use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
 
 use moneymarket::distribution_model::{AncEmissionRateResponse, QueryMsg as DistributionQueryMsg};
 use moneymarket::interest_model::{BorrowRateResponse, QueryMsg as InterestQueryMsg};
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ConfigResponse, IsolatedDebtResponse, QueryMsg as OverseerQueryMsg,
+    WhitelistResponse,
+};
+use moneymarket::pause::PauseStatus;
 
 pub fn query_borrow_rate(
     deps: Deps,
@@ -43,6 +47,22 @@ pub fn query_borrow_limit(
     Ok(borrow_limit)
 }
 
+pub fn query_isolated_debt(
+    deps: Deps,
+    overseer_addr: Addr,
+    borrower: Addr,
+) -> StdResult<IsolatedDebtResponse> {
+    let isolated_debt: IsolatedDebtResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_addr.to_string(),
+            msg: to_binary(&OverseerQueryMsg::IsolatedDebt {
+                borrower: borrower.to_string(),
+            })?,
+        }))?;
+
+    Ok(isolated_debt)
+}
+
 pub fn query_anc_emission_rate(
     deps: Deps,
     distribution_model: Addr,
@@ -65,12 +85,47 @@ pub fn query_anc_emission_rate(
     Ok(anc_emission_rate)
 }
 
-pub fn query_target_deposit_rate(deps: Deps, overseer_contract: Addr) -> StdResult<Decimal256> {
+pub fn query_overseer_config(deps: Deps, overseer_contract: Addr) -> StdResult<ConfigResponse> {
     let overseer_config: ConfigResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: overseer_contract.to_string(),
             msg: to_binary(&OverseerQueryMsg::Config {})?,
         }))?;
 
+    Ok(overseer_config)
+}
+
+pub fn query_whitelist(
+    deps: Deps,
+    overseer_contract: Addr,
+    start_after: Option<String>,
+) -> StdResult<WhitelistResponse> {
+    let whitelist: WhitelistResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_contract.to_string(),
+            msg: to_binary(&OverseerQueryMsg::Whitelist {
+                collateral_token: None,
+                start_after,
+                limit: None,
+            })?,
+        }))?;
+
+    Ok(whitelist)
+}
+
+/// The overseer, liquidation and custody contracts
+/// all answer the same pause status query
+pub fn query_pause_status(deps: Deps, contract_addr: String) -> StdResult<PauseStatus> {
+    let pause_status: PauseStatus = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr,
+        msg: to_binary(&OverseerQueryMsg::PauseStatus {})?,
+    }))?;
+
+    Ok(pause_status)
+}
+
+pub fn query_target_deposit_rate(deps: Deps, overseer_contract: Addr) -> StdResult<Decimal256> {
+    let overseer_config: ConfigResponse = query_overseer_config(deps, overseer_contract)?;
+
     Ok(overseer_config.target_deposit_rate)
 }
@@ -2,28 +2,58 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::market::{BorrowerInfoResponse, RateMode};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
+const KEY_FLASH_LOAN: &[u8] = b"flash_loan";
+const KEY_BORROWER_MIGRATION: &[u8] = b"borrower_migration";
 
+const PREFIX_STABLE: &[u8] = b"stable";
+const PREFIX_STABLE_STATE: &[u8] = b"stable_state";
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_STABLE_LIABILITY: &[u8] = b"stable_liability";
+const PREFIX_RATE_HISTORY: &[u8] = b"rate_history";
+const PREFIX_RATE_HISTORY_COUNT: &[u8] = b"rate_history_count";
+
+/// Number of rate snapshots kept in the ring buffer
+/// of each stable, older snapshots are overwritten
+pub const RATE_HISTORY_SIZE: u64 = 1000;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub contract_addr: CanonicalAddr,
     pub owner_addr: CanonicalAddr,
-    pub aterra_contract: CanonicalAddr,
-    pub interest_model: CanonicalAddr,
     pub distribution_model: CanonicalAddr,
     pub overseer_contract: CanonicalAddr,
     pub collector_contract: CanonicalAddr,
     pub distributor_contract: CanonicalAddr,
+    /// Base stable denom, liabilities of all stables are
+    /// priced in this denom and liquidations are repaid in it
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub flash_loan_fee: Decimal256,
+    pub stable_rate_premium: Decimal256,
+    pub stable_rate_rebalance_threshold: Decimal256,
+    /// Ceilings of the loan value of all borrowers and of a
+    /// single borrower in the base stable denom, unlimited if not set
+    pub max_total_debt: Option<Uint256>,
+    pub max_borrower_debt: Option<Uint256>,
+    /// Address allowed to pause operations besides the owner
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
+}
+
+/// Registered stable asset, each stable has
+/// its own aterra token and interest model
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StableConfig {
+    pub denom: String,
+    pub aterra_contract: CanonicalAddr,
+    pub interest_model: CanonicalAddr,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,6 +67,10 @@ pub struct State {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    /// Part of `total_liabilities` borrowed at stable rates,
+    /// it accrues with `avg_stable_rate` instead of the borrow rate
+    pub total_stable_liabilities: Decimal256,
+    pub avg_stable_rate: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -45,6 +79,33 @@ pub struct BorrowerInfo {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    pub rate_mode: RateMode,
+    pub stable_rate: Decimal256,
+    /// Stable rate loans accrue from this height
+    pub last_interest_updated: u64,
+}
+
+/// Rates applied by an interest accrual, the borrow rate
+/// covers the blocks since the previous accrual
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSnapshot {
+    pub block_height: u64,
+    pub borrow_rate: Decimal256,
+    pub deposit_rate: Decimal256,
+    pub exchange_rate: Decimal256,
+    pub utilization_ratio: Decimal256,
+}
+
+/// Flash loan waiting for its callback to return,
+/// the market balance and liabilities are recorded
+/// before the loan is sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoan {
+    pub denom: String,
+    pub amount: Uint256,
+    pub fee: Uint256,
+    pub prev_balance: Uint256,
+    pub prev_liabilities: Decimal256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -55,44 +116,128 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
-pub fn store_state(storage: &mut dyn Storage, data: &State) -> StdResult<()> {
-    Singleton::new(storage, KEY_STATE).save(data)
+pub fn store_flash_loan(storage: &mut dyn Storage, data: &FlashLoan) -> StdResult<()> {
+    Singleton::new(storage, KEY_FLASH_LOAN).save(data)
+}
+
+pub fn read_flash_loan(storage: &dyn Storage) -> StdResult<Option<FlashLoan>> {
+    ReadonlySingleton::new(storage, KEY_FLASH_LOAN).may_load()
+}
+
+pub fn remove_flash_loan(storage: &mut dyn Storage) {
+    Singleton::<FlashLoan>::new(storage, KEY_FLASH_LOAN).remove()
+}
+
+pub fn store_stable_config(storage: &mut dyn Storage, data: &StableConfig) -> StdResult<()> {
+    bucket(storage, PREFIX_STABLE).save(data.denom.as_bytes(), data)
+}
+
+pub fn read_stable_config(storage: &dyn Storage, denom: &str) -> StdResult<StableConfig> {
+    match bucket_read(storage, PREFIX_STABLE).load(denom.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err(format!(
+            "Stable denom is not registered: {}",
+            denom
+        ))),
+    }
+}
+
+/// Loads the given stable config, defaulting to the base stable denom
+pub fn read_stable(
+    storage: &dyn Storage,
+    config: &Config,
+    denom: Option<String>,
+) -> StdResult<StableConfig> {
+    read_stable_config(storage, denom.as_deref().unwrap_or(&config.stable_denom))
 }
 
-pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
-    ReadonlySingleton::new(storage, KEY_STATE).load()
+/// Registered stables are few, so they are always read at once
+pub fn read_stable_configs(storage: &dyn Storage) -> StdResult<Vec<StableConfig>> {
+    let stable_bucket: ReadonlyBucket<StableConfig> = bucket_read(storage, PREFIX_STABLE);
+    stable_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (_, v) = elem?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn store_state(storage: &mut dyn Storage, denom: &str, data: &State) -> StdResult<()> {
+    bucket(storage, PREFIX_STABLE_STATE).save(denom.as_bytes(), data)
+}
+
+pub fn read_state(storage: &dyn Storage, denom: &str) -> StdResult<State> {
+    bucket_read(storage, PREFIX_STABLE_STATE).load(denom.as_bytes())
 }
 
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
+    denom: &str,
     borrower: &CanonicalAddr,
     liability: &BorrowerInfo,
 ) -> StdResult<()> {
-    bucket(storage, PREFIX_LIABILITY).save(borrower.as_slice(), liability)
+    Bucket::multilevel(storage, &[PREFIX_STABLE_LIABILITY, denom.as_bytes()])
+        .save(borrower.as_slice(), liability)
 }
 
-pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> BorrowerInfo {
-    match bucket_read(storage, PREFIX_LIABILITY).load(borrower.as_slice()) {
+pub fn read_borrower_info(
+    storage: &dyn Storage,
+    denom: &str,
+    borrower: &CanonicalAddr,
+) -> BorrowerInfo {
+    match ReadonlyBucket::multilevel(storage, &[PREFIX_STABLE_LIABILITY, denom.as_bytes()])
+        .load(borrower.as_slice())
+    {
         Ok(v) => v,
         _ => BorrowerInfo {
             interest_index: Decimal256::one(),
             reward_index: Decimal256::zero(),
             loan_amount: Uint256::zero(),
             pending_rewards: Decimal256::zero(),
+            rate_mode: RateMode::Variable,
+            stable_rate: Decimal256::zero(),
+            last_interest_updated: 0,
         },
     }
 }
 
+pub fn store_rate_snapshot(
+    storage: &mut dyn Storage,
+    denom: &str,
+    snapshot: &RateSnapshot,
+) -> StdResult<()> {
+    let count = read_rate_history_count(storage, denom);
+    Bucket::multilevel(storage, &[PREFIX_RATE_HISTORY, denom.as_bytes()])
+        .save(&(count % RATE_HISTORY_SIZE).to_be_bytes(), snapshot)?;
+    bucket(storage, PREFIX_RATE_HISTORY_COUNT).save(denom.as_bytes(), &(count + 1))
+}
+
+/// Returns the total number of snapshots ever written
+pub fn read_rate_history_count(storage: &dyn Storage, denom: &str) -> u64 {
+    bucket_read(storage, PREFIX_RATE_HISTORY_COUNT)
+        .load(denom.as_bytes())
+        .unwrap_or_default()
+}
+
+/// Snapshots are addressed with a sequence number, only the last
+/// `RATE_HISTORY_SIZE` sequences are still in the ring buffer
+pub fn read_rate_snapshot(storage: &dyn Storage, denom: &str, seq: u64) -> StdResult<RateSnapshot> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_RATE_HISTORY, denom.as_bytes()])
+        .load(&(seq % RATE_HISTORY_SIZE).to_be_bytes())
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
 pub fn read_borrower_infos(
     deps: Deps,
+    denom: &str,
     start_after: Option<CanonicalAddr>,
     limit: Option<u32>,
 ) -> StdResult<Vec<BorrowerInfoResponse>> {
     let liability_bucket: ReadonlyBucket<BorrowerInfo> =
-        bucket_read(deps.storage, PREFIX_LIABILITY);
+        ReadonlyBucket::multilevel(deps.storage, &[PREFIX_STABLE_LIABILITY, denom.as_bytes()]);
 
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after);
@@ -105,10 +250,13 @@ pub fn read_borrower_infos(
             let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
             Ok(BorrowerInfoResponse {
                 borrower,
+                denom: denom.to_string(),
                 interest_index: v.interest_index,
                 reward_index: v.reward_index,
                 loan_amount: v.loan_amount,
                 pending_rewards: v.pending_rewards,
+                rate_mode: v.rate_mode,
+                stable_rate: v.stable_rate,
             })
         })
         .collect()
@@ -122,3 +270,202 @@ fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
         v
     })
 }
+
+/// Returns snapshots recorded after `start_after` height in ascending order
+pub fn read_rate_history(
+    storage: &dyn Storage,
+    denom: &str,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RateSnapshot>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as u64;
+    let count = read_rate_history_count(storage, denom);
+    let start = match start_after {
+        Some(start_after) => find_rate_snapshot_seq(storage, denom, start_after)?,
+        None => count.saturating_sub(RATE_HISTORY_SIZE),
+    };
+
+    (start..count.min(start + limit))
+        .map(|seq| read_rate_snapshot(storage, denom, seq))
+        .collect()
+}
+
+/// Returns the sequence of the first snapshot recorded after
+/// `block_height`, snapshot heights are strictly increasing
+/// so it can be found with binary search
+pub fn find_rate_snapshot_seq(
+    storage: &dyn Storage,
+    denom: &str,
+    block_height: u64,
+) -> StdResult<u64> {
+    let mut end = read_rate_history_count(storage, denom);
+    let mut start = end.saturating_sub(RATE_HISTORY_SIZE);
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if read_rate_snapshot(storage, denom, mid)?.block_height <= block_height {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+
+    Ok(start)
+}
+
+/// Config layout of 0.3.x deployments, which
+/// supported a single stable denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub contract_addr: CanonicalAddr,
+    pub owner_addr: CanonicalAddr,
+    pub aterra_contract: CanonicalAddr,
+    pub interest_model: CanonicalAddr,
+    pub distribution_model: CanonicalAddr,
+    pub overseer_contract: CanonicalAddr,
+    pub collector_contract: CanonicalAddr,
+    pub distributor_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub max_borrow_factor: Decimal256,
+}
+
+/// State layout of 0.3.x deployments
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub total_liabilities: Decimal256,
+    pub total_reserves: Decimal256,
+    pub last_interest_updated: u64,
+    pub last_reward_updated: u64,
+    pub global_interest_index: Decimal256,
+    pub global_reward_index: Decimal256,
+    pub anc_emission_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+}
+
+/// Borrower info layout of 0.3.x deployments,
+/// all loans accrued with the variable rate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyBorrowerInfo {
+    pub interest_index: Decimal256,
+    pub reward_index: Decimal256,
+    pub loan_amount: Uint256,
+    pub pending_rewards: Decimal256,
+}
+
+/// Moves the single stable config and state of 0.3.x deployments
+/// under the base stable denom, their borrower infos are moved
+/// afterwards in pages by `migrate_borrowers`
+pub fn migrate_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = ReadonlySingleton::new(storage, KEY_CONFIG).load()?;
+    let legacy_state: LegacyState = ReadonlySingleton::new(storage, KEY_STATE).load()?;
+    let denom = legacy_config.stable_denom.clone();
+
+    store_config(
+        storage,
+        &Config {
+            contract_addr: legacy_config.contract_addr,
+            owner_addr: legacy_config.owner_addr,
+            distribution_model: legacy_config.distribution_model,
+            overseer_contract: legacy_config.overseer_contract,
+            collector_contract: legacy_config.collector_contract,
+            distributor_contract: legacy_config.distributor_contract,
+            stable_denom: legacy_config.stable_denom,
+            max_borrow_factor: legacy_config.max_borrow_factor,
+            flash_loan_fee: Decimal256::zero(),
+            stable_rate_premium: Decimal256::zero(),
+            stable_rate_rebalance_threshold: Decimal256::zero(),
+            max_total_debt: None,
+            max_borrower_debt: None,
+            guardian: None,
+        },
+    )?;
+    store_stable_config(
+        storage,
+        &StableConfig {
+            denom: denom.clone(),
+            aterra_contract: legacy_config.aterra_contract,
+            interest_model: legacy_config.interest_model,
+        },
+    )?;
+
+    store_state(
+        storage,
+        &denom,
+        &State {
+            total_liabilities: legacy_state.total_liabilities,
+            total_reserves: legacy_state.total_reserves,
+            last_interest_updated: legacy_state.last_interest_updated,
+            last_reward_updated: legacy_state.last_reward_updated,
+            global_interest_index: legacy_state.global_interest_index,
+            global_reward_index: legacy_state.global_reward_index,
+            anc_emission_rate: legacy_state.anc_emission_rate,
+            prev_aterra_supply: legacy_state.prev_aterra_supply,
+            prev_exchange_rate: legacy_state.prev_exchange_rate,
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        },
+    )?;
+    Singleton::<LegacyState>::new(storage, KEY_STATE).remove();
+
+    Singleton::new(storage, KEY_BORROWER_MIGRATION).save(&legacy_state.last_interest_updated)
+}
+
+/// Moves a page of the 0.3.x borrower infos under the base
+/// stable denom and returns whether all of them are moved
+pub fn migrate_borrowers(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+    let last_interest_updated: u64 =
+        match ReadonlySingleton::new(storage, KEY_BORROWER_MIGRATION).may_load()? {
+            Some(v) => v,
+            None => return Err(StdError::generic_err("Borrowers are already migrated")),
+        };
+    let denom = read_config(storage)?.stable_denom;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let legacy_liabilities: Vec<(Vec<u8>, LegacyBorrowerInfo)> =
+        bucket_read(storage, PREFIX_LIABILITY)
+            .range(None, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<(Vec<u8>, LegacyBorrowerInfo)>>>()?;
+    for (borrower, liability) in legacy_liabilities {
+        store_borrower_info(
+            storage,
+            &denom,
+            &CanonicalAddr::from(borrower.clone()),
+            &BorrowerInfo {
+                interest_index: liability.interest_index,
+                reward_index: liability.reward_index,
+                loan_amount: liability.loan_amount,
+                pending_rewards: liability.pending_rewards,
+                rate_mode: RateMode::Variable,
+                stable_rate: Decimal256::zero(),
+                last_interest_updated,
+            },
+        )?;
+        bucket::<LegacyBorrowerInfo>(storage, PREFIX_LIABILITY).remove(&borrower);
+    }
+
+    let completed = bucket_read::<LegacyBorrowerInfo>(storage, PREFIX_LIABILITY)
+        .range(None, None, Order::Ascending)
+        .next()
+        .is_none();
+    if completed {
+        Singleton::<u64>::new(storage, KEY_BORROWER_MIGRATION).remove();
+    }
+
+    Ok(completed)
+}
+
+/// Loans cannot be read or changed while some 0.3.x
+/// borrower infos are waiting to be migrated
+pub fn assert_borrowers_migrated(storage: &dyn Storage) -> StdResult<()> {
+    if ReadonlySingleton::<u64>::new(storage, KEY_BORROWER_MIGRATION)
+        .may_load()?
+        .is_some()
+    {
+        return Err(StdError::generic_err(
+            "Borrowers of the legacy market are being migrated",
+        ));
+    }
+
+    Ok(())
+}
@@ -1,9 +1,17 @@
-use crate::borrow::{compute_borrower_interest, compute_interest};
-use crate::state::{store_state, BorrowerInfo, Config, State};
+use crate::borrow::{
+    compute_borrower_interest, compute_interest, query_average_borrow_rate, query_rate_history,
+};
+use crate::state::{
+    store_config, store_rate_snapshot, store_stable_config, store_state, BorrowerInfo, Config,
+    RateSnapshot, StableConfig, State, RATE_HISTORY_SIZE,
+};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::mock_dependencies as mock_std_dependencies;
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{Api, Coin, Uint128};
+use cosmwasm_std::{Api, Coin, StdError, Uint128};
+use moneymarket::market::RateMode;
+use std::str::FromStr;
 
 #[test]
 fn proper_compute_borrower_interest() {
@@ -18,12 +26,17 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_stable_liabilities: Decimal256::zero(),
+        avg_stable_rate: Decimal256::zero(),
     };
     let mut liability1 = BorrowerInfo {
         interest_index: Decimal256::one(),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Variable,
+        stable_rate: Decimal256::zero(),
+        last_interest_updated: 0,
     };
     compute_borrower_interest(&mock_state, &mut liability1);
     let liability2 = BorrowerInfo {
@@ -31,6 +44,9 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Variable,
+        stable_rate: Decimal256::zero(),
+        last_interest_updated: env.block.height,
     };
     assert_eq!(liability1, liability2);
 
@@ -44,12 +60,17 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_stable_liabilities: Decimal256::zero(),
+        avg_stable_rate: Decimal256::zero(),
     };
     let mut liability3 = BorrowerInfo {
         interest_index: Decimal256::from_uint256(4u128),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(80u128),
         pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Variable,
+        stable_rate: Decimal256::zero(),
+        last_interest_updated: 0,
     };
     compute_borrower_interest(&mock_state2, &mut liability3);
     let liability4 = BorrowerInfo {
@@ -57,8 +78,33 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(40u128),
         pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Variable,
+        stable_rate: Decimal256::zero(),
+        last_interest_updated: env.block.height,
     };
     assert_eq!(liability3, liability4);
+
+    // stable rate loans accrue with the locked rate
+    let mut liability5 = BorrowerInfo {
+        interest_index: Decimal256::from_uint256(4u128),
+        reward_index: Decimal256::zero(),
+        loan_amount: Uint256::from(1000u128),
+        pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Stable,
+        stable_rate: Decimal256::percent(1),
+        last_interest_updated: env.block.height - 10,
+    };
+    compute_borrower_interest(&mock_state2, &mut liability5);
+    let liability6 = BorrowerInfo {
+        interest_index: Decimal256::from_uint256(2u128),
+        reward_index: Decimal256::zero(),
+        loan_amount: Uint256::from(1100u128),
+        pending_rewards: Decimal256::zero(),
+        rate_mode: RateMode::Stable,
+        stable_rate: Decimal256::percent(1),
+        last_interest_updated: env.block.height,
+    };
+    assert_eq!(liability5, liability6);
 }
 
 #[test]
@@ -78,14 +124,24 @@ fn proper_compute_interest() {
     let mock_config = Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
-        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
-        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
         distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
         distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
         collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: None,
+    };
+
+    let mock_stable = StableConfig {
+        denom: "uusd".to_string(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
     };
 
     deps.querier
@@ -101,14 +157,17 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_stable_liabilities: Decimal256::zero(),
+        avg_stable_rate: Decimal256::zero(),
     };
-    store_state(&mut deps.storage, &mock_state).unwrap();
+    store_state(&mut deps.storage, "uusd", &mock_state).unwrap();
 
     let mock_deposit_amount = Some(Uint256::from(1000u128));
 
     compute_interest(
         deps.as_ref(),
         &mock_config,
+        &mock_stable,
         &mut mock_state,
         env.block.height,
         mock_deposit_amount,
@@ -126,6 +185,8 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
@@ -134,6 +195,7 @@ fn proper_compute_interest() {
     compute_interest(
         deps.as_ref(),
         &mock_config,
+        &mock_stable,
         &mut mock_state,
         env.block.height,
         mock_deposit_amount,
@@ -151,6 +213,8 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_ratio(19995, 10000),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
@@ -165,8 +229,10 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::from(2000000u128),
         prev_exchange_rate: Decimal256::one(),
+        total_stable_liabilities: Decimal256::zero(),
+        avg_stable_rate: Decimal256::zero(),
     };
-    store_state(&mut deps.storage, &mock_state).unwrap();
+    store_state(&mut deps.storage, "uusd", &mock_state).unwrap();
 
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR,
@@ -183,6 +249,7 @@ fn proper_compute_interest() {
     compute_interest(
         deps.as_ref(),
         &mock_config,
+        &mock_stable,
         &mut mock_state,
         env.block.height,
         None,
@@ -200,6 +267,125 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_uint256(2u64),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        }
+    );
+}
+
+#[test]
+fn proper_rate_history() {
+    let mut deps = mock_std_dependencies(&[]);
+
+    store_config(
+        &mut deps.storage,
+        &Config {
+            contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+            owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+            distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+            distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+            collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+            overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+            stable_denom: "uusd".to_string(),
+            max_borrow_factor: Decimal256::one(),
+            flash_loan_fee: Decimal256::zero(),
+            stable_rate_premium: Decimal256::zero(),
+            stable_rate_rebalance_threshold: Decimal256::zero(),
+            max_total_debt: None,
+            max_borrower_debt: None,
+            guardian: None,
+        },
+    )
+    .unwrap();
+    store_stable_config(
+        &mut deps.storage,
+        &StableConfig {
+            denom: "uusd".to_string(),
+            aterra_contract: deps.api.addr_canonicalize("aterra").unwrap(),
+            interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        },
+    )
+    .unwrap();
+
+    let snapshot = |block_height: u64, borrow_rate: u64| RateSnapshot {
+        block_height,
+        borrow_rate: Decimal256::percent(borrow_rate),
+        deposit_rate: Decimal256::zero(),
+        exchange_rate: Decimal256::one(),
+        utilization_ratio: Decimal256::percent(50),
+    };
+
+    // no history
+    match query_average_borrow_rate(deps.as_ref(), 0, 100, None) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "No rate history between the given heights")
         }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // blocks (100, 200] are charged 1%, (200, 400] are charged 4%
+    store_rate_snapshot(deps.as_mut().storage, "uusd", &snapshot(100, 3)).unwrap();
+    store_rate_snapshot(deps.as_mut().storage, "uusd", &snapshot(200, 1)).unwrap();
+    store_rate_snapshot(deps.as_mut().storage, "uusd", &snapshot(400, 4)).unwrap();
+
+    let res = query_rate_history(deps.as_ref(), None, None, None).unwrap();
+    assert_eq!(
+        res.rate_history
+            .iter()
+            .map(|s| s.block_height)
+            .collect::<Vec<u64>>(),
+        vec![100, 200, 400]
+    );
+
+    let res = query_rate_history(deps.as_ref(), Some(100), Some(1), None).unwrap();
+    assert_eq!(res.rate_history.len(), 1);
+    assert_eq!(res.rate_history[0].block_height, 200);
+    assert_eq!(res.rate_history[0].borrow_rate, Decimal256::percent(1));
+
+    // (100 * 0.01 + 200 * 0.04) / 300
+    let res = query_average_borrow_rate(deps.as_ref(), 0, 1000, None).unwrap();
+    assert_eq!(res.average_borrow_rate, Decimal256::percent(3));
+    assert_eq!((res.from_height, res.to_height), (100, 400));
+
+    // (50 * 0.01 + 50 * 0.04) / 100
+    let res = query_average_borrow_rate(deps.as_ref(), 150, 250, None).unwrap();
+    assert_eq!(
+        res.average_borrow_rate,
+        Decimal256::from_str("0.025").unwrap()
+    );
+    assert_eq!((res.from_height, res.to_height), (150, 250));
+
+    let _res = query_average_borrow_rate(deps.as_ref(), 250, 250, None).unwrap_err();
+
+    // ring buffer keeps the last RATE_HISTORY_SIZE snapshots
+    for i in 1..RATE_HISTORY_SIZE - 1 {
+        store_rate_snapshot(deps.as_mut().storage, "uusd", &snapshot(400 + i, 2)).unwrap();
+    }
+
+    let res = query_rate_history(deps.as_ref(), None, Some(2), None).unwrap();
+    assert_eq!(
+        res.rate_history
+            .iter()
+            .map(|s| s.block_height)
+            .collect::<Vec<u64>>(),
+        vec![200, 400]
+    );
+
+    let res = query_rate_history(deps.as_ref(), Some(1300), None, None).unwrap();
+    assert_eq!(
+        res.rate_history
+            .iter()
+            .map(|s| s.block_height)
+            .collect::<Vec<u64>>(),
+        vec![1301, 1302, 1303, 1304, 1305, 1306, 1307, 1308, 1309, 1310]
+    );
+
+    // the oldest kept snapshot has no known start
+    // (200, 400] is charged 4%, (400, 410] is charged 2%
+    let res = query_average_borrow_rate(deps.as_ref(), 0, 410, None).unwrap();
+    assert_eq!(
+        res.average_borrow_rate,
+        Decimal256::from_str("0.039047619047619047").unwrap()
     );
+    assert_eq!((res.from_height, res.to_height), (200, 410));
 }
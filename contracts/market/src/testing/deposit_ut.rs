@@ -1,5 +1,5 @@
 use crate::deposit::compute_exchange_rate;
-use crate::state::{Config, State};
+use crate::state::{Config, StableConfig, State};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
@@ -16,14 +16,23 @@ fn proper_compute_exchange_rate() {
     let mock_config = Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
-        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
-        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
         distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
         distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
         collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: None,
+    };
+    let mock_stable = StableConfig {
+        denom: "uusd".to_string(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
     };
     deps.querier.with_token_balances(&[(
         &"AT-uusd".to_string(),
@@ -40,12 +49,15 @@ fn proper_compute_exchange_rate() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_stable_liabilities: Decimal256::zero(),
+        avg_stable_rate: Decimal256::zero(),
     };
     let mock_deposit_amount = Some(Uint256::from(1000000u128));
 
     let exchange_rate = compute_exchange_rate(
         deps.as_ref(),
         &mock_config,
+        &mock_stable,
         &mock_state,
         mock_deposit_amount,
     )
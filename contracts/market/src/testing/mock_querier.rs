@@ -13,7 +13,12 @@ use std::collections::HashMap;
 use cw20::TokenInfoResponse;
 use moneymarket::distribution_model::AncEmissionRateResponse;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse};
+use moneymarket::oracle::{PriceMode, PriceResponse};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ConfigResponse, IsolatedDebtResponse, SwapPath, WhitelistResponse,
+    WhitelistResponseElem,
+};
+use moneymarket::pause::PauseStatus;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -30,6 +35,8 @@ pub enum QueryMsg {
         borrower: String,
         block_time: Option<u64>,
     },
+    /// Query isolated debt of a borrower to overseer contract
+    IsolatedDebt { borrower: String },
     /// Query ANC emission rate to distribution model contract
     AncEmissionRate {
         deposit_rate: Decimal256,
@@ -41,6 +48,16 @@ pub enum QueryMsg {
     Config {},
     /// Query cw20 Token Info
     TokenInfo {},
+    /// Query oracle price to oracle contract
+    Price { base: String, quote: String },
+    /// Query whitelisted collaterals to overseer contract
+    Whitelist {
+        collateral_token: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Query paused operations to overseer, liquidation and custody contracts
+    PauseStatus {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -64,6 +81,10 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     borrow_rate_querier: BorrowRateQuerier,
     borrow_limit_querier: BorrowLimitQuerier,
+    oracle_price_querier: OraclePriceQuerier,
+    isolated_debt: HashMap<String, (Vec<String>, Uint256)>,
+    custodies: Vec<(String, String)>,
+    pause_status: HashMap<String, PauseStatus>,
 }
 
 #[derive(Clone, Default)]
@@ -167,6 +188,33 @@ pub(crate) fn borrow_limit_to_map(
     borrow_limit_map
 }
 
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    // this lets us iterate over all pairs that match the first string
+    oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+}
+
+#[allow(clippy::type_complexity)]
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
+        OraclePriceQuerier {
+            oracle_price: oracle_price_to_map(oracle_price),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn oracle_price_to_map(
+    oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+) -> HashMap<(String, String), (Decimal256, u64, u64)> {
+    let mut oracle_price_map: HashMap<(String, String), (Decimal256, u64, u64)> = HashMap::new();
+    for (base_quote, oracle_price) in oracle_price.iter() {
+        oracle_price_map.insert((*base_quote).clone(), **oracle_price);
+    }
+
+    oracle_price_map
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -243,6 +291,18 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
+                    QueryMsg::IsolatedDebt { borrower } => {
+                        let (isolated_collaterals, debt) = self
+                            .isolated_debt
+                            .get(&borrower)
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&IsolatedDebtResponse {
+                            borrower,
+                            isolated_collaterals,
+                            debt,
+                        })))
+                    }
                     QueryMsg::AncEmissionRate {
                         deposit_rate: _,
                         target_deposit_rate: _,
@@ -256,9 +316,9 @@ impl WasmMockQuerier {
                     QueryMsg::Config {} => {
                         SystemResult::Ok(ContractResult::from(to_binary(&ConfigResponse {
                             owner_addr: "".to_string(),
-                            oracle_contract: "".to_string(),
+                            oracle_contract: "oracle".to_string(),
                             market_contract: "".to_string(),
-                            liquidation_contract: "".to_string(),
+                            liquidation_contract: "liquidation".to_string(),
                             collector_contract: "".to_string(),
                             threshold_deposit_rate: Decimal256::one(),
                             target_deposit_rate: Decimal256::from_ratio(1, 100),
@@ -267,6 +327,9 @@ impl WasmMockQuerier {
                             stable_denom: "uusd".to_string(),
                             epoch_period: 100u64,
                             price_timeframe: 100u64,
+                            price_mode: PriceMode::Spot {},
+                            guardian: None,
+                            min_delay: 0u64,
                         })))
                     }
                     QueryMsg::TokenInfo {} => {
@@ -289,6 +352,57 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::Price { base, quote } => {
+                        match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                            Some(v) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                    rate: v.0,
+                                    last_updated_base: v.1,
+                                    last_updated_quote: v.2,
+                                    frozen: false,
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No oracle price exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    QueryMsg::Whitelist {
+                        collateral_token: _,
+                        start_after,
+                        limit: _,
+                    } => {
+                        let elems: Vec<WhitelistResponseElem> = self
+                            .custodies
+                            .iter()
+                            .filter(|(token, _)| Some(token) > start_after.as_ref())
+                            .map(|(token, custody)| WhitelistResponseElem {
+                                name: token.clone(),
+                                symbol: token.clone(),
+                                max_ltv: Decimal256::percent(50),
+                                liquidation_threshold: Decimal256::percent(50),
+                                custody_contract: custody.clone(),
+                                collateral_token: token.clone(),
+                                swap_path: SwapPath::LiquidationQueue {},
+                                max_total_locked: None,
+                                max_borrower_locked: None,
+                                total_locked: Uint256::zero(),
+                                debt_ceiling: None,
+                                isolated_debt: Uint256::zero(),
+                            })
+                            .collect();
+                        SystemResult::Ok(ContractResult::from(to_binary(&WhitelistResponse {
+                            elems,
+                        })))
+                    }
+                    QueryMsg::PauseStatus {} => SystemResult::Ok(ContractResult::from(to_binary(
+                        &self
+                            .pause_status
+                            .get(contract_addr)
+                            .cloned()
+                            .unwrap_or_default(),
+                    ))),
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -342,9 +456,35 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             borrow_rate_querier: BorrowRateQuerier::default(),
             borrow_limit_querier: BorrowLimitQuerier::default(),
+            oracle_price_querier: OraclePriceQuerier::default(),
+            isolated_debt: HashMap::new(),
+            custodies: vec![],
+            pause_status: HashMap::new(),
         }
     }
 
+    pub fn with_custodies(&mut self, custodies: &[(&str, &str)]) {
+        self.custodies = custodies
+            .iter()
+            .map(|(token, custody)| (token.to_string(), custody.to_string()))
+            .collect();
+    }
+
+    pub fn with_pause_status(&mut self, contract_addr: &str, pause_status: PauseStatus) {
+        self.pause_status
+            .insert(contract_addr.to_string(), pause_status);
+    }
+
+    pub fn with_isolated_debt(
+        &mut self,
+        borrower: &str,
+        isolated_collaterals: Vec<String>,
+        debt: Uint256,
+    ) {
+        self.isolated_debt
+            .insert(borrower.to_string(), (isolated_collaterals, debt));
+    }
+
     // set a new balance for the given address and return the old balance
     pub fn update_balance<U: Into<String>>(
         &mut self,
@@ -371,4 +511,12 @@ impl WasmMockQuerier {
     pub fn with_borrow_limit(&mut self, borrow_limit: &[(&String, &Uint256)]) {
         self.borrow_limit_querier = BorrowLimitQuerier::new(borrow_limit);
     }
+
+    #[allow(clippy::type_complexity)]
+    pub fn with_oracle_price(
+        &mut self,
+        oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+    ) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
 }
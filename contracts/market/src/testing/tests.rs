@@ -1,24 +1,36 @@
-use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
+use crate::contract::{
+    execute, instantiate, migrate, query, reply, CONTRACT_VERSION, INITIAL_DEPOSIT_AMOUNT,
+};
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_borrower_infos, read_state, store_state, State};
+use crate::state::{
+    read_borrower_infos, read_state, store_state, LegacyBorrowerInfo, LegacyConfig, LegacyState,
+    State,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply,
-    SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, OwnedDeps, Reply, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
+use cosmwasm_storage::{bucket, singleton};
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use moneymarket::interest_model::ExecuteMsg as InterestExecuteMsg;
 use moneymarket::market::{
-    BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    BorrowerInfoResponse, BorrowerLoanValueResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg, RateHistoryResponse, RateMode, StableResponse,
+    StablesResponse, StateResponse,
 };
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
+use moneymarket::pause::{Operation, PauseStatus};
 use moneymarket::querier::deduct_tax;
+use moneymarket::version::get_contract_version;
 use protobuf::Message;
 use std::str::FromStr;
+use terra_cosmwasm::create_swap_msg;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 #[test]
@@ -34,6 +46,9 @@ fn proper_initialization() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -121,11 +136,15 @@ fn proper_initialization() {
     assert_eq!("overseer".to_string(), config_res.overseer_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
     assert_eq!(Decimal256::one(), config_res.max_borrow_factor);
+    assert_eq!(Decimal256::zero(), config_res.flash_loan_fee);
 
     let query_res = query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::State { block_height: None },
+        QueryMsg::State {
+            block_height: None,
+            denom: None,
+        },
     )
     .unwrap();
     let state: StateResponse = from_binary(&query_res).unwrap();
@@ -153,6 +172,9 @@ fn update_config() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -189,18 +211,26 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // transfer the ownership
     let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some("owner1".to_string()),
-        interest_model: None,
-        distribution_model: None,
-        max_borrow_factor: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner1".to_string(),
+        expires_in: 100u64,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let info = mock_info("owner1", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&res).unwrap();
@@ -209,10 +239,15 @@ fn update_config() {
     // update left items
     let info = mock_info("owner1", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         interest_model: Some("interest2".to_string()),
         distribution_model: Some("distribution2".to_string()),
         max_borrow_factor: Some(Decimal256::percent(100)),
+        flash_loan_fee: Some(Decimal256::permille(9)),
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -225,14 +260,39 @@ fn update_config() {
     assert_eq!("interest2".to_string(), config_res.interest_model);
     assert_eq!("distribution2".to_string(), config_res.distribution_model);
     assert_eq!(Decimal256::percent(100), config_res.max_borrow_factor);
+    assert_eq!(Decimal256::permille(9), config_res.flash_loan_fee);
+
+    // the fee is a share of the loan
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: Some(Decimal256::one()),
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidFlashLoanFee {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     // Unauthorized err
     let info = mock_info("owner", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         interest_model: None,
         distribution_model: None,
         max_borrow_factor: None,
+        flash_loan_fee: None,
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -255,6 +315,9 @@ fn deposit_stable_huge_amount() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -292,7 +355,7 @@ fn deposit_stable_huge_amount() {
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Must deposit stable_denom
-    let msg = ExecuteMsg::DepositStable {};
+    let msg = ExecuteMsg::DepositStable { denom: None };
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -411,6 +474,9 @@ fn deposit_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -448,7 +514,7 @@ fn deposit_stable() {
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Must deposit stable_denom
-    let msg = ExecuteMsg::DepositStable {};
+    let msg = ExecuteMsg::DepositStable { denom: None };
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -510,7 +576,7 @@ fn deposit_stable() {
     // (looking as if the function might not have been invoked at all.)
     // Thus, later, the invocation of compute interest will be tested after increasing the block height.
     assert_eq!(
-        read_state(deps.as_ref().storage).unwrap(),
+        read_state(deps.as_ref().storage, "uusd").unwrap(),
         State {
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
@@ -521,6 +587,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(1000000u64),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
@@ -550,6 +618,7 @@ fn deposit_stable() {
     // make exchange rate to 50%
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(50000u128),
             total_reserves: Decimal256::from_uint256(550000u128),
@@ -560,6 +629,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -600,6 +671,7 @@ fn deposit_stable() {
 
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(50000u128),
             total_reserves: Decimal256::from_uint256(550000u128),
@@ -610,6 +682,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -626,7 +700,7 @@ fn deposit_stable() {
     // mint_amount: 0.55 * 1000000 = 1,818,181
 
     assert_eq!(
-        read_state(deps.as_ref().storage).unwrap(),
+        read_state(deps.as_ref().storage, "uusd").unwrap(),
         State {
             global_interest_index: Decimal256::from_uint256(2u128),
             global_reward_index: Decimal256::from_str("0.002").unwrap(),
@@ -637,6 +711,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
             prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 }
@@ -654,6 +730,9 @@ fn redeem_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -691,7 +770,7 @@ fn redeem_stable() {
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Deposit 1000000
-    let msg = ExecuteMsg::DepositStable {};
+    let msg = ExecuteMsg::DepositStable { denom: None };
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -725,7 +804,7 @@ fn redeem_stable() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(1000000u128),
-        msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::RedeemStable { denom: None }).unwrap(),
     });
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -764,6 +843,7 @@ fn redeem_stable() {
     // make exchange rate to 50%
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(500000u128),
             total_reserves: Decimal256::from_uint256(100000u128),
@@ -774,6 +854,8 @@ fn redeem_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -846,6 +928,9 @@ fn borrow_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -890,6 +975,7 @@ fn borrow_stable() {
 
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(1000000u128),
             total_reserves: Decimal256::zero(),
@@ -900,6 +986,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -907,6 +995,8 @@ fn borrow_stable() {
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(500000u64),
         to: None,
+        denom: None,
+        rate_mode: None,
     };
 
     env.block.height += 100;
@@ -926,7 +1016,9 @@ fn borrow_stable() {
         vec![
             attr("action", "borrow_stable"),
             attr("borrower", "addr0000"),
-            attr("borrow_amount", "500000")
+            attr("denom", "uusd"),
+            attr("borrow_amount", "500000"),
+            attr("rate_mode", "variable"),
         ]
     );
 
@@ -950,7 +1042,10 @@ fn borrow_stable() {
             &query(
                 deps.as_ref(),
                 env.clone(),
-                QueryMsg::State { block_height: None }
+                QueryMsg::State {
+                    block_height: None,
+                    denom: None,
+                }
             )
             .unwrap()
         )
@@ -965,6 +1060,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
@@ -975,7 +1072,8 @@ fn borrow_stable() {
                 deps.as_ref(),
                 mock_env(),
                 QueryMsg::State {
-                    block_height: Some(env.block.height + 1u64)
+                    block_height: Some(env.block.height + 1u64),
+                    denom: None,
                 }
             )
             .unwrap()
@@ -991,6 +1089,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
@@ -1000,6 +1100,7 @@ fn borrow_stable() {
         QueryMsg::BorrowerInfo {
             borrower: "addr0000".to_string(),
             block_height: None,
+            denom: None,
         },
     )
     .unwrap();
@@ -1009,10 +1110,13 @@ fn borrow_stable() {
         liability,
         BorrowerInfoResponse {
             borrower: "addr0000".to_string(),
+            denom: "uusd".to_string(),
             interest_index: Decimal256::from_uint256(2u128),
             reward_index: Decimal256::from_str("0.0001").unwrap(),
             loan_amount: Uint256::from(500000u64),
             pending_rewards: Decimal256::zero(),
+            rate_mode: RateMode::Variable,
+            stable_rate: Decimal256::zero(),
         }
     );
 
@@ -1022,6 +1126,7 @@ fn borrow_stable() {
         QueryMsg::BorrowerInfo {
             borrower: "addr0000".to_string(),
             block_height: Some(env.block.height),
+            denom: None,
         },
     )
     .unwrap();
@@ -1031,10 +1136,13 @@ fn borrow_stable() {
         borrower_info,
         BorrowerInfoResponse {
             borrower: "addr0000".to_string(),
+            denom: "uusd".to_string(),
             interest_index: Decimal256::from_uint256(2u128),
             reward_index: Decimal256::from_str("0.0001").unwrap(),
             loan_amount: Uint256::from(500000u64),
             pending_rewards: Decimal256::zero(),
+            rate_mode: RateMode::Variable,
+            stable_rate: Decimal256::zero(),
         }
     );
 
@@ -1046,6 +1154,7 @@ fn borrow_stable() {
         QueryMsg::BorrowerInfo {
             borrower: "addr0000".to_string(),
             block_height: Some(env.block.height + 100),
+            denom: None,
         },
     )
     .unwrap();
@@ -1055,10 +1164,13 @@ fn borrow_stable() {
         borrower_info,
         BorrowerInfoResponse {
             borrower: "addr0000".to_string(),
+            denom: "uusd".to_string(),
             interest_index: Decimal256::from_uint256(4u128),
             reward_index: Decimal256::from_str("0.00018").unwrap(),
             loan_amount: Uint256::from(1000000u64),
             pending_rewards: Decimal256::from_uint256(20u64),
+            rate_mode: RateMode::Variable,
+            stable_rate: Decimal256::zero(),
         }
     );
 
@@ -1066,12 +1178,44 @@ fn borrow_stable() {
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(500001u64),
         to: None,
+        denom: None,
+        rate_mode: None,
     };
-    let res = execute(deps.as_mut(), env, info, msg);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
     match res {
         Err(ContractError::BorrowExceedsLimit(1000000)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    // Only overseer can borrow for a borrower
+    let msg = ExecuteMsg::BorrowStableFromOverseer {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100000u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The borrowed amount is sent to overseer
+    let res = execute(deps.as_mut(), env, mock_info("overseer", &[]), msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => {
+            assert_eq!(to_address, "overseer")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("borrow_amount", "100000"),
+            attr("rate_mode", "variable"),
+        ]
+    );
 }
 
 #[test]
@@ -1091,6 +1235,9 @@ fn assert_max_borrow_factor() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::percent(1),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -1134,6 +1281,7 @@ fn assert_max_borrow_factor() {
 
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::zero(),
             total_reserves: Decimal256::zero(),
@@ -1144,6 +1292,8 @@ fn assert_max_borrow_factor() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1151,6 +1301,8 @@ fn assert_max_borrow_factor() {
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(10000u64),
         to: None,
+        denom: None,
+        rate_mode: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1159,7 +1311,9 @@ fn assert_max_borrow_factor() {
         vec![
             attr("action", "borrow_stable"),
             attr("borrower", "addr0000"),
-            attr("borrow_amount", "10000")
+            attr("denom", "uusd"),
+            attr("borrow_amount", "10000"),
+            attr("rate_mode", "variable"),
         ]
     );
 
@@ -1175,6 +1329,8 @@ fn assert_max_borrow_factor() {
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(1u64),
         to: None,
+        denom: None,
+        rate_mode: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     let _uusd_string = "uusd";
@@ -1185,15 +1341,11 @@ fn assert_max_borrow_factor() {
 }
 
 #[test]
-fn repay_stable() {
+fn debt_ceilings() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
@@ -1201,6 +1353,9 @@ fn repay_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -1210,8 +1365,6 @@ fn repay_stable() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Register anchor token contract
@@ -1234,149 +1387,156 @@ fn repay_stable() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let mut info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
-
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+    ]);
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: Some(Uint256::from(500000u64)),
+        max_borrower_debt: Some(Uint256::from(300000u64)),
+        guardian: None,
     };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
 
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint256::from(500000u64)), config_res.max_total_debt);
+    assert_eq!(Some(Uint256::from(300000u64)), config_res.max_borrower_debt);
 
-    let msg = ExecuteMsg::RepayStable {};
-    info.funds = vec![Coin {
-        denom: "ukrw".to_string(),
-        amount: Uint128::from(100000u128),
-    }];
+    let borrow_msg = |amount: u64| ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(amount),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
 
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        borrow_msg(300001u64),
+    );
     match res {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        Err(ContractError::MaxBorrowerDebtReached(300000u128)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::zero(),
-    }];
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        borrow_msg(300000u64),
+    )
+    .unwrap();
 
-    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res2 {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+    // loans of all borrowers count towards the total debt ceiling
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        borrow_msg(200001u64),
+    );
+    match res {
+        Err(ContractError::MaxTotalDebtReached(500000u128)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
-        }],
-    );
-
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100000u128),
-    }];
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    // loans backed by isolated collaterals are counted by the overseer
+    deps.querier
+        .with_isolated_debt("addr0001", vec!["batom".to_string()], Uint256::zero());
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        borrow_msg(200000u64),
+    )
+    .unwrap();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "100000"),
-        ]
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::IncreaseIsolatedDebt {
+                borrower: "addr0001".to_string(),
+            })
+            .unwrap(),
+        }))
     );
 
-    //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .loan_amount;
-    assert_eq!(res_loan, Uint256::from(400000u128));
+    // and the remaining loan is charged again on repayment
+    deps.querier
+        .with_isolated_debt("addr0001", vec![], Uint256::from(200000u64));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0001",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(50000u128),
+            }],
+        ),
+        ExecuteMsg::RepayStable { denom: None },
+    )
+    .unwrap();
     assert_eq!(
-        read_state(deps.as_ref().storage).unwrap().total_liabilities,
-        Decimal256::from_uint256(2400000u128)
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::DecreaseIsolatedDebt {
+                borrower: "addr0001".to_string(),
+            })
+            .unwrap(),
+        }))]
     );
 
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(500000u128),
-    }];
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "400000"),
-        ]
-    );
+    // zero removes the ceilings
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: Some(Uint256::zero()),
+        max_borrower_debt: Some(Uint256::zero()),
+        guardian: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
 
-    //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .loan_amount;
-    assert_eq!(res_loan, Uint256::zero());
-    assert_eq!(
-        read_state(deps.as_ref().storage).unwrap().total_liabilities,
-        Decimal256::from_uint256(2000000u128)
-    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(None, config_res.max_total_debt);
+    assert_eq!(None, config_res.max_borrower_debt);
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(100000u128),
-                }
-            )
-            .unwrap()]
-        }))]
-    );
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        borrow_msg(300001u64),
+    )
+    .unwrap();
 }
 
 #[test]
-fn repay_stable_from_liquidation() {
+fn pause_operations() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
@@ -1384,6 +1544,9 @@ fn repay_stable_from_liquidation() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -1393,8 +1556,6 @@ fn repay_stable_from_liquidation() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Register anchor token contract
@@ -1417,75 +1578,366 @@ fn repay_stable_from_liquidation() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: Some("guardian".to_string()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
 
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some("guardian".to_string()), config_res.guardian);
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Deposit, Operation::Redeem],
     };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // update balance to make repay
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
+    let res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
         vec![
-            Coin {
-                denom: "ukrw".to_string(),
-                amount: Uint128::from(100000u128),
-            },
-            Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-            },
-        ],
+            attr("action", "pause"),
+            attr("operations", "deposit,redeem")
+        ]
     );
 
-    let msg = ExecuteMsg::RepayStableFromLiquidation {
-        borrower: "addr0000".to_string(),
-        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatus = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            deposit: true,
+            redeem: true,
+            ..PauseStatus::default()
+        }
+    );
+
+    let deposit_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        deposit_info.clone(),
+        ExecuteMsg::DepositStable { denom: None },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: deposit")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable { denom: None }).unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("AT-uusd", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: redeem")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // repay cannot be paused while borrow is running
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Repay],
     };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "Borrow must be paused while repay or liquidate is paused"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Borrow, Operation::Repay],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: borrow")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the owner can resume operations as well
+    let msg = ExecuteMsg::Unpause {
+        operations: vec![Operation::Deposit],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        deposit_info,
+        ExecuteMsg::DepositStable { denom: None },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Unpause {
+        operations: vec![Operation::Repay],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+
+    // borrow cannot be resumed while loans elsewhere cannot be liquidated
+    deps.querier
+        .with_custodies(&[("bluna", "custody_bluna"), ("beth", "custody_beth")]);
+    deps.querier.with_pause_status(
+        "overseer",
+        PauseStatus {
+            liquidate: true,
+            ..PauseStatus::default()
+        },
+    );
+    let unpause_borrow = ExecuteMsg::Unpause {
+        operations: vec![Operation::Borrow],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        unpause_borrow.clone(),
+    );
+    match res {
+        Err(ContractError::LoanExitPaused(contract)) => assert_eq!(contract, "overseer"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_pause_status("overseer", PauseStatus::default());
+    deps.querier.with_pause_status(
+        "custody_bluna",
+        PauseStatus {
+            liquidate: true,
+            ..PauseStatus::default()
+        },
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        unpause_borrow.clone(),
+    );
+    match res {
+        Err(ContractError::LoanExitPaused(contract)) => assert_eq!(contract, "custody_bluna"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_pause_status("custody_bluna", PauseStatus::default());
+    deps.querier.with_pause_status(
+        "liquidation",
+        PauseStatus {
+            liquidate: true,
+            ..PauseStatus::default()
+        },
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        unpause_borrow.clone(),
+    );
     match res {
+        Err(ContractError::LoanExitPaused(contract)) => assert_eq!(contract, "liquidation"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_pause_status("liquidation", PauseStatus::default());
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        unpause_borrow,
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatus = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            redeem: true,
+            ..PauseStatus::default()
+        }
+    );
+
+    // an empty address removes the guardian
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        stable_rate_premium: None,
+        stable_rate_rebalance_threshold: None,
+        max_total_debt: None,
+        max_borrower_debt: None,
+        guardian: Some("".to_string()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(None, config_res.guardian);
+
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Deposit],
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg) {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
+}
 
-    let info = mock_info("overseer", &[]);
+#[test]
+fn repay_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        "uusd",
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStable { denom: None };
+    info.funds = vec![Coin {
+        denom: "ukrw".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
 
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
     let _uusd_string = "uusd";
     match res {
-        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::zero(),
+    }];
+
+    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res2 {
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // update balance to make repay
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
@@ -1494,34 +1946,64 @@ fn repay_stable_from_liquidation() {
         }],
     );
 
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
             attr("repay_amount", "100000"),
         ]
     );
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
-        }],
+    //Loan amount and Total liability have decreased according to the repayment
+    let res_loan = read_borrower_infos(deps.as_ref(), "uusd", None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::from(400000u128));
+    assert_eq!(
+        read_state(deps.as_ref().storage, "uusd")
+            .unwrap()
+            .total_liabilities,
+        Decimal256::from_uint256(2400000u128)
     );
 
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(500000u128),
+    }];
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
             attr("repay_amount", "400000"),
         ]
     );
 
+    //Loan amount and Total liability have decreased according to the repayment
+    let res_loan = read_borrower_infos(deps.as_ref(), "uusd", None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+    assert_eq!(
+        read_state(deps.as_ref().storage, "uusd")
+            .unwrap()
+            .total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+
     assert_eq!(
         res.messages,
         vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
@@ -1539,7 +2021,7 @@ fn repay_stable_from_liquidation() {
 }
 
 #[test]
-fn claim_rewards() {
+fn repay_stable_from_liquidation() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
@@ -1555,6 +2037,9 @@ fn claim_rewards() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -1588,8 +2073,8 @@ fn claim_rewards() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let info = mock_info("addr0000", &[]);
     let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
     deps.querier
@@ -1599,6 +2084,7 @@ fn claim_rewards() {
 
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(1000000u128),
             total_reserves: Decimal256::zero(),
@@ -1609,22 +2095,207 @@ fn claim_rewards() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
 
-    // zero loan claim, will return empty messages
-    let msg = ExecuteMsg::ClaimRewards { to: None };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(500000u64),
         to: None,
+        denom: None,
+        rate_mode: None,
     };
+
+    env.block.height += 100;
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // zero block passed
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(100000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+        ],
+    );
+
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("repay_amount", "400000"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+}
+
+#[test]
+fn claim_rewards() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        "uusd",
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    // zero loan claim, will return empty messages
+    let msg = ExecuteMsg::ClaimRewards { to: None };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // zero block passed
     let msg = ExecuteMsg::ClaimRewards {
         to: Some("addr0001".to_string()),
     };
@@ -1654,6 +2325,7 @@ fn claim_rewards() {
             QueryMsg::BorrowerInfo {
                 borrower: "addr0000".to_string(),
                 block_height: None,
+                denom: None,
             },
         )
         .unwrap(),
@@ -1686,6 +2358,9 @@ fn execute_epoch_operations() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
     };
 
     let info = mock_info(
@@ -1730,6 +2405,7 @@ fn execute_epoch_operations() {
 
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(1000000u128),
             total_reserves: Decimal256::from_uint256(3000u128),
@@ -1740,6 +2416,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1765,16 +2443,28 @@ fn execute_epoch_operations() {
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(2970u128), // 1% tax
-            }],
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2970u128), // 1% tax
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "interest".to_string(),
+                funds: vec![],
+                msg: to_binary(&InterestExecuteMsg::ExecuteEpochOperations {
+                    market_balance: Uint256::from(997000u64),
+                    total_liabilities: Decimal256::from_uint256(2000000u128),
+                    total_reserves: Decimal256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
     );
 
-    let state = read_state(deps.as_ref().storage).unwrap();
+    let state = read_state(deps.as_ref().storage, "uusd").unwrap();
     assert_eq!(
         state,
         State {
@@ -1787,9 +2477,27 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 
+    // applied rates are recorded
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RateHistory {
+            start_after: None,
+            limit: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let res: RateHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(res.rate_history.len(), 1);
+    assert_eq!(res.rate_history[0].block_height, env.block.height);
+    assert_eq!(res.rate_history[0].borrow_rate, Decimal256::percent(1));
+
     // When there is not enough balance to cover reserve
     // no message will be sent and reserve will be left as same
     deps.querier.update_balance(
@@ -1804,6 +2512,7 @@ fn execute_epoch_operations() {
     let info = mock_info("overseer", &[]);
     store_state(
         deps.as_mut().storage,
+        "uusd",
         &State {
             total_liabilities: Decimal256::from_uint256(1000000u128),
             total_reserves: Decimal256::from_uint256(3000u128),
@@ -1814,6 +2523,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1829,9 +2540,21 @@ fn execute_epoch_operations() {
     };
 
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "interest".to_string(),
+            funds: vec![],
+            msg: to_binary(&InterestExecuteMsg::ExecuteEpochOperations {
+                market_balance: Uint256::from(2999u64),
+                total_liabilities: Decimal256::from_uint256(2000000u128),
+                total_reserves: Decimal256::from_uint256(3000u128),
+            })
+            .unwrap(),
+        }))]
+    );
 
-    let state = read_state(deps.as_ref().storage).unwrap();
+    let state = read_state(deps.as_ref().storage, "uusd").unwrap();
     assert_eq!(
         state,
         State {
@@ -1844,6 +2567,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
         }
     );
 }
@@ -1938,3 +2663,1182 @@ fn execute_epoch_operations() {
 //     // only overseer can execute this
 //     let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
 // }
+
+#[test]
+fn migrate_legacy_deployment() {
+    let mut deps = mock_dependencies(&[]);
+
+    // 0.3.x deployment without version record
+    let legacy_config = LegacyConfig {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("aterra-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        stable_denom: "uusd".to_string(),
+        max_borrow_factor: Decimal256::one(),
+    };
+    let legacy_state = LegacyState {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: mock_env().block.height,
+        last_reward_updated: mock_env().block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+    let borrower_info = LegacyBorrowerInfo {
+        interest_index: Decimal256::one(),
+        reward_index: Decimal256::zero(),
+        loan_amount: Uint256::from(1000000u128),
+        pending_rewards: Decimal256::zero(),
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    singleton(deps.as_mut().storage, b"state")
+        .save(&legacy_state)
+        .unwrap();
+    for borrower in ["addr0000", "addr0001"] {
+        let borrower_raw = deps.api.addr_canonicalize(borrower).unwrap();
+        bucket(deps.as_mut().storage, b"liability")
+            .save(borrower_raw.as_slice(), &borrower_info)
+            .unwrap();
+    }
+
+    let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        CONTRACT_VERSION.to_string()
+    );
+
+    // loans are frozen until all borrowers are migrated
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowerLoanValue {
+            borrower: "addr0000".to_string(),
+            block_height: mock_env().block.height,
+            block_time: mock_env().block.time.seconds(),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Borrowers of the legacy market are being migrated")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RepayStable { denom: None };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Borrowers of the legacy market are being migrated")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::MigrateBorrowers { limit: Some(1u32) };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_borrowers"),
+            attr("completed", "false"),
+        ]
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_borrowers"),
+            attr("completed", "true")
+        ]
+    );
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0002", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Borrowers are already migrated")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.aterra_contract, "aterra-uusd".to_string());
+    assert_eq!(config_res.interest_model, "interest".to_string());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Stables {}).unwrap();
+    let stables_res: StablesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stables_res.stables,
+        vec![StableResponse {
+            denom: "uusd".to_string(),
+            aterra_contract: "aterra-uusd".to_string(),
+            interest_model: "interest".to_string(),
+        }]
+    );
+
+    assert_eq!(
+        read_state(deps.as_ref().storage, "uusd").unwrap(),
+        State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_stable_liabilities: Decimal256::zero(),
+            avg_stable_rate: Decimal256::zero(),
+        }
+    );
+    let borrower_infos = read_borrower_infos(deps.as_ref(), "uusd", None, None).unwrap();
+    assert_eq!(borrower_infos.len(), 2);
+    assert_eq!(
+        borrower_infos[0],
+        BorrowerInfoResponse {
+            borrower: "addr0000".to_string(),
+            denom: "uusd".to_string(),
+            interest_index: Decimal256::one(),
+            reward_index: Decimal256::zero(),
+            loan_amount: Uint256::from(1000000u128),
+            pending_rewards: Decimal256::zero(),
+            rate_mode: RateMode::Variable,
+            stable_rate: Decimal256::zero(),
+        }
+    );
+}
+
+#[test]
+fn multi_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let register_aterra = |deps: &mut OwnedDeps<_, _, _>, aterra: &str| {
+        let mut token_inst_res = MsgInstantiateContractResponse::new();
+        token_inst_res.set_contract_address(aterra.to_string());
+        let reply_msg = Reply {
+            id: 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap()
+    };
+    let _res = register_aterra(&mut deps, "aterra-uusd");
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only owner can register a stable
+    let msg = ExecuteMsg::RegisterStable {
+        denom: "ukrw".to_string(),
+        interest_model: "interest-ukrw".to_string(),
+        aterra_code_id: 123u64,
+    };
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // initial deposit is required
+    let info = mock_info("owner", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(ContractError::InitialFundsNotDeposited(amount, denom)) => {
+            assert_eq!(
+                (amount, denom),
+                (INITIAL_DEPOSIT_AMOUNT, "ukrw".to_string())
+            )
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        "owner",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // stables cannot share an interest model
+    let shared_msg = ExecuteMsg::RegisterStable {
+        denom: "ukrw".to_string(),
+        interest_model: "interest".to_string(),
+        aterra_code_id: 123u64,
+    };
+    match execute(deps.as_mut(), mock_env(), info.clone(), shared_msg) {
+        Err(ContractError::SharedInterestModel(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: 123u64,
+                funds: vec![],
+                label: "".to_string(),
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "Anchor Terra KRW".to_string(),
+                    symbol: "aKRT".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+                    }],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+            }),
+            1
+        )]
+    );
+    let _res = register_aterra(&mut deps, "aterra-ukrw");
+
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::StableAlreadyRegistered(denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Stables {}).unwrap();
+    let stables_res: StablesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stables_res.stables,
+        vec![
+            StableResponse {
+                denom: "ukrw".to_string(),
+                aterra_contract: "aterra-ukrw".to_string(),
+                interest_model: "interest-ukrw".to_string(),
+            },
+            StableResponse {
+                denom: "uusd".to_string(),
+                aterra_contract: "aterra-uusd".to_string(),
+                interest_model: "interest".to_string(),
+            },
+        ]
+    );
+
+    let env = mock_env();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+        ],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra-uusd".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+        (
+            &"aterra-ukrw".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+    ]);
+    deps.querier.with_borrow_rate(&[
+        (&"interest".to_string(), &Decimal256::percent(1)),
+        (&"interest-ukrw".to_string(), &Decimal256::percent(1)),
+    ]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000u64))]);
+    deps.querier.with_oracle_price(&[(
+        &("ukrw".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1u64, 1000u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow 500uusd
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // 600000ukrw is priced 600uusd, exceeds the borrow limit
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(600000u64),
+        to: None,
+        denom: Some("ukrw".to_string()),
+        rate_mode: None,
+    };
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::BorrowExceedsLimit(limit)) => assert_eq!(limit, 1000u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        denom: Some("ukrw".to_string()),
+        rate_mode: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(500000u128),
+            }],
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerLoanValue {
+            borrower: "addr0000".to_string(),
+            block_height: env.block.height,
+            block_time: env.block.time.seconds(),
+        },
+    )
+    .unwrap();
+    let loan_value_res: BorrowerLoanValueResponse = from_binary(&res).unwrap();
+    assert_eq!(loan_value_res.loan_value, Uint256::from(1000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+            denom: Some("ukrw".to_string()),
+        },
+    )
+    .unwrap();
+    let borrower_info_res: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_info_res.denom, "ukrw".to_string());
+    assert_eq!(borrower_info_res.loan_amount, Uint256::from(500000u64));
+
+    // repay must be made in the loan denom
+    let msg = ExecuteMsg::RepayStable {
+        denom: Some("ukrw".to_string()),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::ZeroRepay(denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the aterra of the given denom can redeem
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            denom: Some("ukrw".to_string()),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("aterra-uusd", &[]);
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // switch the interest model of the non base stable
+    let msg = ExecuteMsg::UpdateStable {
+        denom: "ukrw".to_string(),
+        interest_model: "interest-ukrw2".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), env.clone(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let shared_msg = ExecuteMsg::UpdateStable {
+        denom: "ukrw".to_string(),
+        interest_model: "interest".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    match execute(deps.as_mut(), env.clone(), info.clone(), shared_msg) {
+        Err(ContractError::SharedInterestModel(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut later_env = env.clone();
+    later_env.block.height += 100;
+    let res = execute(deps.as_mut(), later_env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_stable"),
+            attr("denom", "ukrw"),
+            attr("interest_model", "interest-ukrw2"),
+        ]
+    );
+
+    // interest is accrued at the previous rate before the switch
+    let res = query(
+        deps.as_ref(),
+        later_env.clone(),
+        QueryMsg::State {
+            block_height: None,
+            denom: Some("ukrw".to_string()),
+        },
+    )
+    .unwrap();
+    let state_res: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_res.last_interest_updated, later_env.block.height);
+    assert_eq!(
+        state_res.total_liabilities,
+        Decimal256::from_uint256(1000000u64)
+    );
+
+    let res = query(deps.as_ref(), later_env, QueryMsg::Stables {}).unwrap();
+    let stables_res: StablesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stables_res.stables[0],
+        StableResponse {
+            denom: "ukrw".to_string(),
+            aterra_contract: "aterra-ukrw".to_string(),
+            interest_model: "interest-ukrw2".to_string(),
+        }
+    );
+
+    // unregistered denom
+    let msg = ExecuteMsg::DepositStable {
+        denom: Some("ueur".to_string()),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "ueur".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+}
+
+#[test]
+fn repay_stable_from_liquidation_multi_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let register_aterra = |deps: &mut OwnedDeps<_, _, _>, aterra: &str| {
+        let mut token_inst_res = MsgInstantiateContractResponse::new();
+        token_inst_res.set_contract_address(aterra.to_string());
+        let reply_msg = Reply {
+            id: 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap()
+    };
+    let _res = register_aterra(&mut deps, "aterra-uusd");
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterStable {
+        denom: "ukrw".to_string(),
+        interest_model: "interest-ukrw".to_string(),
+        aterra_code_id: 123u64,
+    };
+    let info = mock_info(
+        "owner",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let _res = register_aterra(&mut deps, "aterra-ukrw");
+
+    let env = mock_env();
+    let balances = |uusd: u128, ukrw: u128| {
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(uusd),
+            },
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(ukrw),
+            },
+        ]
+    };
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        balances(INITIAL_DEPOSIT_AMOUNT, INITIAL_DEPOSIT_AMOUNT),
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra-uusd".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+        (
+            &"aterra-ukrw".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+    ]);
+    deps.querier.with_borrow_rate(&[
+        (&"interest".to_string(), &Decimal256::percent(1)),
+        (&"interest-ukrw".to_string(), &Decimal256::percent(1)),
+    ]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000u64))]);
+    deps.querier.with_oracle_price(&[(
+        &("ukrw".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1u64, 1000u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow 500uusd and 500000ukrw, priced 500uusd
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        denom: Some("ukrw".to_string()),
+        rate_mode: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the liquidation yields 800uusd, 500uusd repay the uusd loan
+    // and 300uusd are swapped to repay the ukrw loan
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        balances(INITIAL_DEPOSIT_AMOUNT + 800u128, INITIAL_DEPOSIT_AMOUNT),
+    );
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("repay_amount", "500"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(create_swap_msg(
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(300u128),
+                },
+                "ukrw".to_string(),
+            )),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayStableFromSwap {
+                    borrower: "addr0000".to_string(),
+                    denom: "ukrw".to_string(),
+                    prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the swap spread leaves a part of the ukrw loan
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        balances(INITIAL_DEPOSIT_AMOUNT, INITIAL_DEPOSIT_AMOUNT + 290000u128),
+    );
+    let swap_msg = ExecuteMsg::RepayStableFromSwap {
+        borrower: "addr0000".to_string(),
+        denom: "ukrw".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+    match execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        swap_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "ukrw"),
+            attr("repay_amount", "290000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerLoanValue {
+            borrower: "addr0000".to_string(),
+            block_height: env.block.height,
+            block_time: env.block.time.seconds(),
+        },
+    )
+    .unwrap();
+    let loan_value_res: BorrowerLoanValueResponse = from_binary(&res).unwrap();
+    assert_eq!(loan_value_res.loan_value, Uint256::from(210u64));
+
+    // the excess of the next liquidation is refunded
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        balances(INITIAL_DEPOSIT_AMOUNT + 1000u128, INITIAL_DEPOSIT_AMOUNT),
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(790u128),
+                }],
+            })),
+            SubMsg::new(create_swap_msg(
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(210u128),
+                },
+                "ukrw".to_string(),
+            )),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayStableFromSwap {
+                    borrower: "addr0000".to_string(),
+                    denom: "ukrw".to_string(),
+                    prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn flash_loan() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::permille(9),
+        stable_rate_premium: Decimal256::zero(),
+        stable_rate_rebalance_threshold: Decimal256::zero(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("aterra-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_token_balances(&[(
+        &"aterra-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000_000u128),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+
+    // cannot lend more than the market balance
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(1_000_000_001u64),
+        callback_contract: "bot".to_string(),
+        callback_msg: Binary::from(br#"{"callback":{}}"#),
+        denom: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Err(ContractError::NoStableAvailable(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(100_000_000u64),
+        callback_contract: "bot".to_string(),
+        callback_msg: Binary::from(br#"{"callback":{}}"#),
+        denom: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "bot".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(100_000_000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "bot".to_string(),
+                    funds: vec![],
+                    msg: Binary::from(br#"{"callback":{}}"#),
+                }),
+                2
+            ),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_loan"),
+            attr("callback_contract", "bot"),
+            attr("denom", "uusd"),
+            attr("amount", "100000000"),
+            attr("fee", "900000"),
+        ]
+    );
+
+    // market operations are locked during the loan
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Err(ContractError::FlashLoanInProgress {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let deposit_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000u128),
+        }],
+    );
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        deposit_info,
+        ExecuteMsg::DepositStable { denom: None },
+    ) {
+        Err(ContractError::FlashLoanInProgress {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1_000_000u64),
+        to: None,
+        denom: None,
+        rate_mode: None,
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::FlashLoanInProgress {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let callback_reply = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    // principal returned without the fee
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+    match reply(deps.as_mut(), mock_env(), callback_reply.clone()) {
+        Err(ContractError::FlashLoanNotRepaid(amount)) => assert_eq!(amount, "100900000uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_900_000u128),
+        }],
+    );
+    let res = reply(deps.as_mut(), mock_env(), callback_reply.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_loan_repaid"),
+            attr("denom", "uusd"),
+            attr("fee", "900000"),
+        ]
+    );
+
+    // fee is credited to the reserves
+    let state: State = read_state(&deps.storage, "uusd").unwrap();
+    assert_eq!(state.total_reserves, Decimal256::from_uint256(900_000u64));
+
+    // lock is released
+    match reply(deps.as_mut(), mock_env(), callback_reply) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn stable_rate_borrow() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: Decimal256::zero(),
+        stable_rate_premium: Decimal256::percent(1),
+        stable_rate_rebalance_threshold: Decimal256::percent(2),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("aterra-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.with_token_balances(&[(
+        &"aterra-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10000000u128),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10000000u128),
+        }],
+    );
+
+    // stable rate = borrow rate 1% + premium 1%
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        denom: None,
+        rate_mode: Some(RateMode::Stable),
+    };
+    let mut env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("borrow_amount", "500000"),
+            attr("rate_mode", "stable"),
+        ]
+    );
+
+    // rate mode of an existing loan cannot be changed with a borrow
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+        denom: None,
+        rate_mode: Some(RateMode::Variable),
+    };
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::RateModeMismatch(rate_mode)) => assert_eq!(rate_mode, "stable"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // loan = 500000 * (1 + 10 blocks * 2%)
+    env.block.height += 10;
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(600000u64));
+    assert_eq!(borrower_info.rate_mode, RateMode::Stable);
+    assert_eq!(borrower_info.stable_rate, Decimal256::percent(2));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State {
+            block_height: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::from_uint256(600000u64));
+    assert_eq!(
+        state.total_stable_liabilities,
+        Decimal256::from_uint256(600000u64)
+    );
+    assert_eq!(state.avg_stable_rate, Decimal256::percent(2));
+
+    // locked rate is within the rebalance threshold
+    let msg = ExecuteMsg::RebalanceStableRate {
+        borrower: "addr0000".to_string(),
+        denom: None,
+    };
+    let keeper_info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone()) {
+        Err(ContractError::RebalanceNotAllowed {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // drift = 5% + 1% - 2% > 2%
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(5))]);
+    let res = execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "rebalance_stable_rate"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("stable_rate", "0.06"),
+        ]
+    );
+
+    let state: State = read_state(&deps.storage, "uusd").unwrap();
+    assert_eq!(
+        state.total_stable_liabilities,
+        Decimal256::from_uint256(600000u64)
+    );
+    assert_eq!(state.avg_stable_rate, Decimal256::percent(6));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::SwapRateMode { denom: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_rate_mode"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+            attr("rate_mode", "variable"),
+            attr("stable_rate", "0"),
+        ]
+    );
+
+    let state: State = read_state(&deps.storage, "uusd").unwrap();
+    assert_eq!(state.total_stable_liabilities, Decimal256::zero());
+    assert_eq!(state.avg_stable_rate, Decimal256::zero());
+    assert_eq!(state.total_liabilities, Decimal256::from_uint256(600000u64));
+
+    match execute(
+        deps.as_mut(),
+        env,
+        keeper_info,
+        ExecuteMsg::SwapRateMode { denom: None },
+    ) {
+        Err(ContractError::NoLoan(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -4,7 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PriceResponse, PricesResponse, QueryMsg,
+    CircuitBreakerResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PriceHistoryResponse, PriceResponse, PriceSourcesResponse, PricesResponse, QueryMsg,
 };
 
 fn main() {
@@ -16,7 +17,11 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+    export_schema(&schema_for!(CircuitBreakerResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PriceHistoryResponse), &out_dir);
     export_schema(&schema_for!(PriceResponse), &out_dir);
+    export_schema(&schema_for!(PriceSourcesResponse), &out_dir);
     export_schema(&schema_for!(PricesResponse), &out_dir);
 }
@@ -1,16 +1,39 @@
 use crate::error::ContractError;
+use crate::querier::{query_oracle_price, query_oracle_twap_price, query_pair_price};
 use crate::state::{
-    read_config, read_feeder, read_price, read_prices, store_config, store_feeder, store_price,
-    Config, PriceInfo,
+    find_price_observation_seq, migrate_state, prune_price_observations, read_all_feeder_prices,
+    read_circuit_breaker, read_config, read_feeder_prices, read_feeders,
+    read_first_price_observation_seq, read_pair_price, read_pending_price, read_price_anchor,
+    read_price_history, read_price_observation, read_price_observation_count, read_price_sources,
+    remove_pending_price, store_circuit_breaker, store_config, store_feeder_prices, store_feeders,
+    store_pair_price, store_pending_price, store_price_anchor, store_price_observation,
+    store_price_sources, CircuitBreaker, Config, FeederPrice, PairPrice, PriceInfo,
+    PriceObservation, PriceSourceRaw,
 };
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage,
+};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    CircuitBreakerResponse, ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, MigrateMsg,
+    PriceHistoryResponse, PriceHistoryResponseElem, PriceResponse, PriceSource,
+    PriceSourcesResponse, PricesResponse, PricesResponseElem, QueryMsg,
 };
+use moneymarket::ownership;
+use moneymarket::version::{assert_migrate_version, is_older_version, set_contract_version};
+
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-oracle";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Cross rates of cross rates are allowed up to this depth
+const MAX_CROSS_RATE_DEPTH: u32 = 3;
+
+/// Pair observations a window needs to set a twap, each
+/// weighted for at most this share of the window
+const MIN_PAIR_WINDOW_SAMPLES: u64 = 3;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -18,12 +41,19 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    assert_quorum(msg.quorum)?;
+    assert_price_history_size(msg.price_history_size)?;
     store_config(
         deps.storage,
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             base_asset: msg.base_asset,
+            price_window: msg.price_window,
+            quorum: msg.quorum,
+            price_history_size: msg.price_history_size,
         },
     )?;
 
@@ -38,30 +68,122 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::UpdateConfig {
+            price_window,
+            quorum,
+            price_history_size,
+        } => update_config(deps, info, price_window, quorum, price_history_size),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
         ExecuteMsg::RegisterFeeder { asset, feeder } => register_feeder(deps, info, asset, feeder),
+        ExecuteMsg::DeregisterFeeder { asset, feeder } => {
+            deregister_feeder(deps, env, info, asset, feeder)
+        }
+        ExecuteMsg::UpdateCircuitBreaker {
+            asset,
+            max_update_deviation,
+            max_window_deviation,
+            deviation_window,
+        } => update_circuit_breaker(
+            deps,
+            info,
+            asset,
+            CircuitBreaker {
+                max_update_deviation,
+                max_window_deviation,
+                deviation_window,
+            },
+        ),
+        ExecuteMsg::UpdatePriceSources { asset, sources } => {
+            update_price_sources(deps, info, asset, sources)
+        }
         ExecuteMsg::FeedPrice { prices } => feed_prices(deps, env, info, prices),
+        ExecuteMsg::ObservePairPrices { asset } => observe_pair_prices(deps, env, info, asset),
     }
 }
 
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Option<String>,
+    price_window: Option<u64>,
+    quorum: Option<u32>,
+    price_history_size: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+    if let Some(price_window) = price_window {
+        config.price_window = price_window;
+    }
+
+    if let Some(quorum) = quorum {
+        assert_quorum(quorum)?;
+        config.quorum = quorum;
+    }
+
+    // Observations beyond a reduced size are pruned on the next updates
+    if let Some(price_history_size) = price_history_size {
+        assert_price_history_size(price_history_size)?;
+        config.price_history_size = price_history_size;
     }
 
     store_config(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
 pub fn register_feeder(
     deps: DepsMut,
     info: MessageInfo,
@@ -73,7 +195,12 @@ pub fn register_feeder(
         return Err(ContractError::Unauthorized {});
     }
 
-    store_feeder(deps.storage, &asset, &deps.api.addr_canonicalize(&feeder)?)?;
+    let feeder_raw = deps.api.addr_canonicalize(&feeder)?;
+    let mut feeders = read_feeders(deps.storage, &asset).unwrap_or_default();
+    if !feeders.contains(&feeder_raw) {
+        feeders.push(feeder_raw);
+        store_feeders(deps.storage, &asset, &feeders)?;
+    }
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register_feeder"),
@@ -82,12 +209,232 @@ pub fn register_feeder(
     ]))
 }
 
+pub fn deregister_feeder(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+    feeder: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let feeder_raw = deps.api.addr_canonicalize(&feeder)?;
+    let mut feeders = read_feeders(deps.storage, &asset)?;
+    if !feeders.contains(&feeder_raw) {
+        return Err(ContractError::FeederNotRegistered {});
+    }
+
+    feeders.retain(|f| *f != feeder_raw);
+    store_feeders(deps.storage, &asset, &feeders)?;
+
+    // Drop the submission so it no longer counts toward the quorum
+    let mut prices = read_feeder_prices(deps.storage, &asset).unwrap_or_default();
+    prices.retain(|p| p.feeder != feeder_raw);
+    store_feeder_prices(deps.storage, &asset, &prices)?;
+    record_price_observation(deps.storage, &config, &asset, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deregister_feeder"),
+        attr("asset", asset),
+        attr("feeder", feeder),
+    ]))
+}
+
+pub fn update_circuit_breaker(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    circuit_breaker: CircuitBreaker,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_circuit_breaker(deps.storage, &asset, &circuit_breaker)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_circuit_breaker"),
+        attr("asset", asset),
+        attr(
+            "max_update_deviation",
+            circuit_breaker.max_update_deviation.to_string(),
+        ),
+        attr(
+            "max_window_deviation",
+            circuit_breaker.max_window_deviation.to_string(),
+        ),
+        attr(
+            "deviation_window",
+            circuit_breaker.deviation_window.to_string(),
+        ),
+    ]))
+}
+
+pub fn update_price_sources(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    sources: Vec<PriceSource>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let sources_raw = sources
+        .into_iter()
+        .map(|source| {
+            Ok(match source {
+                PriceSource::Feeder {} => PriceSourceRaw::Feeder {},
+                PriceSource::TerraswapPair { pair, window } => {
+                    if window < MIN_PAIR_WINDOW_SAMPLES {
+                        return Err(ContractError::InvalidPairWindow(MIN_PAIR_WINDOW_SAMPLES));
+                    }
+                    PriceSourceRaw::TerraswapPair {
+                        pair: deps.api.addr_canonicalize(&pair)?,
+                        window,
+                    }
+                }
+                PriceSource::Oracle {
+                    contract,
+                    base,
+                    quote,
+                } => PriceSourceRaw::Oracle {
+                    contract: deps.api.addr_canonicalize(&contract)?,
+                    base,
+                    quote,
+                },
+                PriceSource::CrossRate { assets } => {
+                    if assets.is_empty() || assets.contains(&asset) {
+                        return Err(ContractError::InvalidCrossRate {});
+                    }
+                    PriceSourceRaw::CrossRate { assets }
+                }
+            })
+        })
+        .collect::<Result<Vec<PriceSourceRaw>, ContractError>>()?;
+    store_price_sources(deps.storage, &asset, &sources_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_price_sources"),
+        attr("asset", asset),
+    ]))
+}
+
+/// Integrates the spot price of each pair source since its last
+/// observation and completes the window once it has elapsed. The first
+/// observation of a block is kept, so a price moved later in the same
+/// block is never recorded
+pub fn observe_pair_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let block_time = env.block.time.seconds();
+
+    // Keepers are registered as feeders of the asset
+    let feeders = read_feeders(deps.storage, &asset).unwrap_or_default();
+    if !feeders.contains(&deps.api.addr_canonicalize(info.sender.as_str())?) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pairs: Vec<(CanonicalAddr, u64)> = read_price_sources(deps.storage, &asset)
+        .into_iter()
+        .filter_map(|source| match source {
+            PriceSourceRaw::TerraswapPair { pair, window } => Some((pair, window)),
+            _ => None,
+        })
+        .collect();
+    if pairs.is_empty() {
+        return Err(ContractError::NoPairPriceSource {});
+    }
+
+    for (pair, window) in pairs {
+        let spot_price = query_pair_price(
+            deps.as_ref(),
+            deps.api.addr_humanize(&pair)?,
+            &asset,
+            &config.base_asset,
+        )?;
+
+        let pair_price = match read_pair_price(deps.storage, &asset, &pair) {
+            None => PairPrice {
+                last_observed_time: block_time,
+                spot_price,
+                cumulative_price: Decimal256::zero(),
+                window_start_time: block_time,
+                window_start_cumulative_price: Decimal256::zero(),
+                window_weight: 0,
+                window_samples: 0,
+                twap: None,
+            },
+            Some(last) if last.last_observed_time == block_time => continue,
+            Some(last) => {
+                // A price held between sparse observations
+                // cannot outweigh its share of the window
+                let weight = std::cmp::min(
+                    block_time - last.last_observed_time,
+                    window / MIN_PAIR_WINDOW_SAMPLES,
+                );
+                let cumulative_price =
+                    last.cumulative_price + last.spot_price * Decimal256::from_uint256(weight);
+                let window_weight = last.window_weight + weight;
+                let window_samples = last.window_samples + 1;
+                if block_time - last.window_start_time >= window {
+                    // A window with too few samples keeps the previous twap
+                    let twap = if window_samples >= MIN_PAIR_WINDOW_SAMPLES {
+                        Some(PriceInfo {
+                            price: (cumulative_price - last.window_start_cumulative_price)
+                                / Decimal256::from_uint256(window_weight),
+                            last_updated_time: block_time,
+                        })
+                    } else {
+                        last.twap
+                    };
+                    PairPrice {
+                        last_observed_time: block_time,
+                        spot_price,
+                        cumulative_price,
+                        window_start_time: block_time,
+                        window_start_cumulative_price: cumulative_price,
+                        window_weight: 0,
+                        window_samples: 0,
+                        twap,
+                    }
+                } else {
+                    PairPrice {
+                        last_observed_time: block_time,
+                        spot_price,
+                        cumulative_price,
+                        window_weight,
+                        window_samples,
+                        ..last
+                    }
+                }
+            }
+        };
+        store_pair_price(deps.storage, &asset, &pair, &pair_price)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "observe_pair_prices"),
+        attr("asset", asset),
+    ]))
+}
+
 pub fn feed_prices(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     prices: Vec<(String, Decimal256)>,
 ) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
     let mut attributes = vec![attr("action", "feed_prices")];
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     for price in prices {
@@ -95,36 +442,208 @@ pub fn feed_prices(
         let price: Decimal256 = price.1;
 
         // Check feeder permission
-        let feeder = read_feeder(deps.storage, &asset)?;
-        if feeder != sender_raw {
+        let feeders = read_feeders(deps.storage, &asset)?;
+        if !feeders.contains(&sender_raw) {
             return Err(ContractError::Unauthorized {});
         }
 
+        if price.is_zero() {
+            return Err(ContractError::InvalidPrice {});
+        }
+
         attributes.push(attr("asset", asset.to_string()));
         attributes.push(attr("price", price.to_string()));
 
-        store_price(
-            deps.storage,
-            &asset,
-            &PriceInfo {
-                last_updated_time: env.block.time.seconds(),
-                price,
-            },
-        )?;
+        let submission = FeederPrice {
+            feeder: sender_raw.clone(),
+            price,
+            last_updated_time: env.block.time.seconds(),
+        };
+        let status = match read_circuit_breaker(deps.storage, &asset) {
+            Some(circuit_breaker) => apply_circuit_breaker(
+                deps.storage,
+                &config,
+                &circuit_breaker,
+                &asset,
+                submission,
+                env.block.time.seconds(),
+            )?,
+            None => {
+                store_submission(deps.storage, &asset, submission)?;
+                "accepted"
+            }
+        };
+        if status != "pending" {
+            record_price_observation(deps.storage, &config, &asset, env.block.time.seconds())?;
+        }
+        attributes.push(attr("status", status));
     }
 
     Ok(Response::new().add_attributes(attributes))
 }
 
+/// Submissions beyond the deviation limits are held as pending,
+/// freezing the asset until a second feeder confirms the move
+fn apply_circuit_breaker(
+    storage: &mut dyn Storage,
+    config: &Config,
+    circuit_breaker: &CircuitBreaker,
+    asset: &str,
+    submission: FeederPrice,
+    block_time: u64,
+) -> StdResult<&'static str> {
+    if let Some(pending_price) =
+        read_fresh_pending_price(storage, circuit_breaker, asset, block_time)
+    {
+        if pending_price.feeder != submission.feeder
+            && deviation(submission.price, pending_price.price)
+                <= circuit_breaker.max_update_deviation
+        {
+            remove_pending_price(storage, asset);
+            store_submission(storage, asset, pending_price)?;
+            store_submission(storage, asset, submission)?;
+
+            // The confirmed price starts a new deviation window
+            if let Ok(price) =
+                compute_median_price(config, &read_feeder_prices(storage, asset)?, block_time)
+            {
+                store_price_anchor(
+                    storage,
+                    asset,
+                    &PriceInfo {
+                        price: price.price,
+                        last_updated_time: block_time,
+                    },
+                )?;
+            }
+
+            return Ok("confirmed");
+        }
+    }
+
+    // Without a reference price there is nothing to deviate from
+    let prices = read_feeder_prices(storage, asset).unwrap_or_default();
+    if let Ok(reference) = compute_median_price(config, &prices, block_time) {
+        let anchor = match read_price_anchor(storage, asset) {
+            Some(anchor)
+                if block_time.saturating_sub(anchor.last_updated_time)
+                    <= circuit_breaker.deviation_window =>
+            {
+                anchor
+            }
+            _ => {
+                let anchor = PriceInfo {
+                    price: reference.price,
+                    last_updated_time: block_time,
+                };
+                store_price_anchor(storage, asset, &anchor)?;
+                anchor
+            }
+        };
+
+        if deviation(submission.price, reference.price) > circuit_breaker.max_update_deviation
+            || deviation(submission.price, anchor.price) > circuit_breaker.max_window_deviation
+        {
+            store_pending_price(storage, asset, &submission)?;
+            return Ok("pending");
+        }
+    }
+
+    store_submission(storage, asset, submission)?;
+    Ok("accepted")
+}
+
+/// Replace the previous submission of the feeder
+fn store_submission(
+    storage: &mut dyn Storage,
+    asset: &str,
+    submission: FeederPrice,
+) -> StdResult<()> {
+    let mut prices = read_feeder_prices(storage, asset).unwrap_or_default();
+    prices.retain(|p| p.feeder != submission.feeder);
+    prices.push(submission);
+    store_feeder_prices(storage, asset, &prices)
+}
+
+fn read_fresh_pending_price(
+    storage: &dyn Storage,
+    circuit_breaker: &CircuitBreaker,
+    asset: &str,
+    block_time: u64,
+) -> Option<FeederPrice> {
+    read_pending_price(storage, asset).filter(|pending_price| {
+        block_time.saturating_sub(pending_price.last_updated_time)
+            <= circuit_breaker.deviation_window
+    })
+}
+
+fn is_frozen(storage: &dyn Storage, asset: &str, block_time: u64) -> bool {
+    read_circuit_breaker(storage, asset)
+        .and_then(|circuit_breaker| {
+            read_fresh_pending_price(storage, &circuit_breaker, asset, block_time)
+        })
+        .is_some()
+}
+
+/// Cross rates are frozen with any of their components
+fn is_price_frozen(storage: &dyn Storage, asset: &str, block_time: u64, depth: u32) -> bool {
+    if depth > MAX_CROSS_RATE_DEPTH {
+        return false;
+    }
+
+    is_frozen(storage, asset, block_time)
+        || read_price_sources(storage, asset)
+            .iter()
+            .any(|source| match source {
+                PriceSourceRaw::CrossRate { assets } => assets
+                    .iter()
+                    .any(|a| is_price_frozen(storage, a, block_time, depth + 1)),
+                _ => false,
+            })
+}
+
+fn deviation(price: Decimal256, reference: Decimal256) -> Decimal256 {
+    if reference.is_zero() {
+        Decimal256::MAX
+    } else if price > reference {
+        (price - reference) / reference
+    } else {
+        (reference - price) / reference
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if is_older_version(&stored_version, "0.4.0")? {
+        migrate_state(deps.storage)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Feeder { asset } => to_binary(&query_feeder(deps, asset)?),
-        QueryMsg::Price { base, quote } => to_binary(&query_price(deps, base, quote)?),
+        QueryMsg::Price { base, quote } => to_binary(&query_price(deps, env, base, quote)?),
+        QueryMsg::TwapPrice {
+            base,
+            quote,
+            window_seconds,
+        } => to_binary(&query_twap_price(deps, env, base, quote, window_seconds)?),
         QueryMsg::Prices { start_after, limit } => {
-            to_binary(&query_prices(deps, start_after, limit)?)
+            to_binary(&query_prices(deps, env, start_after, limit)?)
         }
+        QueryMsg::CircuitBreaker { asset } => to_binary(&query_circuit_breaker(deps, env, asset)?),
+        QueryMsg::PriceSources { asset } => to_binary(&query_price_sources(deps, asset)?),
+        QueryMsg::PriceHistory {
+            asset,
+            start_after_time,
+            limit,
+        } => to_binary(&query_price_history(deps, asset, start_after_time, limit)?),
     }
 }
 
@@ -133,22 +652,28 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         base_asset: state.base_asset,
+        price_window: state.price_window,
+        quorum: state.quorum,
+        price_history_size: state.price_history_size,
     };
 
     Ok(resp)
 }
 
 fn query_feeder(deps: Deps, asset: String) -> StdResult<FeederResponse> {
-    let feeder = read_feeder(deps.storage, &asset)?;
+    let feeders = read_feeders(deps.storage, &asset)?;
     let resp = FeederResponse {
         asset,
-        feeder: deps.api.addr_humanize(&feeder)?.to_string(),
+        feeders: feeders
+            .iter()
+            .map(|feeder| Ok(deps.api.addr_humanize(feeder)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
     };
 
     Ok(resp)
 }
 
-fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceResponse> {
+fn query_price(deps: Deps, env: Env, base: String, quote: String) -> StdResult<PriceResponse> {
     let config: Config = read_config(deps.storage)?;
     let quote_price = if config.base_asset == quote {
         PriceInfo {
@@ -156,7 +681,7 @@ fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceRespon
             last_updated_time: 9999999999,
         }
     } else {
-        read_price(deps.storage, &quote)?
+        compute_asset_price(deps, &config, &quote, env.block.time.seconds(), 0)?
     };
 
     let base_price = if config.base_asset == base {
@@ -165,21 +690,440 @@ fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceRespon
             last_updated_time: 9999999999,
         }
     } else {
-        read_price(deps.storage, &base)?
+        compute_asset_price(deps, &config, &base, env.block.time.seconds(), 0)?
     };
 
     Ok(PriceResponse {
         rate: base_price.price / quote_price.price,
         last_updated_base: base_price.last_updated_time,
         last_updated_quote: quote_price.last_updated_time,
+        frozen: is_price_frozen(deps.storage, &base, env.block.time.seconds(), 0)
+            || is_price_frozen(deps.storage, &quote, env.block.time.seconds(), 0),
+    })
+}
+
+/// Staleness and freeze information follow the spot price
+fn query_twap_price(
+    deps: Deps,
+    env: Env,
+    base: String,
+    quote: String,
+    window_seconds: u64,
+) -> StdResult<PriceResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let spot_price = query_price(deps, env.clone(), base.clone(), quote.clone())?;
+
+    let twap = |asset: &str| -> StdResult<Decimal256> {
+        if config.base_asset == asset {
+            Ok(Decimal256::one())
+        } else {
+            compute_asset_twap(
+                deps,
+                &config,
+                asset,
+                window_seconds,
+                env.block.time.seconds(),
+                0,
+            )
+        }
+    };
+
+    Ok(PriceResponse {
+        rate: twap(&base)? / twap(&quote)?,
+        ..spot_price
     })
 }
 
+fn query_price_sources(deps: Deps, asset: String) -> StdResult<PriceSourcesResponse> {
+    let sources = read_price_sources(deps.storage, &asset)
+        .into_iter()
+        .map(|source| {
+            Ok(match source {
+                PriceSourceRaw::Feeder {} => PriceSource::Feeder {},
+                PriceSourceRaw::TerraswapPair { pair, window } => PriceSource::TerraswapPair {
+                    pair: deps.api.addr_humanize(&pair)?.to_string(),
+                    window,
+                },
+                PriceSourceRaw::Oracle {
+                    contract,
+                    base,
+                    quote,
+                } => PriceSource::Oracle {
+                    contract: deps.api.addr_humanize(&contract)?.to_string(),
+                    base,
+                    quote,
+                },
+                PriceSourceRaw::CrossRate { assets } => PriceSource::CrossRate { assets },
+            })
+        })
+        .collect::<StdResult<Vec<PriceSource>>>()?;
+
+    Ok(PriceSourcesResponse { asset, sources })
+}
+
+fn query_price_history(
+    deps: Deps,
+    asset: String,
+    start_after_time: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PriceHistoryResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let history = read_price_history(
+        deps.storage,
+        &asset,
+        config.price_history_size,
+        start_after_time,
+        limit,
+    )?
+    .into_iter()
+    .map(|observation| PriceHistoryResponseElem {
+        price: observation.price,
+        last_updated_time: observation.time,
+    })
+    .collect();
+
+    Ok(PriceHistoryResponse { asset, history })
+}
+
+fn query_circuit_breaker(deps: Deps, env: Env, asset: String) -> StdResult<CircuitBreakerResponse> {
+    let circuit_breaker = read_circuit_breaker(deps.storage, &asset)
+        .ok_or_else(|| StdError::generic_err("No circuit breaker for the specified asset exist"))?;
+    let pending_price = read_fresh_pending_price(
+        deps.storage,
+        &circuit_breaker,
+        &asset,
+        env.block.time.seconds(),
+    );
+
+    Ok(CircuitBreakerResponse {
+        asset,
+        max_update_deviation: circuit_breaker.max_update_deviation,
+        max_window_deviation: circuit_breaker.max_window_deviation,
+        deviation_window: circuit_breaker.deviation_window,
+        frozen: pending_price.is_some(),
+        pending_price: pending_price.map(|p| p.price),
+    })
+}
+
+/// Assets without a quorum of fresh submissions are skipped
 fn query_prices(
     deps: Deps,
+    env: Env,
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<PricesResponse> {
-    let prices: Vec<PricesResponseElem> = read_prices(deps.storage, start_after, limit)?;
+    let config: Config = read_config(deps.storage)?;
+    let prices: Vec<PricesResponseElem> = read_all_feeder_prices(deps.storage, start_after, limit)?
+        .into_iter()
+        .filter_map(|(asset, _)| {
+            compute_asset_price(deps, &config, &asset, env.block.time.seconds(), 0)
+                .ok()
+                .map(|price| PricesResponseElem {
+                    asset,
+                    price: price.price,
+                    last_updated_time: price.last_updated_time,
+                })
+        })
+        .collect();
+
     Ok(PricesResponse { prices })
 }
+
+/// Price from the first source of the asset with a fresh price,
+/// the error of the last source is returned when none has one.
+/// `depth` counts the cross rates the asset is a component of
+fn compute_asset_price(
+    deps: Deps,
+    config: &Config,
+    asset: &str,
+    block_time: u64,
+    depth: u32,
+) -> StdResult<PriceInfo> {
+    if depth > MAX_CROSS_RATE_DEPTH {
+        return Err(StdError::generic_err("Cross rate nesting is too deep"));
+    }
+
+    let mut result = Err(StdError::generic_err(
+        "No price source for the specified asset exist",
+    ));
+    for source in read_price_sources(deps.storage, asset) {
+        result = compute_source_price(deps, config, asset, &source, block_time, depth);
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+fn compute_source_price(
+    deps: Deps,
+    config: &Config,
+    asset: &str,
+    source: &PriceSourceRaw,
+    block_time: u64,
+    depth: u32,
+) -> StdResult<PriceInfo> {
+    match source {
+        PriceSourceRaw::Feeder {} => compute_median_price(
+            config,
+            &read_feeder_prices(deps.storage, asset)?,
+            block_time,
+        ),
+        // The spot reserves ratio can be moved within a transaction,
+        // only its average over a completed window is used
+        PriceSourceRaw::TerraswapPair { pair, .. } => {
+            let twap = read_pair_price(deps.storage, asset, pair)
+                .and_then(|pair_price| pair_price.twap)
+                .ok_or_else(|| StdError::generic_err("Pair has no completed price window"))?;
+            if block_time.saturating_sub(twap.last_updated_time) > config.price_window {
+                return Err(StdError::generic_err("Price of the pair is stale"));
+            }
+
+            Ok(twap)
+        }
+        PriceSourceRaw::Oracle {
+            contract,
+            base,
+            quote,
+        } => {
+            let price = query_oracle_price(
+                deps,
+                deps.api.addr_humanize(contract)?,
+                base.to_string(),
+                quote.to_string(),
+            )?;
+            let last_updated_time = price.last_updated_base.min(price.last_updated_quote);
+            if price.frozen || block_time.saturating_sub(last_updated_time) > config.price_window {
+                return Err(StdError::generic_err("Price of the source oracle is stale"));
+            }
+
+            Ok(PriceInfo {
+                price: price.rate,
+                last_updated_time,
+            })
+        }
+        // As old as the oldest component
+        PriceSourceRaw::CrossRate { assets } => {
+            let mut cross_rate = PriceInfo {
+                price: Decimal256::one(),
+                last_updated_time: u64::MAX,
+            };
+            for component in assets.iter().filter(|a| **a != config.base_asset) {
+                let price = compute_asset_price(deps, config, component, block_time, depth + 1)?;
+                cross_rate.price = cross_rate.price * price.price;
+                cross_rate.last_updated_time =
+                    cross_rate.last_updated_time.min(price.last_updated_time);
+            }
+
+            Ok(cross_rate)
+        }
+    }
+}
+
+/// Median of the submissions fed within the price window. The
+/// oldest submission used is reported as the update time
+fn compute_median_price(
+    config: &Config,
+    prices: &[FeederPrice],
+    block_time: u64,
+) -> StdResult<PriceInfo> {
+    let mut fresh_prices: Vec<&FeederPrice> = prices
+        .iter()
+        .filter(|p| block_time.saturating_sub(p.last_updated_time) <= config.price_window)
+        .collect();
+    if fresh_prices.is_empty() || fresh_prices.len() < config.quorum as usize {
+        return Err(StdError::generic_err(format!(
+            "Price quorum not met: {} of {} fresh submissions",
+            fresh_prices.len(),
+            config.quorum
+        )));
+    }
+
+    fresh_prices.sort_by_key(|p| p.price);
+    let mid = fresh_prices.len() / 2;
+    let price = if fresh_prices.len() % 2 == 1 {
+        fresh_prices[mid].price
+    } else {
+        (fresh_prices[mid - 1].price + fresh_prices[mid].price)
+            / Decimal256::from_uint256(Uint256::from(2u64))
+    };
+
+    Ok(PriceInfo {
+        price,
+        last_updated_time: fresh_prices
+            .iter()
+            .map(|p| p.last_updated_time)
+            .min()
+            .unwrap_or(block_time),
+    })
+}
+
+/// Extend the price accumulator with the current median price,
+/// assets without a quorum keep their last observed price
+fn record_price_observation(
+    storage: &mut dyn Storage,
+    config: &Config,
+    asset: &str,
+    block_time: u64,
+) -> StdResult<()> {
+    let prices = read_feeder_prices(storage, asset).unwrap_or_default();
+    let price = match compute_median_price(config, &prices, block_time) {
+        Ok(price) => price.price,
+        Err(_) => return Ok(()),
+    };
+
+    let count = read_price_observation_count(storage, asset);
+    if count == 0 {
+        return store_price_observation(
+            storage,
+            asset,
+            count,
+            &PriceObservation {
+                time: block_time,
+                price,
+                cumulative_price: Decimal256::zero(),
+            },
+        );
+    }
+
+    // Several updates in a block leave a single observation
+    let last = read_price_observation(storage, asset, count - 1)?;
+    if last.time == block_time {
+        return store_price_observation(
+            storage,
+            asset,
+            count - 1,
+            &PriceObservation { price, ..last },
+        );
+    }
+
+    store_price_observation(
+        storage,
+        asset,
+        count,
+        &PriceObservation {
+            time: block_time,
+            price,
+            cumulative_price: last.cumulative_price
+                + last.price * Decimal256::from_uint256(block_time - last.time),
+        },
+    )?;
+    prune_price_observations(storage, asset, config.price_history_size)
+}
+
+/// TWAP of the source the spot price of the asset is taken from
+fn compute_asset_twap(
+    deps: Deps,
+    config: &Config,
+    asset: &str,
+    window: u64,
+    block_time: u64,
+    depth: u32,
+) -> StdResult<Decimal256> {
+    if depth > MAX_CROSS_RATE_DEPTH {
+        return Err(StdError::generic_err("Cross rate nesting is too deep"));
+    }
+
+    let mut result = Err(StdError::generic_err(
+        "No price source for the specified asset exist",
+    ));
+    for source in read_price_sources(deps.storage, asset) {
+        result =
+            compute_source_price(deps, config, asset, &source, block_time, depth).and_then(|_| {
+                compute_source_twap(deps, config, asset, &source, window, block_time, depth)
+            });
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+fn compute_source_twap(
+    deps: Deps,
+    config: &Config,
+    asset: &str,
+    source: &PriceSourceRaw,
+    window: u64,
+    block_time: u64,
+    depth: u32,
+) -> StdResult<Decimal256> {
+    match source {
+        PriceSourceRaw::Feeder {} => compute_twap(deps.storage, config, asset, window, block_time),
+        // Already averaged over the last completed window of the pair
+        PriceSourceRaw::TerraswapPair { .. } => {
+            compute_source_price(deps, config, asset, source, block_time, depth)
+                .map(|twap| twap.price)
+        }
+        PriceSourceRaw::Oracle {
+            contract,
+            base,
+            quote,
+        } => Ok(query_oracle_twap_price(
+            deps,
+            deps.api.addr_humanize(contract)?,
+            base.to_string(),
+            quote.to_string(),
+            window,
+        )?
+        .rate),
+        PriceSourceRaw::CrossRate { assets } => {
+            let mut cross_rate = Decimal256::one();
+            for component in assets.iter().filter(|a| **a != config.base_asset) {
+                cross_rate = cross_rate
+                    * compute_asset_twap(deps, config, component, window, block_time, depth + 1)?;
+            }
+
+            Ok(cross_rate)
+        }
+    }
+}
+
+fn compute_twap(
+    storage: &dyn Storage,
+    config: &Config,
+    asset: &str,
+    window: u64,
+    block_time: u64,
+) -> StdResult<Decimal256> {
+    if window == 0 {
+        return Err(StdError::generic_err("TWAP window must be positive"));
+    }
+
+    // The observation in effect at the start of the window
+    let start_time = block_time.saturating_sub(window);
+    let count = read_price_observation_count(storage, asset);
+    let seq = find_price_observation_seq(storage, asset, config.price_history_size, start_time)?;
+    if seq == read_first_price_observation_seq(storage, asset, config.price_history_size)? {
+        return Err(StdError::generic_err(
+            "Not enough price history for the TWAP window",
+        ));
+    }
+
+    let start = read_price_observation(storage, asset, seq - 1)?;
+    let last = read_price_observation(storage, asset, count - 1)?;
+    let cumulative_start =
+        start.cumulative_price + start.price * Decimal256::from_uint256(start_time - start.time);
+    let cumulative_end =
+        last.cumulative_price + last.price * Decimal256::from_uint256(block_time - last.time);
+
+    Ok((cumulative_end - cumulative_start) / Decimal256::from_uint256(window))
+}
+
+fn assert_quorum(quorum: u32) -> Result<(), ContractError> {
+    if quorum == 0 {
+        return Err(ContractError::InvalidQuorum {});
+    }
+
+    Ok(())
+}
+
+fn assert_price_history_size(price_history_size: u64) -> Result<(), ContractError> {
+    if price_history_size == 0 {
+        return Err(ContractError::InvalidPriceHistorySize {});
+    }
+
+    Ok(())
+}
@@ -11,4 +11,25 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Feeder is not registered for the asset")]
+    FeederNotRegistered {},
+
+    #[error("Price must be greater than zero")]
+    InvalidPrice {},
+
+    #[error("Quorum must be at least one")]
+    InvalidQuorum {},
+
+    #[error("Price history size must be at least one")]
+    InvalidPriceHistorySize {},
+
+    #[error("Cross rate needs components other than the asset itself")]
+    InvalidCrossRate {},
+
+    #[error("Pair price window must be at least {0} seconds")]
+    InvalidPairWindow(u64),
+
+    #[error("Asset has no terraswap pair price source")]
+    NoPairPriceSource {},
 }
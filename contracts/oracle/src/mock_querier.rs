@@ -0,0 +1,128 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use std::collections::HashMap;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::PoolResponse;
+
+use moneymarket::oracle::PriceResponse;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query reserves to terraswap pair contract
+    Pool {},
+    /// Query price to another oracle contract
+    Price { base: String, quote: String },
+}
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    pools: HashMap<String, [(String, Uint128); 2]>,
+    oracle_prices: HashMap<String, PriceResponse>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::Pool {} => match self.pools.get(contract_addr) {
+                        Some(reserves) => {
+                            let asset = |(denom, amount): &(String, Uint128)| Asset {
+                                info: AssetInfo::NativeToken {
+                                    denom: denom.to_string(),
+                                },
+                                amount: *amount,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&PoolResponse {
+                                assets: [asset(&reserves[0]), asset(&reserves[1])],
+                                total_share: Uint128::zero(),
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No pool exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    QueryMsg::Price { .. } => match self.oracle_prices.get(contract_addr) {
+                        Some(price) => SystemResult::Ok(ContractResult::from(to_binary(price))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No oracle price exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            pools: HashMap::new(),
+            oracle_prices: HashMap::new(),
+        }
+    }
+
+    pub fn with_pool(&mut self, pair: &str, reserves: [(&str, u128); 2]) {
+        self.pools.insert(
+            pair.to_string(),
+            [
+                (reserves[0].0.to_string(), Uint128::from(reserves[0].1)),
+                (reserves[1].0.to_string(), Uint128::from(reserves[1].1)),
+            ],
+        );
+    }
+
+    pub fn with_oracle_price(&mut self, oracle: &str, rate: Decimal256, last_updated: u64) {
+        self.oracle_prices.insert(
+            oracle.to_string(),
+            PriceResponse {
+                rate,
+                last_updated_base: last_updated,
+                last_updated_quote: last_updated,
+                frozen: false,
+            },
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdError, StdResult, WasmQuery};
+use terraswap::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+
+use moneymarket::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+
+/// Price of an asset in the base asset from the reserves of a terraswap pair
+pub fn query_pair_price(
+    deps: Deps,
+    pair_addr: Addr,
+    asset: &str,
+    base_asset: &str,
+) -> StdResult<Decimal256> {
+    let pool: PoolResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_addr.to_string(),
+        msg: to_binary(&PairQueryMsg::Pool {})?,
+    }))?;
+
+    let reserve = |asset: &str| {
+        pool.assets
+            .iter()
+            .find(|pool_asset| pool_asset.info.to_string() == asset)
+            .map(|pool_asset| Uint256::from(pool_asset.amount))
+    };
+    match (reserve(asset), reserve(base_asset)) {
+        (Some(asset_reserve), Some(base_reserve)) if !asset_reserve.is_zero() => {
+            Ok(Decimal256::from_uint256(base_reserve) / Decimal256::from_uint256(asset_reserve))
+        }
+        (Some(_), Some(_)) => Err(StdError::generic_err("Pair has no liquidity")),
+        _ => Err(StdError::generic_err(format!(
+            "Pair does not trade {} against {}",
+            asset, base_asset
+        ))),
+    }
+}
+
+pub fn query_oracle_price(
+    deps: Deps,
+    oracle_addr: Addr,
+    base: String,
+    quote: String,
+) -> StdResult<PriceResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle_addr.to_string(),
+        msg: to_binary(&OracleQueryMsg::Price { base, quote })?,
+    }))
+}
+
+pub fn query_oracle_twap_price(
+    deps: Deps,
+    oracle_addr: Addr,
+    base: String,
+    quote: String,
+    window_seconds: u64,
+) -> StdResult<PriceResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle_addr.to_string(),
+        msg: to_binary(&OracleQueryMsg::TwapPrice {
+            base,
+            quote,
+            window_seconds,
+        })?,
+    }))
+}
@@ -3,12 +3,24 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
-
-use moneymarket::oracle::PricesResponseElem;
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static PREFIX_PRICE: &[u8] = b"price";
 static PREFIX_FEEDER: &[u8] = b"feeder";
+static PREFIX_CIRCUIT_BREAKER: &[u8] = b"circuit_breaker";
+static PREFIX_PRICE_ANCHOR: &[u8] = b"price_anchor";
+static PREFIX_PENDING_PRICE: &[u8] = b"pending_price";
+static PREFIX_PRICE_OBSERVATION: &[u8] = b"price_observation";
+static PREFIX_PRICE_OBSERVATION_COUNT: &[u8] = b"price_observation_count";
+static PREFIX_PRICE_SOURCE: &[u8] = b"price_source";
+static PREFIX_PAIR_PRICE: &[u8] = b"pair_price";
+
+/// History size of contracts migrated from a version without price history
+pub const DEFAULT_PRICE_HISTORY_SIZE: u64 = 1000;
+
+/// Observations beyond the history size removed per update,
+/// so shrinking the history does not make a single feed expensive
+const MAX_PRUNED_OBSERVATIONS: usize = 10;
 
 static KEY_CONFIG: &[u8] = b"config";
 
@@ -16,6 +28,9 @@ static KEY_CONFIG: &[u8] = b"config";
 pub struct Config {
     pub owner: CanonicalAddr,
     pub base_asset: String,
+    pub price_window: u64,
+    pub quorum: u32,
+    pub price_history_size: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -32,13 +47,30 @@ pub struct PriceInfo {
     pub last_updated_time: u64,
 }
 
-pub fn store_price(storage: &mut dyn Storage, asset: &str, price: &PriceInfo) -> StdResult<()> {
-    let mut price_bucket: Bucket<PriceInfo> = Bucket::new(storage, PREFIX_PRICE);
-    price_bucket.save(asset.as_bytes(), price)
+/// Latest submission of a feeder
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederPrice {
+    pub feeder: CanonicalAddr,
+    pub price: Decimal256,
+    pub last_updated_time: u64,
 }
 
-pub fn read_price(storage: &dyn Storage, asset: &str) -> StdResult<PriceInfo> {
-    let price_bucket: ReadonlyBucket<PriceInfo> = ReadonlyBucket::new(storage, PREFIX_PRICE);
+pub fn store_feeder_prices(
+    storage: &mut dyn Storage,
+    asset: &str,
+    prices: &[FeederPrice],
+) -> StdResult<()> {
+    let mut price_bucket: Bucket<Vec<FeederPrice>> = Bucket::new(storage, PREFIX_PRICE);
+    if prices.is_empty() {
+        price_bucket.remove(asset.as_bytes());
+        Ok(())
+    } else {
+        price_bucket.save(asset.as_bytes(), &prices.to_vec())
+    }
+}
+
+pub fn read_feeder_prices(storage: &dyn Storage, asset: &str) -> StdResult<Vec<FeederPrice>> {
+    let price_bucket: ReadonlyBucket<Vec<FeederPrice>> = ReadonlyBucket::new(storage, PREFIX_PRICE);
     let res = price_bucket.load(asset.as_bytes());
     match res {
         Ok(data) => Ok(data),
@@ -51,12 +83,12 @@ pub fn read_price(storage: &dyn Storage, asset: &str) -> StdResult<PriceInfo> {
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
-pub fn read_prices(
+pub fn read_all_feeder_prices(
     storage: &dyn Storage,
     start_after: Option<String>,
     limit: Option<u32>,
-) -> StdResult<Vec<PricesResponseElem>> {
-    let price_bucket: ReadonlyBucket<PriceInfo> = ReadonlyBucket::new(storage, PREFIX_PRICE);
+) -> StdResult<Vec<(String, Vec<FeederPrice>)>> {
+    let price_bucket: ReadonlyBucket<Vec<FeederPrice>> = ReadonlyBucket::new(storage, PREFIX_PRICE);
 
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after);
@@ -68,27 +100,24 @@ pub fn read_prices(
             let (k, v) = item?;
 
             let asset = std::str::from_utf8(&k).unwrap().to_string();
-            Ok(PricesResponseElem {
-                asset,
-                price: v.price,
-                last_updated_time: v.last_updated_time,
-            })
+            Ok((asset, v))
         })
         .collect()
 }
 
-pub fn store_feeder(
+pub fn store_feeders(
     storage: &mut dyn Storage,
     asset: &str,
-    feeder: &CanonicalAddr,
+    feeders: &[CanonicalAddr],
 ) -> StdResult<()> {
-    let mut price_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_FEEDER);
-    price_bucket.save(asset.as_bytes(), feeder)
+    let mut feeder_bucket: Bucket<Vec<CanonicalAddr>> = Bucket::new(storage, PREFIX_FEEDER);
+    feeder_bucket.save(asset.as_bytes(), &feeders.to_vec())
 }
 
-pub fn read_feeder(storage: &dyn Storage, asset: &str) -> StdResult<CanonicalAddr> {
-    let price_bucket: ReadonlyBucket<CanonicalAddr> = ReadonlyBucket::new(storage, PREFIX_FEEDER);
-    let res = price_bucket.load(asset.as_bytes());
+pub fn read_feeders(storage: &dyn Storage, asset: &str) -> StdResult<Vec<CanonicalAddr>> {
+    let feeder_bucket: ReadonlyBucket<Vec<CanonicalAddr>> =
+        ReadonlyBucket::new(storage, PREFIX_FEEDER);
+    let res = feeder_bucket.load(asset.as_bytes());
     match res {
         Ok(data) => Ok(data),
         Err(_err) => Err(StdError::generic_err(
@@ -97,6 +126,275 @@ pub fn read_feeder(storage: &dyn Storage, asset: &str) -> StdResult<CanonicalAdd
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CircuitBreaker {
+    pub max_update_deviation: Decimal256,
+    pub max_window_deviation: Decimal256,
+    pub deviation_window: u64,
+}
+
+pub fn store_circuit_breaker(
+    storage: &mut dyn Storage,
+    asset: &str,
+    circuit_breaker: &CircuitBreaker,
+) -> StdResult<()> {
+    let mut circuit_breaker_bucket: Bucket<CircuitBreaker> =
+        Bucket::new(storage, PREFIX_CIRCUIT_BREAKER);
+    circuit_breaker_bucket.save(asset.as_bytes(), circuit_breaker)
+}
+
+pub fn read_circuit_breaker(storage: &dyn Storage, asset: &str) -> Option<CircuitBreaker> {
+    let circuit_breaker_bucket: ReadonlyBucket<CircuitBreaker> =
+        ReadonlyBucket::new(storage, PREFIX_CIRCUIT_BREAKER);
+    circuit_breaker_bucket.load(asset.as_bytes()).ok()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSourceRaw {
+    Feeder {},
+    TerraswapPair {
+        pair: CanonicalAddr,
+        window: u64,
+    },
+    Oracle {
+        contract: CanonicalAddr,
+        base: String,
+        quote: String,
+    },
+    CrossRate {
+        assets: Vec<String>,
+    },
+}
+
+pub fn store_price_sources(
+    storage: &mut dyn Storage,
+    asset: &str,
+    sources: &[PriceSourceRaw],
+) -> StdResult<()> {
+    let mut source_bucket: Bucket<Vec<PriceSourceRaw>> = Bucket::new(storage, PREFIX_PRICE_SOURCE);
+    if sources.is_empty() {
+        source_bucket.remove(asset.as_bytes());
+        Ok(())
+    } else {
+        source_bucket.save(asset.as_bytes(), &sources.to_vec())
+    }
+}
+
+/// Assets without configured sources are priced by their feeders
+pub fn read_price_sources(storage: &dyn Storage, asset: &str) -> Vec<PriceSourceRaw> {
+    let source_bucket: ReadonlyBucket<Vec<PriceSourceRaw>> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_SOURCE);
+    match source_bucket.load(asset.as_bytes()) {
+        Ok(v) => v,
+        _ => vec![PriceSourceRaw::Feeder {}],
+    }
+}
+
+/// Spot price of a pair source integrated over the capped observation
+/// times, `twap` is the average of the last completed window
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairPrice {
+    pub last_observed_time: u64,
+    pub spot_price: Decimal256,
+    pub cumulative_price: Decimal256,
+    pub window_start_time: u64,
+    pub window_start_cumulative_price: Decimal256,
+    /// Sum of the capped observation times in the current window
+    pub window_weight: u64,
+    pub window_samples: u64,
+    pub twap: Option<PriceInfo>,
+}
+
+pub fn store_pair_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    pair: &CanonicalAddr,
+    pair_price: &PairPrice,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_PAIR_PRICE, asset.as_bytes()])
+        .save(pair.as_slice(), pair_price)
+}
+
+pub fn read_pair_price(
+    storage: &dyn Storage,
+    asset: &str,
+    pair: &CanonicalAddr,
+) -> Option<PairPrice> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_PAIR_PRICE, asset.as_bytes()])
+        .load(pair.as_slice())
+        .ok()
+}
+
+/// Price at the start of the current deviation window
+pub fn store_price_anchor(
+    storage: &mut dyn Storage,
+    asset: &str,
+    anchor: &PriceInfo,
+) -> StdResult<()> {
+    let mut anchor_bucket: Bucket<PriceInfo> = Bucket::new(storage, PREFIX_PRICE_ANCHOR);
+    anchor_bucket.save(asset.as_bytes(), anchor)
+}
+
+pub fn read_price_anchor(storage: &dyn Storage, asset: &str) -> Option<PriceInfo> {
+    let anchor_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::new(storage, PREFIX_PRICE_ANCHOR);
+    anchor_bucket.load(asset.as_bytes()).ok()
+}
+
+pub fn store_pending_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    pending_price: &FeederPrice,
+) -> StdResult<()> {
+    let mut pending_bucket: Bucket<FeederPrice> = Bucket::new(storage, PREFIX_PENDING_PRICE);
+    pending_bucket.save(asset.as_bytes(), pending_price)
+}
+
+pub fn remove_pending_price(storage: &mut dyn Storage, asset: &str) {
+    let mut pending_bucket: Bucket<FeederPrice> = Bucket::new(storage, PREFIX_PENDING_PRICE);
+    pending_bucket.remove(asset.as_bytes())
+}
+
+pub fn read_pending_price(storage: &dyn Storage, asset: &str) -> Option<FeederPrice> {
+    let pending_bucket: ReadonlyBucket<FeederPrice> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_PRICE);
+    pending_bucket.load(asset.as_bytes()).ok()
+}
+
+/// Aggregated price of an asset from `time` until the next
+/// observation, with the time weighted sum of all prices before
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceObservation {
+    pub time: u64,
+    pub price: Decimal256,
+    pub cumulative_price: Decimal256,
+}
+
+pub fn store_price_observation(
+    storage: &mut dyn Storage,
+    asset: &str,
+    seq: u64,
+    observation: &PriceObservation,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()])
+        .save(&seq.to_be_bytes(), observation)?;
+
+    let count = read_price_observation_count(storage, asset);
+    if seq >= count {
+        bucket(storage, PREFIX_PRICE_OBSERVATION_COUNT).save(asset.as_bytes(), &(seq + 1))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the total number of observations ever written
+pub fn read_price_observation_count(storage: &dyn Storage, asset: &str) -> u64 {
+    bucket_read(storage, PREFIX_PRICE_OBSERVATION_COUNT)
+        .load(asset.as_bytes())
+        .unwrap_or_default()
+}
+
+/// Observations are addressed with a sequence number, only the
+/// last `history_size` sequences belong to the price history
+pub fn read_price_observation(
+    storage: &dyn Storage,
+    asset: &str,
+    seq: u64,
+) -> StdResult<PriceObservation> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()])
+        .load(&seq.to_be_bytes())
+}
+
+/// Returns the sequence of the oldest observation of the price history,
+/// observations pruned before a history size increase are not restored
+pub fn read_first_price_observation_seq(
+    storage: &dyn Storage,
+    asset: &str,
+    history_size: u64,
+) -> StdResult<u64> {
+    let first_seq = read_price_observation_count(storage, asset).saturating_sub(history_size);
+    let observation_bucket: ReadonlyBucket<PriceObservation> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()]);
+    let stored_key = observation_bucket
+        .range(None, None, Order::Ascending)
+        .next()
+        .transpose()?
+        .map(|(k, _)| k);
+
+    match stored_key {
+        Some(k) => {
+            let mut stored_seq = [0u8; 8];
+            stored_seq.copy_from_slice(&k);
+            Ok(first_seq.max(u64::from_be_bytes(stored_seq)))
+        }
+        None => Ok(first_seq),
+    }
+}
+
+/// Removes observations older than the last `history_size` ones
+pub fn prune_price_observations(
+    storage: &mut dyn Storage,
+    asset: &str,
+    history_size: u64,
+) -> StdResult<()> {
+    let first_seq = read_price_observation_count(storage, asset).saturating_sub(history_size);
+    let mut observation_bucket: Bucket<PriceObservation> =
+        Bucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()]);
+    let pruned_keys: Vec<Vec<u8>> = observation_bucket
+        .range(None, Some(&first_seq.to_be_bytes()), Order::Ascending)
+        .take(MAX_PRUNED_OBSERVATIONS)
+        .map(|item| Ok(item?.0))
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    for key in pruned_keys {
+        observation_bucket.remove(&key);
+    }
+
+    Ok(())
+}
+
+/// Returns the sequence of the first observation recorded after
+/// `time`, observation times are strictly increasing so it can
+/// be found with binary search
+pub fn find_price_observation_seq(
+    storage: &dyn Storage,
+    asset: &str,
+    history_size: u64,
+    time: u64,
+) -> StdResult<u64> {
+    let mut start = read_first_price_observation_seq(storage, asset, history_size)?;
+    let mut end = read_price_observation_count(storage, asset);
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if read_price_observation(storage, asset, mid)?.time <= time {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+
+    Ok(start)
+}
+
+pub fn read_price_history(
+    storage: &dyn Storage,
+    asset: &str,
+    history_size: u64,
+    start_after_time: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PriceObservation>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as u64;
+    let start = match start_after_time {
+        Some(time) => find_price_observation_seq(storage, asset, history_size, time)?,
+        None => read_first_price_observation_seq(storage, asset, history_size)?,
+    };
+    let end = read_price_observation_count(storage, asset).min(start + limit);
+
+    (start..end)
+        .map(|seq| read_price_observation(storage, asset, seq))
+        .collect()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<String>) -> Option<Vec<u8>> {
     start_after.map(|idx| {
@@ -105,3 +403,50 @@ fn calc_range_start(start_after: Option<String>) -> Option<Vec<u8>> {
         v
     })
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub base_asset: String,
+}
+
+/// Legacy assets had a single feeder and a single price. They keep
+/// that behaviour, a quorum of one without a price window, until
+/// the owner updates the config
+pub fn migrate_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = singleton_read(storage, KEY_CONFIG).load()?;
+    store_config(
+        storage,
+        &Config {
+            owner: legacy_config.owner,
+            base_asset: legacy_config.base_asset,
+            price_window: u64::MAX,
+            quorum: 1,
+            price_history_size: DEFAULT_PRICE_HISTORY_SIZE,
+        },
+    )?;
+
+    let legacy_feeders: Vec<(Vec<u8>, CanonicalAddr)> = ReadonlyBucket::new(storage, PREFIX_FEEDER)
+        .range(None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, CanonicalAddr)>>>()?;
+    for (asset, feeder) in legacy_feeders {
+        let asset = std::str::from_utf8(&asset).unwrap().to_string();
+        let legacy_price: Option<PriceInfo> =
+            ReadonlyBucket::new(storage, PREFIX_PRICE).may_load(asset.as_bytes())?;
+
+        if let Some(legacy_price) = legacy_price {
+            store_feeder_prices(
+                storage,
+                &asset,
+                &[FeederPrice {
+                    feeder: feeder.clone(),
+                    price: legacy_price.price,
+                    last_updated_time: legacy_price.last_updated_time,
+                }],
+            )?;
+        }
+        store_feeders(storage, &asset, &[feeder])?;
+    }
+
+    Ok(())
+}
@@ -1,11 +1,15 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::state::{LegacyConfig, PriceInfo};
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, Api, Deps, DepsMut, Env, StdError};
+use cosmwasm_storage::{bucket, singleton};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    CircuitBreakerResponse, ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, MigrateMsg,
+    PriceHistoryResponse, PriceHistoryResponseElem, PriceResponse, PriceSource,
+    PriceSourcesResponse, PricesResponse, PricesResponseElem, QueryMsg,
 };
 use std::str::FromStr;
 
@@ -16,6 +20,9 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
         base_asset: "base0000".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -38,29 +45,87 @@ fn update_config() {
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
         base_asset: "base0000".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
     };
 
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
+        price_window: Some(120u64),
+        quorum: Some(2u32),
+        price_history_size: Some(100u64),
     };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    // transfer the ownership
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: 100u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner0000", value.owner.as_str());
+
+    let info = mock_info("owner0001", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let value: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0001", value.owner.as_str());
     assert_eq!("base0000", &value.base_asset);
+    assert_eq!(120u64, value.price_window);
+    assert_eq!(2u32, value.quorum);
+    assert_eq!(100u64, value.price_history_size);
+
+    // quorum cannot be zero
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        price_window: None,
+        quorum: Some(0u32),
+        price_history_size: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidQuorum {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // history size cannot be zero
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        price_window: None,
+        quorum: None,
+        price_history_size: Some(0u64),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidPriceHistorySize {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig { owner: None };
+    let msg = ExecuteMsg::UpdateConfig {
+        price_window: None,
+        quorum: None,
+        price_history_size: None,
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -76,6 +141,9 @@ fn register_feeder() {
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
         base_asset: "base0000".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -110,9 +178,72 @@ fn register_feeder() {
         feeder_res,
         FeederResponse {
             asset: "mAAPL".to_string(),
-            feeder: "feeder0000".to_string(),
+            feeders: vec!["feeder0000".to_string()],
         }
     );
+
+    // registering twice keeps a single entry
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0001".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let feeder_res: FeederResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeder {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        feeder_res.feeders,
+        vec!["feeder0000".to_string(), "feeder0001".to_string()]
+    );
+
+    let msg = ExecuteMsg::DeregisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let feeder_res: FeederResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeder {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(feeder_res.feeders, vec!["feeder0001".to_string()]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::FeederNotRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -122,6 +253,9 @@ fn feed_price() {
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
         base_asset: "base0000".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -170,6 +304,7 @@ fn feed_price() {
             rate: Decimal256::from_str("1.2").unwrap(),
             last_updated_base: env.block.time.seconds(),
             last_updated_quote: 9999999999,
+            frozen: false,
         }
     );
 
@@ -190,6 +325,7 @@ fn feed_price() {
             rate: Decimal256::from_str("1.833333333333333333").unwrap(),
             last_updated_base: env.block.time.seconds(),
             last_updated_quote: env.block.time.seconds(),
+            frozen: false,
         }
     );
 
@@ -234,3 +370,937 @@ fn feed_price() {
         _ => panic!("Must return unauthorized error"),
     }
 }
+
+#[test]
+fn median_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+        price_window: 60u64,
+        quorum: 3u32,
+        price_history_size: 1000u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for feeder in ["feeder0000", "feeder0001", "feeder0002", "feeder0003"] {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: feeder.to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let feed = |deps: DepsMut, env: Env, feeder: &str, price: &str| {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(deps, env, mock_info(feeder, &[]), msg).unwrap();
+    };
+    let query_maapl = |deps: Deps, env: Env| {
+        query(
+            deps,
+            env,
+            QueryMsg::Price {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+            },
+        )
+    };
+
+    // a feeder outside the set cannot feed
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("1.0").unwrap())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("feeder0004", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env();
+    let feed_time = env.block.time.seconds();
+    feed(deps.as_mut(), env.clone(), "feeder0000", "1.0");
+    feed(deps.as_mut(), env.clone(), "feeder0001", "1.2");
+
+    // 2 of 3 submissions
+    match query_maapl(deps.as_ref(), env.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price quorum not met: 2 of 3 fresh submissions")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // an outlier does not move the median
+    env.block.time = env.block.time.plus_seconds(30);
+    feed(deps.as_mut(), env.clone(), "feeder0002", "100.0");
+    let value: PriceResponse =
+        from_binary(&query_maapl(deps.as_ref(), env.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.2").unwrap(),
+            last_updated_base: feed_time,
+            last_updated_quote: 9999999999,
+            frozen: false,
+        }
+    );
+
+    // even number of submissions averages the middle two
+    feed(deps.as_mut(), env.clone(), "feeder0003", "1.3");
+    let value: PriceResponse =
+        from_binary(&query_maapl(deps.as_ref(), env.clone()).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.25").unwrap());
+
+    // a new submission replaces the previous one of the feeder
+    feed(deps.as_mut(), env.clone(), "feeder0003", "1.1");
+    let value: PriceResponse =
+        from_binary(&query_maapl(deps.as_ref(), env.clone()).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.15").unwrap());
+
+    // the first two submissions leave the price window
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = query_maapl(deps.as_ref(), env.clone());
+    assert!(res.is_err());
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Prices {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: PricesResponse = from_binary(&res).unwrap();
+    assert_eq!(value, PricesResponse { prices: vec![] });
+
+    feed(deps.as_mut(), env.clone(), "feeder0000", "1.0");
+    let value: PriceResponse =
+        from_binary(&query_maapl(deps.as_ref(), env.clone()).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.1").unwrap(),
+            last_updated_base: feed_time + 30,
+            last_updated_quote: 9999999999,
+            frozen: false,
+        }
+    );
+}
+
+#[test]
+fn circuit_breaker() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+        price_window: 600u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for feeder in ["feeder0000", "feeder0001"] {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: feeder.to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateCircuitBreaker {
+        asset: "mAAPL".to_string(),
+        max_update_deviation: Decimal256::percent(10),
+        max_window_deviation: Decimal256::percent(15),
+        deviation_window: 100u64,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let feed = |deps: DepsMut, env: Env, feeder: &str, price: &str| {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(deps, env, mock_info(feeder, &[]), msg)
+    };
+    let query_maapl = |deps: Deps, env: Env| -> PriceResponse {
+        let res = query(
+            deps,
+            env,
+            QueryMsg::Price {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    let mut env = mock_env();
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "0");
+    match res {
+        Err(ContractError::InvalidPrice {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the first price has no reference
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "1.0").unwrap();
+    assert!(res.attributes.contains(&attr("status", "accepted")));
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "1.05").unwrap();
+    assert!(res.attributes.contains(&attr("status", "accepted")));
+
+    // 100% jump waits for a second feeder
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "2.0").unwrap();
+    assert!(res.attributes.contains(&attr("status", "pending")));
+    let value = query_maapl(deps.as_ref(), env.clone());
+    assert_eq!(value.rate, Decimal256::from_str("1.05").unwrap());
+    assert!(value.frozen);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CircuitBreaker {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: CircuitBreakerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        CircuitBreakerResponse {
+            asset: "mAAPL".to_string(),
+            max_update_deviation: Decimal256::percent(10),
+            max_window_deviation: Decimal256::percent(15),
+            deviation_window: 100u64,
+            pending_price: Some(Decimal256::from_str("2.0").unwrap()),
+            frozen: true,
+        }
+    );
+
+    // the same feeder cannot confirm its own price
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "2.05").unwrap();
+    assert!(res.attributes.contains(&attr("status", "pending")));
+
+    let res = feed(deps.as_mut(), env.clone(), "feeder0001", "2.0").unwrap();
+    assert!(res.attributes.contains(&attr("status", "confirmed")));
+    let value = query_maapl(deps.as_ref(), env.clone());
+    assert_eq!(value.rate, Decimal256::from_str("2.025").unwrap());
+    assert!(!value.frozen);
+
+    // small updates add up to a 21% move within the window
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "2.2").unwrap();
+    assert!(res.attributes.contains(&attr("status", "accepted")));
+    let res = feed(deps.as_mut(), env.clone(), "feeder0001", "2.3").unwrap();
+    assert!(res.attributes.contains(&attr("status", "accepted")));
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "2.45").unwrap();
+    assert!(res.attributes.contains(&attr("status", "pending")));
+    assert!(query_maapl(deps.as_ref(), env.clone()).frozen);
+
+    // pending prices expire with the window, which starts again
+    // from the current price
+    env.block.time = env.block.time.plus_seconds(101);
+    assert!(!query_maapl(deps.as_ref(), env.clone()).frozen);
+    let res = feed(deps.as_mut(), env.clone(), "feeder0000", "2.45").unwrap();
+    assert!(res.attributes.contains(&attr("status", "accepted")));
+    let value = query_maapl(deps.as_ref(), env);
+    assert_eq!(value.rate, Decimal256::from_str("2.375").unwrap());
+}
+
+#[test]
+fn twap_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+        price_window: 3600u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let feed = |deps: DepsMut, env: Env, price: &str| {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(deps, env, mock_info("feeder0000", &[]), msg).unwrap();
+    };
+    let query_twap = |deps: Deps, env: Env, window_seconds: u64| {
+        query(
+            deps,
+            env,
+            QueryMsg::TwapPrice {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+                window_seconds,
+            },
+        )
+    };
+
+    // the last update of a block is the observation of the block
+    let mut env = mock_env();
+    let feed_time = env.block.time.seconds();
+    feed(deps.as_mut(), env.clone(), "1.0");
+    feed(deps.as_mut(), env.clone(), "2.0");
+
+    env.block.time = env.block.time.plus_seconds(100);
+    feed(deps.as_mut(), env.clone(), "4.0");
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let value: PriceResponse =
+        from_binary(&query_twap(deps.as_ref(), env.clone(), 100u64).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("4.0").unwrap(),
+            last_updated_base: feed_time + 100,
+            last_updated_quote: 9999999999,
+            frozen: false,
+        }
+    );
+
+    let value: PriceResponse =
+        from_binary(&query_twap(deps.as_ref(), env.clone(), 200u64).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("3.0").unwrap());
+
+    let value: PriceResponse =
+        from_binary(&query_twap(deps.as_ref(), env.clone(), 160u64).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("3.25").unwrap());
+
+    // the inverse pair divides by the TWAP
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::TwapPrice {
+            base: "base0000".to_string(),
+            quote: "mAAPL".to_string(),
+            window_seconds: 200u64,
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.rate,
+        Decimal256::one() / Decimal256::from_str("3.0").unwrap()
+    );
+
+    match query_twap(deps.as_ref(), env.clone(), 201u64) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Not enough price history for the TWAP window")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match query_twap(deps.as_ref(), env, 0u64) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "TWAP window must be positive"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn price_history() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+        price_window: 3600u64,
+        quorum: 1u32,
+        price_history_size: 3u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let query_history = |deps: Deps, start_after_time: Option<u64>, limit: Option<u32>| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::PriceHistory {
+                asset: "mAAPL".to_string(),
+                start_after_time,
+                limit,
+            },
+        )
+        .unwrap();
+        let value: PriceHistoryResponse = from_binary(&res).unwrap();
+        value.history
+    };
+    let update_history_size = |deps: DepsMut, price_history_size: u64| {
+        let msg = ExecuteMsg::UpdateConfig {
+            price_window: None,
+            quorum: None,
+            price_history_size: Some(price_history_size),
+        };
+        execute(deps, mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    };
+    let elem = |price: &str, last_updated_time: u64| PriceHistoryResponseElem {
+        price: Decimal256::from_str(price).unwrap(),
+        last_updated_time,
+    };
+
+    assert_eq!(query_history(deps.as_ref(), None, None), vec![]);
+
+    let mut env = mock_env();
+    let feed_time = env.block.time.seconds();
+    for price in ["1.0", "2.0", "3.0", "4.0"] {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("feeder0000", &[]),
+            msg,
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(10);
+    }
+
+    // only the last 3 prices are kept
+    assert_eq!(
+        query_history(deps.as_ref(), None, None),
+        vec![
+            elem("2.0", feed_time + 10),
+            elem("3.0", feed_time + 20),
+            elem("4.0", feed_time + 30),
+        ]
+    );
+    assert_eq!(
+        query_history(deps.as_ref(), Some(feed_time + 10), None),
+        vec![elem("3.0", feed_time + 20), elem("4.0", feed_time + 30)]
+    );
+    assert_eq!(
+        query_history(deps.as_ref(), Some(feed_time + 15), Some(1u32)),
+        vec![elem("3.0", feed_time + 20)]
+    );
+
+    // a smaller history applies right away
+    update_history_size(deps.as_mut(), 2u64);
+    assert_eq!(
+        query_history(deps.as_ref(), None, None),
+        vec![elem("3.0", feed_time + 20), elem("4.0", feed_time + 30)]
+    );
+
+    // pruned prices are not restored by a larger history
+    update_history_size(deps.as_mut(), 10u64);
+    assert_eq!(
+        query_history(deps.as_ref(), None, None),
+        vec![
+            elem("2.0", feed_time + 10),
+            elem("3.0", feed_time + 20),
+            elem("4.0", feed_time + 30),
+        ]
+    );
+}
+
+#[test]
+fn price_sources() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "bluna".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let env = mock_env();
+    let feed_time = env.block.time.seconds();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("bluna".to_string(), Decimal256::from_str("10.0").unwrap())],
+    };
+    execute(deps.as_mut(), env, mock_info("feeder0000", &[]), msg).unwrap();
+
+    deps.querier
+        .with_pool("pair0000", [("bluna", 1000000u128), ("uusd", 12000000u128)]);
+    deps.querier.with_pool(
+        "pair0001",
+        [("bluna", 1000000u128), ("uluna", 12000000u128)],
+    );
+    deps.querier.with_oracle_price(
+        "oracle0001",
+        Decimal256::from_str("11.0").unwrap(),
+        feed_time,
+    );
+
+    let query_sources = |deps: Deps| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::PriceSources {
+                asset: "bluna".to_string(),
+            },
+        )
+        .unwrap();
+        let value: PriceSourcesResponse = from_binary(&res).unwrap();
+        value.sources
+    };
+    let query_bluna = |deps: Deps, seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        query(
+            deps,
+            env,
+            QueryMsg::Price {
+                base: "bluna".to_string(),
+                quote: "uusd".to_string(),
+            },
+        )
+        .map(|res| from_binary::<PriceResponse>(&res).unwrap())
+    };
+    let observe_bluna = |deps: DepsMut, seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        let msg = ExecuteMsg::ObservePairPrices {
+            asset: "bluna".to_string(),
+        };
+        execute(deps, env, mock_info("feeder0000", &[]), msg)
+    };
+
+    // feeders price assets without sources
+    assert_eq!(query_sources(deps.as_ref()), vec![PriceSource::Feeder {}]);
+    match observe_bluna(deps.as_mut(), 0u64) {
+        Err(ContractError::NoPairPriceSource {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdatePriceSources {
+        asset: "bluna".to_string(),
+        sources: vec![PriceSource::TerraswapPair {
+            pair: "pair0000".to_string(),
+            window: 2u64,
+        }],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::InvalidPairWindow(3u64)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let sources = vec![
+        PriceSource::Feeder {},
+        PriceSource::TerraswapPair {
+            pair: "pair0000".to_string(),
+            window: 30u64,
+        },
+    ];
+    let msg = ExecuteMsg::UpdatePriceSources {
+        asset: "bluna".to_string(),
+        sources: sources.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(query_sources(deps.as_ref()), sources);
+
+    // the pair is used once the feeder price is stale,
+    // after a full window of observations
+    let value = query_bluna(deps.as_ref(), 0u64).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("10.0").unwrap());
+    assert_eq!(value.last_updated_base, feed_time);
+
+    // only the feeders of the asset observe its pairs
+    let msg = ExecuteMsg::ObservePairPrices {
+        asset: "bluna".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = observe_bluna(deps.as_mut(), 0u64).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "observe_pair_prices"),
+            attr("asset", "bluna")
+        ]
+    );
+    deps.querier
+        .with_pool("pair0000", [("bluna", 1000000u128), ("uusd", 18000000u128)]);
+    let _res = observe_bluna(deps.as_mut(), 20u64).unwrap();
+    match query_bluna(deps.as_ref(), 61u64) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Pair has no completed price window")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a price moved after the first observation of a block is not recorded
+    deps.querier.with_pool(
+        "pair0000",
+        [("bluna", 1000000u128), ("uusd", 100000000u128)],
+    );
+    let _res = observe_bluna(deps.as_mut(), 20u64).unwrap();
+    deps.querier
+        .with_pool("pair0000", [("bluna", 1000000u128), ("uusd", 18000000u128)]);
+    let _res = observe_bluna(deps.as_mut(), 25u64).unwrap();
+    let _res = observe_bluna(deps.as_mut(), 35u64).unwrap();
+
+    // each sample is weighted for at most a third of the window,
+    // (12 * 10 + 18 * 5 + 18 * 10) / 25
+    let value = query_bluna(deps.as_ref(), 61u64).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("15.6").unwrap());
+    assert_eq!(value.last_updated_base, feed_time + 35);
+
+    // the TWAP of a pair source is the average of its last window
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61);
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::TwapPrice {
+            base: "bluna".to_string(),
+            quote: "uusd".to_string(),
+            window_seconds: 3600u64,
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("15.6").unwrap());
+
+    // a window with too few samples does not refresh the price
+    let _res = observe_bluna(deps.as_mut(), 70u64).unwrap();
+    match query_bluna(deps.as_ref(), 96u64) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price of the pair is stale"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the pair has to trade against the base asset
+    let msg = ExecuteMsg::UpdatePriceSources {
+        asset: "bluna".to_string(),
+        sources: vec![
+            PriceSource::TerraswapPair {
+                pair: "pair0001".to_string(),
+                window: 30u64,
+            },
+            PriceSource::Oracle {
+                contract: "oracle0001".to_string(),
+                base: "bluna".to_string(),
+                quote: "uusd".to_string(),
+            },
+        ],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let value = query_bluna(deps.as_ref(), 30u64).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("11.0").unwrap());
+    assert_eq!(value.last_updated_base, feed_time);
+
+    match query_bluna(deps.as_ref(), 61u64) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price of the source oracle is stale")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdatePriceSources {
+        asset: "bluna".to_string(),
+        sources: vec![PriceSource::TerraswapPair {
+            pair: "pair0001".to_string(),
+            window: 30u64,
+        }],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    match observe_bluna(deps.as_mut(), 0u64) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Pair does not trade bluna against uusd")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // removing the sources returns the asset to its feeders
+    let msg = ExecuteMsg::UpdatePriceSources {
+        asset: "bluna".to_string(),
+        sources: vec![],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(query_sources(deps.as_ref()), vec![PriceSource::Feeder {}]);
+    let value = query_bluna(deps.as_ref(), 0u64).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("10.0").unwrap());
+}
+
+#[test]
+fn cross_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "uusd".to_string(),
+        price_window: 60u64,
+        quorum: 1u32,
+        price_history_size: 1000u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for asset in ["eth", "beth_eth"] {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let feed = |deps: DepsMut, env: Env, asset: &str, price: &str| {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![(asset.to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(deps, env, mock_info("feeder0000", &[]), msg).unwrap();
+    };
+    let update_sources = |deps: DepsMut, asset: &str, sources: Vec<PriceSource>| {
+        let msg = ExecuteMsg::UpdatePriceSources {
+            asset: asset.to_string(),
+            sources,
+        };
+        execute(deps, mock_env(), mock_info("owner0000", &[]), msg)
+    };
+    let query_beth = |deps: Deps, env: Env| {
+        query(
+            deps,
+            env,
+            QueryMsg::Price {
+                base: "beth".to_string(),
+                quote: "uusd".to_string(),
+            },
+        )
+        .map(|res| from_binary::<PriceResponse>(&res).unwrap())
+    };
+
+    // a cross rate needs other assets
+    for assets in [vec![], vec!["eth".to_string(), "beth".to_string()]] {
+        let res = update_sources(
+            deps.as_mut(),
+            "beth",
+            vec![PriceSource::CrossRate { assets }],
+        );
+        match res {
+            Err(ContractError::InvalidCrossRate {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let beth_sources = vec![PriceSource::CrossRate {
+        assets: vec!["eth".to_string(), "beth_eth".to_string()],
+    }];
+    update_sources(deps.as_mut(), "beth", beth_sources).unwrap();
+
+    let mut env = mock_env();
+    let feed_time = env.block.time.seconds();
+    feed(deps.as_mut(), env.clone(), "eth", "2000.0");
+    env.block.time = env.block.time.plus_seconds(10);
+    feed(deps.as_mut(), env.clone(), "beth_eth", "0.98");
+
+    // updated as of the oldest component
+    let value = query_beth(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1960.0").unwrap(),
+            last_updated_base: feed_time,
+            last_updated_quote: 9999999999,
+            frozen: false,
+        }
+    );
+
+    // the inverse rate divides by the cross rate
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Price {
+            base: "eth".to_string(),
+            quote: "beth".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.rate,
+        Decimal256::from_str("2000.0").unwrap() / Decimal256::from_str("1960.0").unwrap()
+    );
+
+    // the TWAP of a cross rate is the product of the component TWAPs
+    let mut twap_env = env.clone();
+    twap_env.block.time = twap_env.block.time.plus_seconds(20);
+    let res = query(
+        deps.as_ref(),
+        twap_env,
+        QueryMsg::TwapPrice {
+            base: "beth".to_string(),
+            quote: "uusd".to_string(),
+            window_seconds: 20u64,
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1960.0").unwrap());
+
+    // a stale component makes the cross rate stale
+    let mut stale_env = env.clone();
+    stale_env.block.time = stale_env.block.time.plus_seconds(55);
+    match query_beth(deps.as_ref(), stale_env) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price quorum not met: 0 of 1 fresh submissions")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the cross rate is frozen with its components
+    let msg = ExecuteMsg::UpdateCircuitBreaker {
+        asset: "eth".to_string(),
+        max_update_deviation: Decimal256::percent(10),
+        max_window_deviation: Decimal256::percent(10),
+        deviation_window: 100u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    feed(deps.as_mut(), env.clone(), "eth", "3000.0");
+    let value = query_beth(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1960.0").unwrap());
+    assert!(value.frozen);
+
+    // cycles between cross rates are cut
+    update_sources(
+        deps.as_mut(),
+        "eth",
+        vec![PriceSource::CrossRate {
+            assets: vec!["beth".to_string()],
+        }],
+    )
+    .unwrap();
+    match query_beth(deps.as_ref(), env) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cross rate nesting is too deep")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn migrate_legacy_feeder() {
+    let mut deps = mock_dependencies(&[]);
+
+    // 0.3.x state with a single feeder and price
+    let legacy_config = LegacyConfig {
+        owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+        base_asset: "base0000".to_string(),
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+    let feeder_raw = deps.api.addr_canonicalize("feeder0000").unwrap();
+    bucket(deps.as_mut().storage, b"feeder")
+        .save(b"mAAPL", &feeder_raw)
+        .unwrap();
+    bucket(deps.as_mut().storage, b"price")
+        .save(
+            b"mAAPL",
+            &PriceInfo {
+                price: Decimal256::from_str("1.2").unwrap(),
+                last_updated_time: 100u64,
+            },
+        )
+        .unwrap();
+
+    let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            base_asset: "base0000".to_string(),
+            price_window: u64::MAX,
+            quorum: 1u32,
+            price_history_size: 1000u64,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Feeder {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: FeederResponse = from_binary(&res).unwrap();
+    assert_eq!(value.feeders, vec!["feeder0000".to_string()]);
+
+    // stale legacy prices are still reported
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Price {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.2").unwrap(),
+            last_updated_base: 100u64,
+            last_updated_quote: 9999999999,
+            frozen: false,
+        }
+    );
+}
@@ -1,11 +1,13 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerPositionResponse, CollateralsResponse,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, IsolatedDebtResponse,
+    LiquidatableBorrowersResponse, LiquidationLimitResponse, MigrateMsg, PendingChangesResponse,
+    QueryMsg, WhitelistResponse,
 };
 use moneymarket_overseer::state::EpochState;
 
@@ -18,10 +20,20 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(AllCollateralsResponse), &out_dir);
     export_schema(&schema_for!(BorrowLimitResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerPositionResponse), &out_dir);
     export_schema(&schema_for!(CollateralsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(PendingChangesResponse),
+        &out_dir,
+        "PendingChangesResponse",
+    );
     export_schema(&schema_for!(EpochState), &out_dir);
+    export_schema(&schema_for!(IsolatedDebtResponse), &out_dir);
+    export_schema(&schema_for!(LiquidatableBorrowersResponse), &out_dir);
+    export_schema(&schema_for!(LiquidationLimitResponse), &out_dir);
     export_schema(&schema_for!(WhitelistResponse), &out_dir);
 }
@@ -1,26 +1,39 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg,
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, SubMsg, WasmMsg,
 };
 
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
+use crate::querier::{query_borrower_loan_value, query_liquidation_amount};
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    read_all_collaterals, read_borrower_isolated_debt, read_collaterals, read_config,
+    read_isolated_collaterals, read_isolated_debt, read_swap_path, read_total_locked,
+    read_whitelist_elem, store_borrower_isolated_debt, store_collaterals, store_isolated_debt,
+    store_total_locked, Config, WhitelistElem,
 };
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{Cw20HookMsg as TerraswapCw20HookMsg, ExecuteMsg as TerraswapExecuteMsg};
 
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
-use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::liquidation::{Cw20HookMsg as LiquidationCw20HookMsg, LiquidationAmountResponse};
+use moneymarket::market::{BorrowerLoanValueResponse, ExecuteMsg as MarketExecuteMsg};
 use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
-use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerPositionResponse,
+    CollateralPositionResponse, CollateralsResponse, ExecuteMsg, IsolatedDebtResponse,
+    LiquidatableBorrowerResponse, LiquidatableBorrowersResponse, LiquidationLimitResponse,
+    SwapPath,
+};
+use moneymarket::querier::{query_balance, query_price_with_mode, TimeConstraints};
+use moneymarket::tokens::{
+    collateral_to_human, collateral_to_raw, native_denom, Tokens, TokensHuman, TokensMath,
+    TokensToHuman, TokensToRaw,
+};
 
 pub fn lock_collateral(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
@@ -30,6 +43,26 @@ pub fn lock_collateral(
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
 
     cur_collaterals.add(collaterals.clone());
+
+    // An existing loan is charged in full against the
+    // debt ceiling of a newly locked isolated collateral
+    for collateral in collaterals.iter() {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if whitelist_elem.debt_ceiling.is_some() {
+            let config: Config = read_config(deps.storage)?;
+            charge_isolated_debt(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                &cur_collaterals,
+                true,
+            )?;
+            break;
+        }
+    }
+
+    increase_total_locked(deps.storage, &cur_collaterals, &collaterals)?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
@@ -80,23 +113,32 @@ pub fn unlock_collateral(
         return Err(ContractError::UnlockExceedsLocked {});
     }
 
-    // Compute borrow limit with collaterals except unlock target collaterals
+    // Compute borrow limit with collaterals except unlock target collaterals,
+    // the liquidation threshold buffer is kept by checking against max LTV
     let (borrow_limit, _) = compute_borrow_limit(
         deps.as_ref(),
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    if borrow_limit < borrow_amount_res.loan_amount {
+    let borrow_amount_res: BorrowerLoanValueResponse = query_borrower_loan_value(
+        deps.as_ref(),
+        market,
+        borrower.clone(),
+        env.block.height,
+        env.block.time.seconds(),
+    )?;
+    if borrow_limit < borrow_amount_res.loan_value {
         return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
     }
 
+    decrease_total_locked(deps.storage, &collaterals)?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
+    let mut unlocks_isolated = false;
     let mut messages: Vec<SubMsg> = vec![];
     for collateral in collaterals.clone() {
         let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        unlocks_isolated |= whitelist_elem.debt_ceiling.is_some();
         messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -110,6 +152,11 @@ pub fn unlock_collateral(
         })));
     }
 
+    // Release the loan from the ceilings of fully unlocked collaterals
+    if unlocks_isolated {
+        charge_isolated_debt(deps, &env, &config, &borrower, &cur_collaterals, false)?;
+    }
+
     // Logging stuff, so can be removed
     let collateral_logs: Vec<String> = collaterals_human
         .iter()
@@ -143,16 +190,27 @@ pub fn liquidate_collateral(
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    let borrow_amount = borrow_amount_res.loan_amount;
+    let borrow_amount_res: BorrowerLoanValueResponse = query_borrower_loan_value(
+        deps.as_ref(),
+        market,
+        borrower.clone(),
+        env.block.height,
+        env.block.time.seconds(),
+    )?;
+    let borrow_amount = borrow_amount_res.loan_value;
 
-    // borrow limit is equal or bigger than loan amount
+    // liquidation limit is equal or bigger than loan amount
     // cannot liquidation collaterals
-    if borrow_limit >= borrow_amount {
+    let (liquidation_limit, _) = compute_liquidation_limit(
+        deps.as_ref(),
+        &cur_collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    if liquidation_limit >= borrow_amount {
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
+    // Liquidate down to the borrow limit, not to the liquidation limit
     let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
         deps.as_ref(),
         deps.api.addr_humanize(&config.liquidation_contract)?,
@@ -166,6 +224,7 @@ pub fn liquidate_collateral(
 
     // Store left collaterals
     cur_collaterals.sub(liquidation_amount.clone())?;
+    decrease_total_locked(deps.storage, &liquidation_amount)?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
     let market_contract = deps.api.addr_humanize(&config.market_contract)?;
@@ -205,6 +264,210 @@ pub fn liquidate_collateral(
         })))
 }
 
+pub fn repay_with_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+    // Underflow check is done in sub_collateral
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    if cur_collaterals
+        .sub(vec![(collateral_token_raw.clone(), amount)])
+        .is_err()
+    {
+        return Err(ContractError::UnlockExceedsLocked {});
+    }
+
+    decrease_total_locked(deps.storage, &vec![(collateral_token_raw.clone(), amount)])?;
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+
+    let prev_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        market_contract.clone(),
+        config.stable_denom.to_string(),
+    )?;
+
+    // The swap proceeds are sent to the market, which repays
+    // the loan and refunds the excess to the borrower
+    let (swap_contract, swap_msg) = match read_swap_path(deps.storage, &collateral_token_raw) {
+        SwapPath::LiquidationQueue {} => (
+            deps.api
+                .addr_humanize(&config.liquidation_contract)?
+                .to_string(),
+            to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                liquidator: borrower.to_string(),
+                fee_address: Some(env.contract.address.to_string()),
+                repay_address: Some(market_contract.to_string()),
+            })?,
+        ),
+        SwapPath::Terraswap {
+            pair_contract,
+            max_spread,
+        } => match native_denom(&collateral_token_raw) {
+            // native collateral is sent along with the swap
+            Some(denom) => (
+                pair_contract,
+                to_binary(&TerraswapExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken { denom },
+                        amount: amount.into(),
+                    },
+                    belief_price: None,
+                    max_spread,
+                    to: Some(market_contract.to_string()),
+                })?,
+            ),
+            None => (
+                pair_contract,
+                to_binary(&TerraswapCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread,
+                    to: Some(market_contract.to_string()),
+                })?,
+            ),
+        },
+    };
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps
+                    .api
+                    .addr_humanize(&whitelist_elem.custody_contract)?
+                    .to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SwapCollateral {
+                    borrower: borrower.to_string(),
+                    amount,
+                    swap_contract,
+                    swap_msg,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: market_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: borrower.to_string(),
+                    prev_balance,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertBorrowerHealth {
+                    borrower: borrower.to_string(),
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "repay_with_collateral"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("amount", amount),
+        ]))
+}
+
+/// Adds newly locked collaterals to the locked totals and checks
+/// them and the borrower collaterals against the whitelist caps
+#[allow(clippy::ptr_arg)]
+pub(crate) fn increase_total_locked(
+    storage: &mut dyn Storage,
+    borrower_collaterals: &Tokens,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    for (collateral_token, amount) in collaterals.iter() {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(storage, collateral_token)?;
+
+        let total_locked = read_total_locked(storage, collateral_token) + *amount;
+        if let Some(max_total_locked) = whitelist_elem.max_total_locked {
+            if total_locked > max_total_locked {
+                return Err(ContractError::TotalLockedCapExceeded(
+                    max_total_locked.into(),
+                ));
+            }
+        }
+
+        if let Some(max_borrower_locked) = whitelist_elem.max_borrower_locked {
+            let borrower_locked = borrower_collaterals
+                .iter()
+                .find(|c| c.0 == *collateral_token)
+                .map(|c| c.1)
+                .unwrap_or_else(Uint256::zero);
+            if borrower_locked > max_borrower_locked {
+                return Err(ContractError::BorrowerLockedCapExceeded(
+                    max_borrower_locked.into(),
+                ));
+            }
+        }
+
+        store_total_locked(storage, collateral_token, &total_locked)?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::ptr_arg)]
+pub(crate) fn decrease_total_locked(
+    storage: &mut dyn Storage,
+    collaterals: &Tokens,
+) -> StdResult<()> {
+    for (collateral_token, amount) in collaterals.iter() {
+        // a drifted total must not block unlocks and liquidations
+        let total_locked = read_total_locked(storage, collateral_token);
+        let released = std::cmp::min(total_locked, *amount);
+        store_total_locked(storage, collateral_token, &(total_locked - released))?;
+    }
+
+    Ok(())
+}
+
+pub fn assert_borrower_health(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let collaterals: Tokens = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    let (borrow_limit, _) =
+        compute_borrow_limit(deps.as_ref(), &collaterals, Some(env.block.time.seconds()))?;
+    let borrow_amount_res: BorrowerLoanValueResponse = query_borrower_loan_value(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower.clone(),
+        env.block.height,
+        env.block.time.seconds(),
+    )?;
+    if borrow_limit < borrow_amount_res.loan_value {
+        return Err(ContractError::RepayWithCollateralTooLarge(
+            borrow_limit.into(),
+        ));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "assert_borrower_health"),
+        attr("borrower", borrower),
+    ]))
+}
+
 pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsResponse> {
     let collaterals: Tokens = read_collaterals(
         deps.storage,
@@ -215,7 +478,7 @@ pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsRes
         borrower: borrower.to_string(),
         collaterals: collaterals
             .iter()
-            .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
+            .map(|c| Ok((collateral_to_human(deps.api, &c.0)?, c.1)))
             .collect::<StdResult<Vec<(String, Uint256)>>>()?,
     })
 }
@@ -241,37 +504,96 @@ pub(crate) fn compute_borrow_limit(
     deps: Deps,
     collaterals: &Tokens,
     block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_collateral_limit(deps, collaterals, block_time, |elem| elem.max_ltv, true)
+}
+
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_liquidation_limit(
+    deps: Deps,
+    collaterals: &Tokens,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_collateral_limit(
+        deps,
+        collaterals,
+        block_time,
+        |elem| elem.liquidation_threshold,
+        true,
+    )
+}
+
+#[allow(clippy::ptr_arg)]
+fn compute_collateral_limit(
+    deps: Deps,
+    collaterals: &Tokens,
+    block_time: Option<u64>,
+    ltv: fn(&WhitelistElem) -> Decimal256,
+    allow_frozen: bool,
 ) -> StdResult<(Uint256, Vec<Decimal256>)> {
     let config: Config = read_config(deps.storage)?;
-    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
 
     let mut borrow_limit: Uint256 = Uint256::zero();
     let mut collateral_prices: Vec<Decimal256> = vec![];
     for collateral in collaterals.iter() {
-        let collateral_token = collateral.0.clone();
         let collateral_amount = collateral.1;
+        let price = query_collateral_price(deps, &config, &collateral.0, block_time)?;
 
-        let price: PriceResponse = query_price(
-            deps,
-            oracle_contract.clone(),
-            (deps.api.addr_humanize(&collateral_token)?).to_string(),
-            config.stable_denom.to_string(),
-            block_time.map(|block_time| TimeConstraints {
-                block_time,
-                valid_timeframe: config.price_timeframe,
-            }),
-        )?;
+        collateral_prices.push(price.rate);
+
+        // Nothing can be borrowed against a price waiting for
+        // confirmation in the oracle, the frozen price is still
+        // the last confirmed one so other operations can use it
+        if price.frozen && !allow_frozen {
+            continue;
+        }
 
         let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
         let collateral_value = collateral_amount * price.rate;
-        borrow_limit += collateral_value * elem.max_ltv;
-        collateral_prices.push(price.rate);
+        borrow_limit += collateral_value * ltv(&elem);
     }
 
     // returns borrow_limit with collaterals value in stable denom
     Ok((borrow_limit, collateral_prices))
 }
 
+fn query_collateral_price(
+    deps: Deps,
+    config: &Config,
+    collateral_token: &CanonicalAddr,
+    block_time: Option<u64>,
+) -> StdResult<PriceResponse> {
+    query_price_with_mode(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_to_human(deps.api, collateral_token)?,
+        config.stable_denom.to_string(),
+        &config.price_mode,
+        block_time.map(|block_time| TimeConstraints {
+            block_time,
+            valid_timeframe: config.price_timeframe,
+        }),
+    )
+}
+
+pub fn query_liquidation_limit(
+    deps: Deps,
+    borrower: Addr,
+    block_time: Option<u64>,
+) -> StdResult<LiquidationLimitResponse> {
+    let collaterals = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    let (liquidation_limit, _) = compute_liquidation_limit(deps, &collaterals, block_time)?;
+
+    Ok(LiquidationLimitResponse {
+        borrower: borrower.to_string(),
+        liquidation_limit,
+    })
+}
+
 pub fn query_borrow_limit(
     deps: Deps,
     borrower: Addr,
@@ -282,11 +604,305 @@ pub fn query_borrow_limit(
         &deps.api.addr_canonicalize(borrower.as_str())?,
     );
 
-    // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    // Compute borrow limit of new loans with collaterals, the debt
+    // ceilings of isolated collaterals are checked on the borrow itself
+    let (borrow_limit, _) =
+        compute_collateral_limit(deps, &collaterals, block_time, |elem| elem.max_ltv, false)?;
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
         borrow_limit,
     })
 }
+
+/// Charge the live loan value of a borrower against the debt ceilings
+/// of the isolated collaterals it holds and release the collaterals it
+/// no longer holds. Interest is counted as of the last charge, so the
+/// ceiling is only enforced on the operations growing the loan
+fn charge_isolated_debt(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    borrower: &Addr,
+    collaterals: &Tokens,
+    enforce_ceiling: bool,
+) -> Result<Uint256, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    let mut loan_value: Option<Uint256> = None;
+    for collateral_token in read_isolated_collaterals(deps.storage)? {
+        let borrower_debt =
+            read_borrower_isolated_debt(deps.storage, &collateral_token, &borrower_raw);
+        let holds = collaterals
+            .iter()
+            .any(|c| c.0 == collateral_token && !c.1.is_zero());
+        if !holds && borrower_debt.is_zero() {
+            continue;
+        }
+
+        let new_borrower_debt = if holds {
+            match loan_value {
+                Some(v) => v,
+                None => {
+                    let v = query_borrower_loan_value(
+                        deps.as_ref(),
+                        deps.api.addr_humanize(&config.market_contract)?,
+                        borrower.clone(),
+                        env.block.height,
+                        env.block.time.seconds(),
+                    )?
+                    .loan_value;
+                    loan_value = Some(v);
+                    v
+                }
+            }
+        } else {
+            Uint256::zero()
+        };
+
+        let isolated_debt = read_isolated_debt(deps.storage, &collateral_token);
+        let isolated_debt =
+            isolated_debt - std::cmp::min(isolated_debt, borrower_debt) + new_borrower_debt;
+        if enforce_ceiling {
+            let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token)?;
+            if let Some(debt_ceiling) = elem.debt_ceiling {
+                if isolated_debt > debt_ceiling {
+                    return Err(ContractError::DebtCeilingReached(debt_ceiling.into()));
+                }
+            }
+        }
+
+        store_isolated_debt(deps.storage, &collateral_token, &isolated_debt)?;
+        store_borrower_isolated_debt(
+            deps.storage,
+            &collateral_token,
+            &borrower_raw,
+            &new_borrower_debt,
+        )?;
+    }
+
+    Ok(loan_value.unwrap_or_else(Uint256::zero))
+}
+
+/// Market operation after a borrow, the whole loan of the
+/// borrower is charged and must fit within the ceilings
+pub fn increase_isolated_debt(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collaterals = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+    let loan_value = charge_isolated_debt(deps, &env, &config, &borrower, &collaterals, true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "increase_isolated_debt"),
+        attr("borrower", borrower),
+        attr("loan_value", loan_value),
+    ]))
+}
+
+/// Market operation after a repayment, the remaining loan is
+/// charged including its interest and is never rejected
+pub fn decrease_isolated_debt(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collaterals = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+    let loan_value = charge_isolated_debt(deps, &env, &config, &borrower, &collaterals, false)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "decrease_isolated_debt"),
+        attr("borrower", borrower),
+        attr("loan_value", loan_value),
+    ]))
+}
+
+pub fn query_isolated_debt(deps: Deps, borrower: Addr) -> StdResult<IsolatedDebtResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    let mut isolated_collaterals: Vec<String> = vec![];
+    for (collateral_token, _) in read_collaterals(deps.storage, &borrower_raw) {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token)?;
+        if elem.debt_ceiling.is_some() {
+            isolated_collaterals.push(collateral_to_human(deps.api, &collateral_token)?);
+        }
+    }
+
+    let mut debt = Uint256::zero();
+    for collateral_token in read_isolated_collaterals(deps.storage)? {
+        let borrower_debt =
+            read_borrower_isolated_debt(deps.storage, &collateral_token, &borrower_raw);
+        if borrower_debt > debt {
+            debt = borrower_debt;
+        }
+    }
+
+    Ok(IsolatedDebtResponse {
+        borrower: borrower.to_string(),
+        isolated_collaterals,
+        debt,
+    })
+}
+
+pub fn query_borrower_position(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerPositionResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let collaterals = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    let mut positions: Vec<CollateralPositionResponse> = vec![];
+    let mut liquidation_values: Vec<Uint256> = vec![];
+    let mut borrow_limit = Uint256::zero();
+    let mut liquidation_limit = Uint256::zero();
+    for collateral in collaterals.iter() {
+        let price =
+            query_collateral_price(deps, &config, &collateral.0, Some(env.block.time.seconds()))?;
+
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let value = collateral.1 * price.rate;
+        let collateral_borrow_limit = value * elem.max_ltv;
+        let liquidation_value = value * elem.liquidation_threshold;
+        borrow_limit += collateral_borrow_limit;
+        liquidation_limit += liquidation_value;
+        liquidation_values.push(liquidation_value);
+
+        positions.push(CollateralPositionResponse {
+            collateral_token: collateral_to_human(deps.api, &collateral.0)?,
+            amount: collateral.1,
+            price: price.rate,
+            frozen: price.frozen,
+            value,
+            max_ltv: elem.max_ltv,
+            borrow_limit: collateral_borrow_limit,
+            liquidation_threshold: elem.liquidation_threshold,
+            liquidation_price_drop: Decimal256::zero(),
+        });
+    }
+
+    let loan_value = query_borrower_loan_value(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower.clone(),
+        env.block.height,
+        env.block.time.seconds(),
+    )?
+    .loan_value;
+
+    // A price drop of d on one collateral lowers the liquidation
+    // limit by d * liquidation_value, so the position stays safe
+    // while d <= (liquidation_limit - loan_value) / liquidation_value
+    if liquidation_limit > loan_value {
+        let buffer = liquidation_limit - loan_value;
+        for (position, liquidation_value) in positions.iter_mut().zip(liquidation_values) {
+            position.liquidation_price_drop = if buffer >= liquidation_value {
+                Decimal256::one()
+            } else {
+                Decimal256::from_ratio(buffer, liquidation_value)
+            };
+        }
+    }
+
+    let health_factor = if loan_value.is_zero() {
+        None
+    } else {
+        Some(Decimal256::from_ratio(liquidation_limit, loan_value))
+    };
+
+    Ok(BorrowerPositionResponse {
+        borrower: borrower.to_string(),
+        collaterals: positions,
+        borrow_limit,
+        liquidation_limit,
+        loan_value,
+        health_factor,
+    })
+}
+
+pub fn query_liquidatable_borrowers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<LiquidatableBorrowersResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+    let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    // Only a page of the collateral index is scanned, so pages
+    // without liquidatable positions can be empty
+    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?;
+    let last_borrower = all_collaterals.last().map(|c| c.borrower.clone());
+
+    let mut borrowers: Vec<LiquidatableBorrowerResponse> = vec![];
+    for collaterals_res in all_collaterals {
+        let collaterals: Tokens = collaterals_res.collaterals.to_raw(deps)?;
+        let block_time = Some(env.block.time.seconds());
+        let (liquidation_limit, _) = compute_liquidation_limit(deps, &collaterals, block_time)?;
+        let loan_value = query_borrower_loan_value(
+            deps,
+            market_contract.clone(),
+            deps.api.addr_validate(&collaterals_res.borrower)?,
+            env.block.height,
+            env.block.time.seconds(),
+        )?
+        .loan_value;
+
+        // Same check as liquidate_collateral
+        if liquidation_limit >= loan_value {
+            continue;
+        }
+
+        let (borrow_limit, collateral_prices) =
+            compute_borrow_limit(deps, &collaterals, block_time)?;
+        let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
+            deps,
+            liquidation_contract.clone(),
+            loan_value,
+            borrow_limit,
+            &collaterals_res.collaterals,
+            collateral_prices,
+        )?;
+
+        borrowers.push(LiquidatableBorrowerResponse {
+            borrower: collaterals_res.borrower,
+            loan_value,
+            borrow_limit,
+            liquidation_limit,
+            liquidation_amount: liquidation_amount_res.collaterals,
+        });
+    }
+
+    Ok(LiquidatableBorrowersResponse {
+        borrowers,
+        last_borrower,
+    })
+}
@@ -6,14 +6,19 @@ use cosmwasm_std::{
 };
 
 use crate::collateral::{
-    liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    assert_borrower_health, decrease_isolated_debt, increase_isolated_debt, liquidate_collateral,
+    lock_collateral, query_all_collaterals, query_borrow_limit, query_borrower_position,
+    query_collaterals, query_isolated_debt, query_liquidatable_borrowers, query_liquidation_limit,
+    repay_with_collateral, unlock_collateral,
 };
 use crate::error::ContractError;
+use crate::leverage::{continue_leverage_loop, leverage_loop};
 use crate::querier::query_epoch_state;
 use crate::state::{
-    read_config, read_epoch_state, read_whitelist, read_whitelist_elem, store_config,
-    store_epoch_state, store_whitelist_elem, Config, EpochState, WhitelistElem,
+    assert_total_locked_migrated, migrate_state, migrate_total_locked, read_config,
+    read_epoch_state, read_isolated_debt, read_swap_path, read_total_locked, read_whitelist,
+    read_whitelist_elem, store_config, store_epoch_state, store_swap_path, store_whitelist_elem,
+    Config, EpochState, WhitelistElem,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -21,10 +26,23 @@ use moneymarket::common::optional_addr_validate;
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceMode;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    ConfigChange, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SwapPath,
+    WhitelistResponse, WhitelistResponseElem,
+};
+use moneymarket::ownership;
+use moneymarket::pause::{
+    assert_not_paused, guardian_to_raw, read_pause_status, update_pause_status_with_market,
+    Operation,
 };
 use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket::timelock;
+use moneymarket::tokens::collateral_to_raw;
+use moneymarket::version::{assert_migrate_version, is_older_version, set_contract_version};
+
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-overseer";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -33,6 +51,8 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     store_config(
         deps.storage,
         &Config {
@@ -48,6 +68,8 @@ pub fn instantiate(
             buffer_distribution_factor: msg.buffer_distribution_factor,
             anc_purchase_factor: msg.anc_purchase_factor,
             price_timeframe: msg.price_timeframe,
+            price_mode: msg.price_mode,
+            guardian: None,
         },
     )?;
 
@@ -62,6 +84,8 @@ pub fn instantiate(
         },
     )?;
 
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+
     Ok(Response::default())
 }
 
@@ -72,9 +96,15 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if !matches!(msg, ExecuteMsg::MigrateTotalLocked { .. }) {
+        assert_total_locked_migrated(deps.storage)?;
+    }
+    if let Some(operation) = pausable_operation(&msg) {
+        assert_not_paused(deps.storage, operation)?;
+    }
+
     match msg {
         ExecuteMsg::UpdateConfig {
-            owner_addr,
             oracle_contract,
             liquidation_contract,
             threshold_deposit_rate,
@@ -83,12 +113,13 @@ pub fn execute(
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            price_mode,
+            guardian,
         } => {
             let api = deps.api;
             update_config(
                 deps,
                 info,
-                optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, oracle_contract)?,
                 optional_addr_validate(api, liquidation_contract)?,
                 threshold_deposit_rate,
@@ -97,14 +128,27 @@ pub fn execute(
                 anc_purchase_factor,
                 epoch_period,
                 price_timeframe,
+                price_mode,
+                guardian,
             )
         }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::Pause { operations } => update_pause(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => update_pause(deps, info, operations, false),
         ExecuteMsg::Whitelist {
             name,
             symbol,
             collateral_token,
             custody_contract,
             max_ltv,
+            liquidation_threshold,
+            max_total_locked,
+            max_borrower_locked,
+            debt_ceiling,
         } => {
             let api = deps.api;
             register_whitelist(
@@ -112,38 +156,86 @@ pub fn execute(
                 info,
                 name,
                 symbol,
-                api.addr_validate(&collateral_token)?,
+                collateral_token,
                 api.addr_validate(&custody_contract)?,
                 max_ltv,
+                liquidation_threshold,
+                max_total_locked,
+                max_borrower_locked,
+                debt_ceiling,
             )
         }
-        ExecuteMsg::UpdateWhitelist {
-            collateral_token,
-            custody_contract,
-            max_ltv,
-        } => {
-            let api = deps.api;
-            update_whitelist(
-                deps,
-                info,
-                api.addr_validate(&collateral_token)?,
-                optional_addr_validate(api, custody_contract)?,
-                max_ltv,
-            )
-        }
+        ExecuteMsg::ScheduleChange { change, eta } => schedule_change(deps, env, info, change, eta),
+        ExecuteMsg::ExecuteChange { id } => execute_change(deps, env, id),
+        ExecuteMsg::CancelChange { id } => cancel_change(deps, info, id),
         ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
         ExecuteMsg::UpdateEpochState {
             interest_buffer,
             distributed_interest,
         } => update_epoch_state(deps, env, info, interest_buffer, distributed_interest),
-        ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, info, collaterals),
+        ExecuteMsg::AssertBorrowerHealth { borrower } => {
+            let api = deps.api;
+            assert_borrower_health(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::IncreaseIsolatedDebt { borrower } => {
+            let api = deps.api;
+            increase_isolated_debt(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::DecreaseIsolatedDebt { borrower } => {
+            let api = deps.api;
+            decrease_isolated_debt(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, env, info, collaterals),
         ExecuteMsg::UnlockCollateral { collaterals } => {
             unlock_collateral(deps, env, info, collaterals)
         }
+        ExecuteMsg::ContinueLeverageLoop {
+            borrower,
+            collateral_token,
+            target_ltv,
+            iterations,
+            prev_balance,
+        } => {
+            let api = deps.api;
+            continue_leverage_loop(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                iterations,
+                prev_balance,
+            )
+        }
+        ExecuteMsg::LeverageLoop {
+            collateral_token,
+            target_ltv,
+            iterations,
+        } => leverage_loop(deps, env, info, collateral_token, target_ltv, iterations),
+        ExecuteMsg::RepayWithCollateral {
+            collateral_token,
+            amount,
+        } => repay_with_collateral(deps, env, info, collateral_token, amount),
         ExecuteMsg::LiquidateCollateral { borrower } => {
             let api = deps.api;
             liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
         }
+        ExecuteMsg::MigrateTotalLocked { limit } => migrate_legacy_total_locked(deps, limit),
+    }
+}
+
+/// Operation of the message the guardian can pause
+fn pausable_operation(msg: &ExecuteMsg) -> Option<Operation> {
+    match msg {
+        ExecuteMsg::LockCollateral { .. } => Some(Operation::Lock),
+        ExecuteMsg::UnlockCollateral { .. } => Some(Operation::Unlock),
+        ExecuteMsg::LeverageLoop { .. } | ExecuteMsg::ContinueLeverageLoop { .. } => {
+            Some(Operation::Borrow)
+        }
+        ExecuteMsg::RepayWithCollateral { .. } => Some(Operation::Repay),
+        ExecuteMsg::LiquidateCollateral { .. } => Some(Operation::Liquidate),
+        _ => None,
     }
 }
 
@@ -151,7 +243,6 @@ pub fn execute(
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
     oracle_contract: Option<Addr>,
     liquidation_contract: Option<Addr>,
     threshold_deposit_rate: Option<Decimal256>,
@@ -160,6 +251,8 @@ pub fn update_config(
     anc_purchase_factor: Option<Decimal256>,
     epoch_period: Option<u64>,
     price_timeframe: Option<u64>,
+    price_mode: Option<PriceMode>,
+    guardian: Option<String>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -167,10 +260,6 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(&owner_addr.to_string())?;
-    }
-
     if let Some(oracle_contract) = oracle_contract {
         config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract.to_string())?;
     }
@@ -205,30 +294,115 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(price_mode) = price_mode {
+        config.price_mode = price_mode;
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = guardian_to_raw(deps.api, &guardian)?;
+    }
+
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, &owner, expires_in)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+    ]))
+}
+
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ownership::drop_ownership_proposal(deps.storage)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "drop_ownership_proposal")]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    config.owner_addr = ownership::claim_ownership(deps.storage, &env, &sender_raw)?;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<Operation>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && Some(sender_raw) != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?.to_string();
+    update_pause_status_with_market(deps, market_contract, &operations, paused)?;
+
+    let operation_logs: Vec<String> = operations.iter().map(|o| o.to_string()).collect();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", if paused { "pause" } else { "unpause" }),
+        attr("operations", operation_logs.join(",")),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn register_whitelist(
     deps: DepsMut,
     info: MessageInfo,
     name: String,
     symbol: String,
-    collateral_token: Addr,
+    collateral_token: String,
     custody_contract: Addr,
     max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+    max_total_locked: Uint256,
+    max_borrower_locked: Option<Uint256>,
+    debt_ceiling: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
-    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     if read_whitelist_elem(deps.storage, &collateral_token_raw).is_ok() {
         return Err(ContractError::TokenAlreadyRegistered {});
     }
 
+    assert_liquidation_threshold(max_ltv, liquidation_threshold)?;
+
     store_whitelist_elem(
         deps.storage,
         &collateral_token_raw,
@@ -237,6 +411,10 @@ pub fn register_whitelist(
             symbol: symbol.to_string(),
             custody_contract: deps.api.addr_canonicalize(custody_contract.as_str())?,
             max_ltv,
+            liquidation_threshold,
+            max_total_locked: Some(max_total_locked),
+            max_borrower_locked,
+            debt_ceiling,
         },
     )?;
 
@@ -247,22 +425,24 @@ pub fn register_whitelist(
         attr("collateral_token", collateral_token),
         attr("custody_contract", custody_contract),
         attr("LTV", max_ltv.to_string()),
+        attr("liquidation_threshold", liquidation_threshold.to_string()),
+        attr("max_total_locked", max_total_locked),
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_whitelist(
     deps: DepsMut,
-    info: MessageInfo,
-    collateral_token: Addr,
+    collateral_token: String,
     custody_contract: Option<Addr>,
     max_ltv: Option<Decimal256>,
+    liquidation_threshold: Option<Decimal256>,
+    swap_path: Option<SwapPath>,
+    max_total_locked: Option<Uint256>,
+    max_borrower_locked: Option<Uint256>,
+    debt_ceiling: Option<Uint256>,
 ) -> Result<Response, ContractError> {
-    let config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
     let mut whitelist_elem: WhitelistElem =
         read_whitelist_elem(deps.storage, &collateral_token_raw)?;
 
@@ -274,6 +454,33 @@ pub fn update_whitelist(
         whitelist_elem.max_ltv = max_ltv;
     }
 
+    if let Some(liquidation_threshold) = liquidation_threshold {
+        whitelist_elem.liquidation_threshold = liquidation_threshold;
+    }
+
+    assert_liquidation_threshold(whitelist_elem.max_ltv, whitelist_elem.liquidation_threshold)?;
+
+    // Lowering a cap below the locked amount only blocks new locks
+    if let Some(max_total_locked) = max_total_locked {
+        whitelist_elem.max_total_locked = Some(max_total_locked);
+    }
+
+    if let Some(max_borrower_locked) = max_borrower_locked {
+        whitelist_elem.max_borrower_locked = Some(max_borrower_locked);
+    }
+
+    if let Some(debt_ceiling) = debt_ceiling {
+        whitelist_elem.debt_ceiling = Some(debt_ceiling);
+    }
+
+    if let Some(swap_path) = swap_path {
+        if let SwapPath::Terraswap { pair_contract, .. } = &swap_path {
+            deps.api.addr_validate(pair_contract)?;
+        }
+
+        store_swap_path(deps.storage, &collateral_token_raw, &swap_path)?;
+    }
+
     store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -284,6 +491,93 @@ pub fn update_whitelist(
             deps.api.addr_humanize(&whitelist_elem.custody_contract)?,
         ),
         attr("LTV", whitelist_elem.max_ltv.to_string()),
+        attr(
+            "liquidation_threshold",
+            whitelist_elem.liquidation_threshold.to_string(),
+        ),
+    ]))
+}
+
+/// Loans must become borrowable before they become liquidatable
+fn assert_liquidation_threshold(
+    max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+) -> Result<(), ContractError> {
+    if liquidation_threshold < max_ltv || liquidation_threshold > Decimal256::one() {
+        return Err(ContractError::InvalidLiquidationThreshold {});
+    }
+
+    Ok(())
+}
+
+pub fn schedule_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    change: ConfigChange,
+    eta: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let id = timelock::schedule_change(deps.storage, &env, change, eta)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "schedule_change"),
+        attr("id", id.to_string()),
+        attr("eta", eta.to_string()),
+    ]))
+}
+
+/// Anyone can apply a queued change once its ETA has passed
+pub fn execute_change(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let res = match timelock::take_ready_change(deps.storage, &env, id)? {
+        ConfigChange::UpdateWhitelist {
+            collateral_token,
+            custody_contract,
+            max_ltv,
+            liquidation_threshold,
+            swap_path,
+            max_total_locked,
+            max_borrower_locked,
+            debt_ceiling,
+        } => {
+            let api = deps.api;
+            update_whitelist(
+                deps,
+                collateral_token,
+                optional_addr_validate(api, custody_contract)?,
+                max_ltv,
+                liquidation_threshold,
+                swap_path,
+                max_total_locked,
+                max_borrower_locked,
+                debt_ceiling,
+            )?
+        }
+        ConfigChange::UpdateMinDelay { min_delay } => {
+            timelock::store_min_delay(deps.storage, min_delay)?;
+            Response::new()
+        }
+    };
+
+    Ok(res.add_attributes(vec![
+        attr("action", "execute_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+pub fn cancel_change(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    timelock::cancel_change::<ConfigChange>(deps.storage, id)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_change"),
+        attr("id", id.to_string()),
     ]))
 }
 
@@ -482,10 +776,40 @@ pub fn update_epoch_state(
         ]))
 }
 
+pub fn migrate_legacy_total_locked(
+    deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let completed = migrate_total_locked(deps.storage, limit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate_total_locked"),
+        attr("completed", completed.to_string()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if is_older_version(&stored_version, "0.4.0")? {
+        migrate_state(deps.storage)?;
+    }
+
+    timelock::store_min_delay(deps.storage, msg.min_delay)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingChanges { start_after, limit } => to_binary(
+            &timelock::read_pending_changes::<ConfigChange>(deps.storage, start_after, limit)?,
+        ),
+        QueryMsg::PauseStatus {} => to_binary(&read_pause_status(deps.storage)?),
         QueryMsg::EpochState {} => to_binary(&query_state(deps)?),
         QueryMsg::Whitelist {
             collateral_token,
@@ -493,8 +817,8 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
         } => to_binary(&query_whitelist(
             deps,
-            optional_addr_validate(deps.api, collateral_token)?,
-            optional_addr_validate(deps.api, start_after)?,
+            collateral_token,
+            start_after,
             limit,
         )?),
         QueryMsg::Collaterals { borrower } => to_binary(&query_collaterals(
@@ -514,6 +838,31 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             deps.api.addr_validate(&borrower)?,
             block_time,
         )?),
+        QueryMsg::IsolatedDebt { borrower } => to_binary(&query_isolated_debt(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::LiquidationLimit {
+            borrower,
+            block_time,
+        } => to_binary(&query_liquidation_limit(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            block_time,
+        )?),
+        QueryMsg::BorrowerPosition { borrower } => to_binary(&query_borrower_position(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::LiquidatableBorrowers { start_after, limit } => {
+            to_binary(&query_liquidatable_borrowers(
+                deps,
+                env,
+                optional_addr_validate(deps.api, start_after)?,
+                limit,
+            )?)
+        }
     }
 }
 
@@ -538,6 +887,12 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         buffer_distribution_factor: config.buffer_distribution_factor,
         anc_purchase_factor: config.anc_purchase_factor,
         price_timeframe: config.price_timeframe,
+        price_mode: config.price_mode,
+        guardian: match config.guardian {
+            Some(guardian) => Some(deps.api.addr_humanize(&guardian)?.to_string()),
+            None => None,
+        },
+        min_delay: timelock::read_min_delay(deps.storage)?,
     })
 }
 
@@ -547,30 +902,36 @@ pub fn query_state(deps: Deps) -> StdResult<EpochState> {
 
 pub fn query_whitelist(
     deps: Deps,
-    collateral_token: Option<Addr>,
-    start_after: Option<Addr>,
+    collateral_token: Option<String>,
+    start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<WhitelistResponse> {
     if let Some(collateral_token) = collateral_token {
-        let whitelist_elem: WhitelistElem = read_whitelist_elem(
-            deps.storage,
-            &deps.api.addr_canonicalize(collateral_token.as_str())?,
-        )?;
+        let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
+        let whitelist_elem: WhitelistElem =
+            read_whitelist_elem(deps.storage, &collateral_token_raw)?;
         Ok(WhitelistResponse {
             elems: vec![WhitelistResponseElem {
                 name: whitelist_elem.name,
                 symbol: whitelist_elem.symbol,
                 max_ltv: whitelist_elem.max_ltv,
+                liquidation_threshold: whitelist_elem.liquidation_threshold,
                 custody_contract: deps
                     .api
                     .addr_humanize(&whitelist_elem.custody_contract)?
                     .to_string(),
-                collateral_token: collateral_token.to_string(),
+                collateral_token,
+                swap_path: read_swap_path(deps.storage, &collateral_token_raw),
+                max_total_locked: whitelist_elem.max_total_locked,
+                max_borrower_locked: whitelist_elem.max_borrower_locked,
+                total_locked: read_total_locked(deps.storage, &collateral_token_raw),
+                debt_ceiling: whitelist_elem.debt_ceiling,
+                isolated_debt: read_isolated_debt(deps.storage, &collateral_token_raw),
             }],
         })
     } else {
         let start_after = if let Some(start_after) = start_after {
-            Some(deps.api.addr_canonicalize(start_after.as_str())?)
+            Some(collateral_to_raw(deps.api, &start_after)?)
         } else {
             None
         };
@@ -12,12 +12,36 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Borrower collateral cap exceeded; locked amount cannot exceed {0}")]
+    BorrowerLockedCapExceeded(u128),
+
+    #[error("Debt ceiling of the isolated collateral reached; total loans cannot exceed {0}")]
+    DebtCeilingReached(u128),
+
     #[error("Cannot liquidate safely collateralized loan")]
     CannotLiquidateSafeLoan {},
 
     #[error("An epoch has not passed yet; last executed height: {0}")]
     EpochNotPassed(u64),
 
+    #[error("Liquidation threshold must be between max LTV and 1")]
+    InvalidLiquidationThreshold {},
+
+    #[error("Leverage loop requires a swap pair for the collateral")]
+    SwapPathNotSupported {},
+
+    #[error("Leverage loop does not support native collateral")]
+    NativeCollateralNotSupported {},
+
+    #[error("Target LTV cannot exceed max LTV: {0}")]
+    TargetLtvExceedsMaxLtv(String),
+
+    #[error("Repay amount too high; Loan liability becomes greater than borrow limit: {0}")]
+    RepayWithCollateralTooLarge(u128),
+
+    #[error("Collateral cap exceeded; total locked amount cannot exceed {0}")]
+    TotalLockedCapExceeded(u128),
+
     #[error("Token is already registered as collateral")]
     TokenAlreadyRegistered {},
 
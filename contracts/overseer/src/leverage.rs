@@ -0,0 +1,268 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use crate::collateral::{compute_borrow_limit, increase_total_locked};
+use crate::error::ContractError;
+use crate::querier::query_borrower_loan_value;
+use crate::state::{
+    read_collaterals, read_config, read_swap_path, read_whitelist_elem, store_collaterals, Config,
+    WhitelistElem,
+};
+
+use moneymarket::custody::{Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::overseer::{ExecuteMsg, SwapPath};
+use moneymarket::pause::{assert_not_paused, Operation};
+use moneymarket::querier::{deduct_tax, query_token_balance};
+use moneymarket::tokens::{collateral_to_raw, native_denom, Tokens, TokensMath};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
+
+pub fn leverage_loop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+    target_ltv: Decimal256,
+    iterations: u32,
+) -> Result<Response, ContractError> {
+    // Every step locks the bought collateral
+    assert_not_paused(deps.storage, Operation::Lock)?;
+
+    // Bought collateral is deposited to the custody with a cw20 send
+    let collateral_token_raw = collateral_to_raw(deps.api, &collateral_token)?;
+    if native_denom(&collateral_token_raw).is_some() {
+        return Err(ContractError::NativeCollateralNotSupported {});
+    }
+
+    let collateral_token = deps.api.addr_validate(&collateral_token)?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    if target_ltv > whitelist_elem.max_ltv {
+        return Err(ContractError::TargetLtvExceedsMaxLtv(
+            whitelist_elem.max_ltv.to_string(),
+        ));
+    }
+
+    // Collateral can only be bought on a swap pair
+    if let SwapPath::LiquidationQueue {} = read_swap_path(deps.storage, &collateral_token_raw) {
+        return Err(ContractError::SwapPathNotSupported {});
+    }
+
+    let (messages, borrow_amount) = leverage_step(
+        deps.as_ref(),
+        &env,
+        &info.sender,
+        &collateral_token,
+        target_ltv,
+        iterations,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "leverage_loop"),
+        attr("borrower", info.sender),
+        attr("collateral_token", collateral_token),
+        attr("target_ltv", target_ltv.to_string()),
+        attr("iterations", iterations.to_string()),
+        attr("borrow_amount", borrow_amount),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn continue_leverage_loop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    iterations: u32,
+    prev_balance: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_not_paused(deps.storage, Operation::Lock)?;
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+
+    let cur_balance: Uint256 = query_token_balance(
+        deps.as_ref(),
+        collateral_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    let amount = cur_balance - prev_balance;
+
+    // Lock the bought collateral before the next borrow
+    // checks the borrow limit
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let collaterals: Tokens = vec![(collateral_token_raw, amount)];
+    cur_collaterals.add(collaterals.clone());
+    increase_total_locked(deps.storage, &cur_collaterals, &collaterals)?;
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+
+    let (messages, borrow_amount) = leverage_step(
+        deps.as_ref(),
+        &env,
+        &borrower,
+        &collateral_token,
+        target_ltv,
+        iterations,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: custody_contract.to_string(),
+                    amount: amount.into(),
+                    msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some(borrower.to_string()),
+                    })?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: custody_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: borrower.to_string(),
+                    amount,
+                })?,
+            }),
+        ])
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "continue_leverage_loop"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", amount),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Borrow up to the target LTV and buy collateral with it,
+/// the health of the loan is asserted once the loop is over
+fn leverage_step(
+    deps: Deps,
+    env: &Env,
+    borrower: &Addr,
+    collateral_token: &Addr,
+    target_ltv: Decimal256,
+    iterations: u32,
+) -> Result<(Vec<CosmosMsg>, Uint256), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+    let assert_health_message = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::AssertBorrowerHealth {
+            borrower: borrower.to_string(),
+        })?,
+    });
+
+    if iterations == 0 {
+        return Ok((vec![assert_health_message], Uint256::zero()));
+    }
+
+    let collaterals: Tokens = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+    let (_, collateral_prices) =
+        compute_borrow_limit(deps, &collaterals, Some(env.block.time.seconds()))?;
+    let collateral_value: Uint256 = collaterals
+        .iter()
+        .zip(collateral_prices.iter())
+        .fold(Uint256::zero(), |value, (collateral, price)| {
+            value + collateral.1 * *price
+        });
+
+    let loan_value = query_borrower_loan_value(
+        deps,
+        market_contract.clone(),
+        borrower.clone(),
+        env.block.height,
+        env.block.time.seconds(),
+    )?
+    .loan_value;
+
+    let target_loan_value = collateral_value * target_ltv;
+    if target_loan_value <= loan_value {
+        return Ok((vec![assert_health_message], Uint256::zero()));
+    }
+
+    let borrow_amount = target_loan_value - loan_value;
+    let (pair_contract, max_spread) = match read_swap_path(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    ) {
+        SwapPath::Terraswap {
+            pair_contract,
+            max_spread,
+        } => (pair_contract, max_spread),
+        SwapPath::LiquidationQueue {} => return Err(ContractError::SwapPathNotSupported {}),
+    };
+
+    // The borrowed amount is taxed on the way to
+    // the overseer and again on the way to the pair
+    let offer_coin = deduct_tax(
+        deps,
+        deduct_tax(
+            deps,
+            Coin {
+                denom: config.stable_denom.clone(),
+                amount: borrow_amount.into(),
+            },
+        )?,
+    )?;
+
+    let prev_balance: Uint256 =
+        query_token_balance(deps, collateral_token.clone(), env.contract.address.clone())?;
+
+    Ok((
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: market_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: borrower.to_string(),
+                    borrow_amount,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_contract,
+                funds: vec![offer_coin.clone()],
+                msg: to_binary(&TerraswapExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: offer_coin.denom,
+                        },
+                        amount: offer_coin.amount,
+                    },
+                    belief_price: None,
+                    max_spread,
+                    to: None,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ContinueLeverageLoop {
+                    borrower: borrower.to_string(),
+                    collateral_token: collateral_token.to_string(),
+                    target_ltv,
+                    iterations: iterations - 1,
+                    prev_balance,
+                })?,
+            }),
+        ],
+        borrow_amount,
+    ))
+}
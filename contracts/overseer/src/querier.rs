@@ -2,7 +2,9 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
 
 use moneymarket::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg};
+use moneymarket::market::{
+    BorrowerLoanValueResponse, EpochStateResponse, QueryMsg as MarketQueryMsg,
+};
 use moneymarket::tokens::TokensHuman;
 
 pub fn query_epoch_state(
@@ -17,29 +19,32 @@ pub fn query_epoch_state(
             msg: to_binary(&MarketQueryMsg::EpochState {
                 block_height: Some(block_height),
                 distributed_interest,
+                denom: None,
             })?,
         }))?;
 
     Ok(epoch_state)
 }
 
-/// Query borrow amount from the market contract
-pub fn query_borrower_info(
+/// Query the value of all stable loans from the market contract
+pub fn query_borrower_loan_value(
     deps: Deps,
     market_addr: Addr,
     borrower: Addr,
     block_height: u64,
-) -> StdResult<BorrowerInfoResponse> {
-    let borrower_amount: BorrowerInfoResponse =
+    block_time: u64,
+) -> StdResult<BorrowerLoanValueResponse> {
+    let loan_value: BorrowerLoanValueResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: market_addr.to_string(),
-            msg: to_binary(&MarketQueryMsg::BorrowerInfo {
+            msg: to_binary(&MarketQueryMsg::BorrowerLoanValue {
                 borrower: borrower.to_string(),
-                block_height: Some(block_height),
+                block_height,
+                block_time,
             })?,
         }))?;
 
-    Ok(borrower_amount)
+    Ok(loan_value)
 }
 
 #[allow(clippy::ptr_arg)]
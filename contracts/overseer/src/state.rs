@@ -5,14 +5,20 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
-use moneymarket::tokens::Tokens;
+use moneymarket::oracle::PriceMode;
+use moneymarket::overseer::{CollateralsResponse, SwapPath, WhitelistResponseElem};
+use moneymarket::tokens::{collateral_to_human, Tokens, TokensMath};
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
+const KEY_TOTAL_LOCKED_MIGRATION: &[u8] = b"total_locked_migration";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_SWAP_PATH: &[u8] = b"swap_path";
+const PREFIX_TOTAL_LOCKED: &[u8] = b"total_locked";
+const PREFIX_ISOLATED_DEBT: &[u8] = b"isolated_debt";
+const PREFIX_BORROWER_ISOLATED_DEBT: &[u8] = b"borrower_isolated_debt";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -28,6 +34,10 @@ pub struct Config {
     pub buffer_distribution_factor: Decimal256,
     pub anc_purchase_factor: Decimal256,
     pub price_timeframe: u64,
+    pub price_mode: PriceMode,
+    /// Address allowed to pause operations besides the owner
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -44,7 +54,15 @@ pub struct WhitelistElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
     pub custody_contract: CanonicalAddr,
+    /// Cap on the amount locked by all borrowers, collaterals
+    /// whitelisted before caps were introduced are uncapped
+    pub max_total_locked: Option<Uint256>,
+    /// Cap on the amount locked by a single borrower
+    pub max_borrower_locked: Option<Uint256>,
+    /// Isolated collaterals cap the loans of all the borrowers holding them
+    pub debt_ceiling: Option<Uint256>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -104,19 +122,129 @@ pub fn read_whitelist(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let collateral_token = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            let collateral_token_raw = CanonicalAddr::from(k);
+            let collateral_token = collateral_to_human(deps.api, &collateral_token_raw)?;
             let custody_contract = deps.api.addr_humanize(&v.custody_contract)?.to_string();
+            let swap_path = read_swap_path(deps.storage, &collateral_token_raw);
+            let total_locked = read_total_locked(deps.storage, &collateral_token_raw);
+            let isolated_debt = read_isolated_debt(deps.storage, &collateral_token_raw);
             Ok(WhitelistResponseElem {
                 name: v.name,
                 symbol: v.symbol,
                 collateral_token,
                 custody_contract,
                 max_ltv: v.max_ltv,
+                liquidation_threshold: v.liquidation_threshold,
+                swap_path,
+                max_total_locked: v.max_total_locked,
+                max_borrower_locked: v.max_borrower_locked,
+                total_locked,
+                debt_ceiling: v.debt_ceiling,
+                isolated_debt,
             })
         })
         .collect()
 }
 
+pub fn store_swap_path(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    swap_path: &SwapPath,
+) -> StdResult<()> {
+    let mut swap_path_bucket: Bucket<SwapPath> = Bucket::new(storage, PREFIX_SWAP_PATH);
+    swap_path_bucket.save(collateral_token.as_slice(), swap_path)
+}
+
+/// Collaterals without a registered path are sold to the liquidation contract
+pub fn read_swap_path(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> SwapPath {
+    let swap_path_bucket: ReadonlyBucket<SwapPath> = ReadonlyBucket::new(storage, PREFIX_SWAP_PATH);
+    match swap_path_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => SwapPath::LiquidationQueue {},
+    }
+}
+
+pub fn store_total_locked(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    total_locked: &Uint256,
+) -> StdResult<()> {
+    let mut total_locked_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_TOTAL_LOCKED);
+    total_locked_bucket.save(collateral_token.as_slice(), total_locked)
+}
+
+pub fn read_total_locked(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Uint256 {
+    let total_locked_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_LOCKED);
+    match total_locked_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
+/// Whitelisted collaterals with a debt ceiling
+pub fn read_isolated_collaterals(storage: &dyn Storage) -> StdResult<Vec<CanonicalAddr>> {
+    ReadonlyBucket::<WhitelistElem>::new(storage, PREFIX_WHITELIST)
+        .range(None, None, Order::Ascending)
+        .filter_map(|elem| match elem {
+            Ok((k, v)) => v.debt_ceiling.map(|_| Ok(CanonicalAddr::from(k))),
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+pub fn store_isolated_debt(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    isolated_debt: &Uint256,
+) -> StdResult<()> {
+    let mut isolated_debt_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_ISOLATED_DEBT);
+    isolated_debt_bucket.save(collateral_token.as_slice(), isolated_debt)
+}
+
+pub fn read_isolated_debt(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Uint256 {
+    let isolated_debt_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_ISOLATED_DEBT);
+    match isolated_debt_bucket.load(collateral_token.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
+/// Loans of a borrower counted toward the debt ceiling of a collateral
+pub fn store_borrower_isolated_debt(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    isolated_debt: &Uint256,
+) -> StdResult<()> {
+    let mut isolated_debt_bucket: Bucket<Uint256> = Bucket::multilevel(
+        storage,
+        &[PREFIX_BORROWER_ISOLATED_DEBT, collateral_token.as_slice()],
+    );
+    if isolated_debt.is_zero() {
+        isolated_debt_bucket.remove(borrower.as_slice());
+        Ok(())
+    } else {
+        isolated_debt_bucket.save(borrower.as_slice(), isolated_debt)
+    }
+}
+
+pub fn read_borrower_isolated_debt(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+) -> Uint256 {
+    let isolated_debt_bucket: ReadonlyBucket<Uint256> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BORROWER_ISOLATED_DEBT, collateral_token.as_slice()],
+    );
+    match isolated_debt_bucket.load(borrower.as_slice()) {
+        Ok(v) => v,
+        _ => Uint256::zero(),
+    }
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn store_collaterals(
     storage: &mut dyn Storage,
@@ -164,7 +292,7 @@ pub fn read_all_collaterals(
             let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
             let collaterals: Vec<(String, Uint256)> = v
                 .iter()
-                .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
+                .map(|c| Ok((collateral_to_human(deps.api, &c.0)?, c.1)))
                 .collect::<StdResult<Vec<(String, Uint256)>>>()?;
 
             Ok(CollateralsResponse {
@@ -183,3 +311,147 @@ fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
         v
     })
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner_addr: CanonicalAddr,
+    pub oracle_contract: CanonicalAddr,
+    pub market_contract: CanonicalAddr,
+    pub liquidation_contract: CanonicalAddr,
+    pub collector_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub epoch_period: u64,
+    pub threshold_deposit_rate: Decimal256,
+    pub target_deposit_rate: Decimal256,
+    pub buffer_distribution_factor: Decimal256,
+    pub anc_purchase_factor: Decimal256,
+    pub price_timeframe: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyWhitelistElem {
+    pub name: String,
+    pub symbol: String,
+    pub max_ltv: Decimal256,
+    pub custody_contract: CanonicalAddr,
+}
+
+/// Progress of the locked totals summed from the borrower
+/// collaterals of a migrated 0.3.x deployment
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalLockedMigration {
+    pub last_borrower: Option<CanonicalAddr>,
+}
+
+/// Legacy collaterals are liquidated at their max LTV, valued at spot prices
+/// and uncapped, their locked totals are summed afterwards in pages of
+/// borrowers by `migrate_total_locked`
+pub fn migrate_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = ReadonlySingleton::new(storage, KEY_CONFIG).load()?;
+    store_config(
+        storage,
+        &Config {
+            owner_addr: legacy_config.owner_addr,
+            oracle_contract: legacy_config.oracle_contract,
+            market_contract: legacy_config.market_contract,
+            liquidation_contract: legacy_config.liquidation_contract,
+            collector_contract: legacy_config.collector_contract,
+            stable_denom: legacy_config.stable_denom,
+            epoch_period: legacy_config.epoch_period,
+            threshold_deposit_rate: legacy_config.threshold_deposit_rate,
+            target_deposit_rate: legacy_config.target_deposit_rate,
+            buffer_distribution_factor: legacy_config.buffer_distribution_factor,
+            anc_purchase_factor: legacy_config.anc_purchase_factor,
+            price_timeframe: legacy_config.price_timeframe,
+            price_mode: PriceMode::Spot {},
+            guardian: None,
+        },
+    )?;
+
+    let legacy_whitelist: Vec<(Vec<u8>, LegacyWhitelistElem)> =
+        ReadonlyBucket::new(storage, PREFIX_WHITELIST)
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, LegacyWhitelistElem)>>>()?;
+    for (collateral_token, elem) in legacy_whitelist {
+        store_whitelist_elem(
+            storage,
+            &CanonicalAddr::from(collateral_token),
+            &WhitelistElem {
+                name: elem.name,
+                symbol: elem.symbol,
+                max_ltv: elem.max_ltv,
+                liquidation_threshold: elem.max_ltv,
+                custody_contract: elem.custody_contract,
+                max_total_locked: None,
+                max_borrower_locked: None,
+                debt_ceiling: None,
+            },
+        )?;
+    }
+
+    Singleton::new(storage, KEY_TOTAL_LOCKED_MIGRATION).save(&TotalLockedMigration {
+        last_borrower: None,
+    })
+}
+
+/// Adds the collaterals of a page of borrowers to the locked
+/// totals and returns whether all borrowers are summed
+pub fn migrate_total_locked(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+    let migration: TotalLockedMigration =
+        match ReadonlySingleton::new(storage, KEY_TOTAL_LOCKED_MIGRATION).may_load()? {
+            Some(v) => v,
+            None => return Err(StdError::generic_err("Locked totals are already migrated")),
+        };
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(migration.last_borrower.clone());
+    let page: Vec<(Vec<u8>, Tokens)> = ReadonlyBucket::new(storage, PREFIX_COLLATERALS)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Vec<u8>, Tokens)>>>()?;
+
+    let mut total_locked: Tokens = vec![];
+    for (_, collaterals) in page.iter() {
+        total_locked.add(collaterals.clone());
+    }
+    for (collateral_token, amount) in total_locked {
+        let amount = read_total_locked(storage, &collateral_token) + amount;
+        store_total_locked(storage, &collateral_token, &amount)?;
+    }
+
+    let last_borrower = match page.last() {
+        Some((k, _)) => Some(CanonicalAddr::from(k.clone())),
+        None => migration.last_borrower,
+    };
+    let completed = ReadonlyBucket::<Tokens>::new(storage, PREFIX_COLLATERALS)
+        .range(
+            calc_range_start(last_borrower.clone()).as_deref(),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .is_none();
+    if completed {
+        Singleton::<TotalLockedMigration>::new(storage, KEY_TOTAL_LOCKED_MIGRATION).remove();
+    } else {
+        Singleton::new(storage, KEY_TOTAL_LOCKED_MIGRATION)
+            .save(&TotalLockedMigration { last_borrower })?;
+    }
+
+    Ok(completed)
+}
+
+/// Collaterals cannot be locked or released while the
+/// locked totals of a 0.3.x deployment are being summed
+pub fn assert_total_locked_migrated(storage: &dyn Storage) -> StdResult<()> {
+    if ReadonlySingleton::<TotalLockedMigration>::new(storage, KEY_TOTAL_LOCKED_MIGRATION)
+        .may_load()?
+        .is_some()
+    {
+        return Err(StdError::generic_err(
+            "Locked totals of the legacy overseer are being migrated",
+        ));
+    }
+
+    Ok(())
+}
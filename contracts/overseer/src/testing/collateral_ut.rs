@@ -1,11 +1,13 @@
-use crate::collateral::compute_borrow_limit;
+use crate::collateral::{compute_borrow_limit, decrease_total_locked};
 use crate::contract::{execute, instantiate};
+use crate::state::{read_total_locked, store_total_locked};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::Api;
 
+use moneymarket::oracle::PriceMode;
 use moneymarket::overseer::{ExecuteMsg, InstantiateMsg};
 use moneymarket::tokens::{Token, Tokens};
 
@@ -28,6 +30,8 @@ fn proper_compute_borrow_limit() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -40,6 +44,10 @@ fn proper_compute_borrow_limit() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -50,6 +58,10 @@ fn proper_compute_borrow_limit() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -93,4 +105,68 @@ fn proper_compute_borrow_limit() {
 
     let res2 = (Uint256::from(1800000u128), vec);
     assert_eq!(res, res2);
+
+    // switch collateral valuation to TWAP prices
+    deps.querier.with_twap_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_uint256(800u128),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_uint256(2500u128),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_mode: Some(PriceMode::Twap {
+            window_seconds: 600u64,
+        }),
+        guardian: None,
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap();
+
+    let res = compute_borrow_limit(deps.as_ref(), &collaterals, None).unwrap();
+    let vec: Vec<Decimal256> = vec![
+        Decimal256::from_uint256(800u128),
+        Decimal256::from_uint256(2500u128),
+    ];
+    assert_eq!(res, (Uint256::from(1980000u128), vec));
+}
+
+#[test]
+fn decrease_total_locked_saturates() {
+    let mut deps = mock_dependencies(&[]);
+    let bluna = deps.api.addr_canonicalize("bluna").unwrap();
+    store_total_locked(deps.as_mut().storage, &bluna, &Uint256::from(100u64)).unwrap();
+
+    let collaterals: Tokens = vec![(bluna.clone(), Uint256::from(60u64))];
+    decrease_total_locked(deps.as_mut().storage, &collaterals).unwrap();
+    assert_eq!(
+        read_total_locked(deps.as_ref().storage, &bluna),
+        Uint256::from(40u64)
+    );
+
+    let collaterals: Tokens = vec![(bluna.clone(), Uint256::from(60u64))];
+    decrease_total_locked(deps.as_mut().storage, &collaterals).unwrap();
+    assert_eq!(
+        read_total_locked(deps.as_ref().storage, &bluna),
+        Uint256::zero()
+    );
 }
@@ -10,8 +10,9 @@ use cosmwasm_std::{
 use std::collections::HashMap;
 
 use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse};
+use moneymarket::market::{BorrowerLoanValueResponse, EpochStateResponse};
 use moneymarket::oracle::PriceResponse;
+use moneymarket::pause::PauseStatus;
 use moneymarket::tokens::TokensHuman;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
@@ -24,13 +25,20 @@ pub enum QueryMsg {
         block_height: Option<u64>,
         distributed_interest: Option<Uint256>,
     },
-    /// Query loan amount to market contract
-    BorrowerInfo {
+    /// Query loan value to market contract
+    BorrowerLoanValue {
         borrower: String,
-        block_height: Option<u64>,
+        block_height: u64,
+        block_time: u64,
     },
     /// Query oracle price to oracle contract
     Price { base: String, quote: String },
+    /// Query oracle TWAP price to oracle contract
+    TwapPrice {
+        base: String,
+        quote: String,
+        window_seconds: u64,
+    },
     /// Query liquidation amount to liquidation model contract
     LiquidationAmount {
         borrow_amount: Uint256,
@@ -38,6 +46,10 @@ pub enum QueryMsg {
         collaterals: TokensHuman,
         collateral_prices: Vec<Decimal256>,
     },
+    /// Query cw20 balance to token contract
+    Balance { address: String },
+    /// Query pause status to market contract
+    PauseStatus {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -62,6 +74,8 @@ pub struct WasmMockQuerier {
     oracle_price_querier: OraclePriceQuerier,
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
+    token_querier: TokenQuerier,
+    market_pause_status: PauseStatus,
 }
 
 #[derive(Clone, Default)]
@@ -92,6 +106,8 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
     oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    twap_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    frozen_assets: Vec<String>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -99,6 +115,8 @@ impl OraclePriceQuerier {
     pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
         OraclePriceQuerier {
             oracle_price: oracle_price_to_map(oracle_price),
+            twap_price: HashMap::new(),
+            frozen_assets: vec![],
         }
     }
 }
@@ -203,6 +221,35 @@ pub(crate) fn liquidation_percent_to_map(
     liquidation_percent_map
 }
 
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // this lets us iterate over all pairs that match the first string
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl TokenQuerier {
+    pub fn new(balances: &[(&String, &[(&String, &Uint128)])]) -> Self {
+        TokenQuerier {
+            balances: balances_to_map(balances),
+        }
+    }
+}
+
+pub(crate) fn balances_to_map(
+    balances: &[(&String, &[(&String, &Uint128)])],
+) -> HashMap<String, HashMap<String, Uint128>> {
+    let mut balances_map: HashMap<String, HashMap<String, Uint128>> = HashMap::new();
+    for (contract_addr, balances) in balances.iter() {
+        let mut contract_balances_map: HashMap<String, Uint128> = HashMap::new();
+        for (addr, balance) in balances.iter() {
+            contract_balances_map.insert(addr.to_string(), **balance);
+        }
+
+        balances_map.insert(contract_addr.to_string(), contract_balances_map);
+    }
+    balances_map
+}
+
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
         match &request {
@@ -248,17 +295,15 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
-                    QueryMsg::BorrowerInfo {
+                    QueryMsg::BorrowerLoanValue {
                         borrower,
                         block_height: _,
+                        block_time: _,
                     } => match self.loan_amount_querier.borrower_amount.get(&borrower) {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
-                            &BorrowerInfoResponse {
+                            &BorrowerLoanValueResponse {
                                 borrower,
-                                interest_index: Decimal256::one(),
-                                reward_index: Decimal256::zero(),
-                                loan_amount: *v,
-                                pending_rewards: Decimal256::zero(),
+                                loan_value: *v,
                             },
                         ))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -267,12 +312,14 @@ impl WasmMockQuerier {
                         }),
                     },
                     QueryMsg::Price { base, quote } => {
+                        let frozen = self.oracle_price_querier.frozen_assets.contains(&base);
                         match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
                             Some(v) => {
                                 SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
                                     rate: v.0,
                                     last_updated_base: v.1,
                                     last_updated_quote: v.2,
+                                    frozen,
                                 })))
                             }
                             None => SystemResult::Err(SystemError::InvalidRequest {
@@ -281,6 +328,27 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::TwapPrice {
+                        base,
+                        quote,
+                        window_seconds: _,
+                    } => {
+                        let frozen = self.oracle_price_querier.frozen_assets.contains(&base);
+                        match self.oracle_price_querier.twap_price.get(&(base, quote)) {
+                            Some(v) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                    rate: v.0,
+                                    last_updated_base: v.1,
+                                    last_updated_quote: v.2,
+                                    frozen,
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No oracle twap price exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
                     QueryMsg::LiquidationAmount {
                         borrow_amount,
                         borrow_limit,
@@ -317,6 +385,21 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&cw20::BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    QueryMsg::PauseStatus {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&self.market_pause_status)))
+                    }
                 }
             }
             _ => self.base.handle_query(request),
@@ -329,13 +412,20 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             tax_querier: TaxQuerier::default(),
+            token_querier: TokenQuerier::default(),
             epoch_state_querier: EpochStateQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
+            market_pause_status: PauseStatus::default(),
         }
     }
 
+    // configure the token owner mock querier
+    pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.token_querier = TokenQuerier::new(balances);
+    }
+
     // configure the tax mock querier
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
@@ -353,6 +443,16 @@ impl WasmMockQuerier {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn with_twap_price(&mut self, twap_price: &[(&(String, String), &(Decimal256, u64, u64))]) {
+        self.oracle_price_querier.twap_price = oracle_price_to_map(twap_price);
+    }
+
+    pub fn with_frozen_assets(&mut self, frozen_assets: &[&String]) {
+        self.oracle_price_querier.frozen_assets =
+            frozen_assets.iter().map(|asset| (*asset).clone()).collect();
+    }
+
     pub fn with_loan_amount(&mut self, loan_amount: &[(&String, &Uint256)]) {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
@@ -360,4 +460,8 @@ impl WasmMockQuerier {
     pub fn with_liquidation_percent(&mut self, liquidation_percent: &[(&String, &Decimal256)]) {
         self.liquidation_percent_querier = LiquidationPercentQuerier::new(liquidation_percent);
     }
+
+    pub fn with_market_pause_status(&mut self, market_pause_status: PauseStatus) {
+        self.market_pause_status = market_pause_status;
+    }
 }
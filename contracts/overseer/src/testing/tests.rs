@@ -1,23 +1,37 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::error::ContractError;
 use crate::querier::query_epoch_state;
-use crate::state::{read_epoch_state, store_epoch_state, EpochState};
+use crate::state::{
+    read_epoch_state, store_epoch_state, EpochState, LegacyConfig, LegacyWhitelistElem,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
-    SubMsg, Uint128, WasmMsg,
+    Deps, StdError, SubMsg, Uint128, WasmMsg,
 };
+use cosmwasm_storage::{bucket, singleton};
+use cw20::Cw20ExecuteMsg;
 
-use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::custody::{Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg};
+use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceMode;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerPositionResponse,
+    CollateralPositionResponse, CollateralsResponse, ConfigChange, ConfigResponse, ExecuteMsg,
+    InstantiateMsg, IsolatedDebtResponse, LiquidatableBorrowerResponse,
+    LiquidatableBorrowersResponse, LiquidationLimitResponse, MigrateMsg, QueryMsg, SwapPath,
+    WhitelistResponse, WhitelistResponseElem,
 };
+use moneymarket::pause::{Operation, PauseStatus};
 use moneymarket::querier::deduct_tax;
+use moneymarket::tokens::Tokens;
+use moneymarket::version::{get_contract_version, set_contract_version};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{Cw20HookMsg as TerraswapCw20HookMsg, ExecuteMsg as TerraswapExecuteMsg};
 
 use std::str::FromStr;
 
@@ -38,6 +52,8 @@ fn proper_initialization() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -62,6 +78,9 @@ fn proper_initialization() {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            price_mode: PriceMode::Spot {},
+            guardian: None,
+            min_delay: 1u64,
         }
     );
 
@@ -97,27 +116,48 @@ fn update_config() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // a new proposal replaces the pending one
     let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some("owner1".to_string()),
-        oracle_contract: None,
-        liquidation_contract: None,
-        threshold_deposit_rate: None,
-        target_deposit_rate: None,
-        buffer_distribution_factor: None,
-        anc_purchase_factor: None,
-        epoch_period: None,
-        price_timeframe: None,
-    };
+    for owner in ["owner2", "owner1"] {
+        let msg = ExecuteMsg::ProposeNewOwner {
+            owner: owner.to_string(),
+            expires_in: 100u64,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Sender is not the proposed owner")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner1", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "claim_ownership"), attr("owner", "owner1")]
+    );
 
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
@@ -127,7 +167,6 @@ fn update_config() {
     // update left items
     let info = mock_info("owner1", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         oracle_contract: Some("oracle1".to_string()),
         liquidation_contract: Some("liquidation1".to_string()),
         threshold_deposit_rate: Some(Decimal256::permille(1)),
@@ -136,6 +175,10 @@ fn update_config() {
         anc_purchase_factor: Some(Decimal256::percent(10)),
         epoch_period: Some(100000u64),
         price_timeframe: Some(120u64),
+        price_mode: Some(PriceMode::Twap {
+            window_seconds: 600u64,
+        }),
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -156,11 +199,16 @@ fn update_config() {
     assert_eq!(Decimal256::percent(10), config_res.anc_purchase_factor);
     assert_eq!(100000u64, config_res.epoch_period);
     assert_eq!(120u64, config_res.price_timeframe);
+    assert_eq!(
+        PriceMode::Twap {
+            window_seconds: 600u64
+        },
+        config_res.price_mode
+    );
 
     // Unauthorized err
     let info = mock_info("owner", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         oracle_contract: None,
         liquidation_contract: None,
         threshold_deposit_rate: None,
@@ -169,6 +217,8 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        price_mode: None,
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -196,6 +246,8 @@ fn whitelist() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -207,6 +259,10 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -216,7 +272,22 @@ fn whitelist() {
         _ => panic!("DO NOT ENTER HERE"),
     };
 
+    // Liquidation threshold cannot be lower than max LTV
+    let mut invalid_msg = msg.clone();
+    if let ExecuteMsg::Whitelist {
+        ref mut liquidation_threshold,
+        ..
+    } = invalid_msg
+    {
+        *liquidation_threshold = Decimal256::percent(50);
+    }
     let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg);
+    match res {
+        Err(ContractError::InvalidLiquidationThreshold {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    };
+
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
@@ -227,6 +298,8 @@ fn whitelist() {
             attr("collateral_token", "bluna"),
             attr("custody_contract", "custody"),
             attr("LTV", "0.6"),
+            attr("liquidation_threshold", "0.7"),
+            attr("max_total_locked", "100000000000"),
         ]
     );
 
@@ -250,6 +323,13 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody".to_string(),
                 max_ltv: Decimal256::percent(60),
+                liquidation_threshold: Decimal256::percent(70),
+                swap_path: SwapPath::LiquidationQueue {},
+                max_total_locked: Some(Uint256::from(100000000000u64)),
+                max_borrower_locked: None,
+                total_locked: Uint256::zero(),
+                debt_ceiling: None,
+                isolated_debt: Uint256::zero(),
             }]
         }
     );
@@ -261,6 +341,10 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -270,10 +354,21 @@ fn whitelist() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let msg = ExecuteMsg::UpdateWhitelist {
-        collateral_token: "bluna".to_string(),
-        custody_contract: Some("custody2".to_string()),
-        max_ltv: Some(Decimal256::percent(30)),
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: "bluna".to_string(),
+            custody_contract: Some("custody2".to_string()),
+            max_ltv: Some(Decimal256::percent(30)),
+            liquidation_threshold: None,
+            swap_path: Some(SwapPath::Terraswap {
+                pair_contract: "pair".to_string(),
+                max_spread: Some(Decimal::percent(1)),
+            }),
+            max_total_locked: None,
+            max_borrower_locked: None,
+            debt_ceiling: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -284,7 +379,13 @@ fn whitelist() {
     };
 
     let info = mock_info("owner", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let msg = ExecuteMsg::ExecuteChange { id: 1u64 };
+    let res = execute(deps.as_mut(), delayed_env, info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
@@ -292,6 +393,9 @@ fn whitelist() {
             attr("collateral_token", "bluna"),
             attr("custody_contract", "custody2"),
             attr("LTV", "0.3"),
+            attr("liquidation_threshold", "0.7"),
+            attr("action", "execute_change"),
+            attr("id", "1"),
         ]
     );
 
@@ -315,9 +419,43 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody2".to_string(),
                 max_ltv: Decimal256::percent(30),
+                liquidation_threshold: Decimal256::percent(70),
+                swap_path: SwapPath::Terraswap {
+                    pair_contract: "pair".to_string(),
+                    max_spread: Some(Decimal::percent(1)),
+                },
+                max_total_locked: Some(Uint256::from(100000000000u64)),
+                max_borrower_locked: None,
+                total_locked: Uint256::zero(),
+                debt_ceiling: None,
+                isolated_debt: Uint256::zero(),
             }]
         }
     );
+
+    // Max LTV cannot exceed the liquidation threshold
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: "bluna".to_string(),
+            custody_contract: None,
+            max_ltv: Some(Decimal256::percent(80)),
+            liquidation_threshold: None,
+            swap_path: None,
+            max_total_locked: None,
+            max_borrower_locked: None,
+            debt_ceiling: None,
+        },
+        eta: mock_env().block.time.seconds() + 1u64,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let mut delayed_env = mock_env();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let msg = ExecuteMsg::ExecuteChange { id: 2u64 };
+    let res = execute(deps.as_mut(), delayed_env, mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::InvalidLiquidationThreshold {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    };
 }
 
 #[test]
@@ -342,6 +480,8 @@ fn execute_epoch_operations() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -372,6 +512,10 @@ fn execute_epoch_operations() {
         collateral_token: bluna_collat_token,
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -382,6 +526,10 @@ fn execute_epoch_operations() {
         collateral_token: batom_collat_token,
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -566,6 +714,8 @@ fn update_epoch_state() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -578,6 +728,10 @@ fn update_epoch_state() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -588,6 +742,10 @@ fn update_epoch_state() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -712,6 +870,8 @@ fn lock_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -742,6 +902,10 @@ fn lock_collateral() {
         collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -752,6 +916,10 @@ fn lock_collateral() {
         collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -848,7 +1016,7 @@ fn lock_collateral() {
 }
 
 #[test]
-fn unlock_collateral() {
+fn collateral_caps() {
     let mut deps = mock_dependencies(&[]);
 
     let info = mock_info("owner", &[]);
@@ -866,200 +1034,156 @@ fn unlock_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(3000000u64),
+        max_borrower_locked: Some(Uint256::from(2000000u64)),
+        debt_ceiling: None,
     };
-
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-
-    let msg = ExecuteMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: "batom".to_string(),
-        custody_contract: "custody_batom".to_string(),
-        max_ltv: Decimal256::percent(60),
-    };
-
-    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let msg = ExecuteMsg::LockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000000u64)),
-            ("batom".to_string(), Uint256::from(10000000u64)),
-        ],
+        collaterals: vec![("bluna".to_string(), Uint256::from(1500000u64))],
     };
-    let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
 
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000001u64)),
-            ("batom".to_string(), Uint256::from(10000001u64)),
-        ],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    // addr0000 would lock 3,000,000
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(ContractError::UnlockExceedsLocked {}) => (),
+        Err(ContractError::BorrowerLockedCapExceeded(2000000u128)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier.with_oracle_price(&[
-        (
-            &("bluna".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(1000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
-            ),
-        ),
-        (
-            &("batom".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(2000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
-            ),
-        ),
-    ]);
-
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap();
 
-    // cannot unlock any tokens
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    // all borrowers would lock 4,500,000
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(ContractError::UnlockTooLarge(12599999400)) => (),
+        Err(ContractError::TotalLockedCapExceeded(3000000u128)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("batom".to_string(), Uint256::one())],
+    let query_total_locked = |deps: Deps| -> Uint256 {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::Whitelist {
+                collateral_token: Some("bluna".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+        whitelist_res.elems[0].total_locked
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
+    assert_eq!(query_total_locked(deps.as_ref()), Uint256::from(3000000u64));
+
+    // unlocked collaterals free up the cap
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
     deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
-    let res = query(
-        deps.as_ref(),
+        .with_loan_amount(&[(&"addr0001".to_string(), &Uint256::zero())]);
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
         env.clone(),
-        QueryMsg::BorrowLimit {
-            borrower: "addr0000".to_string(),
-            block_time: None,
-        },
+        mock_info("addr0001", &[]),
+        unlock_msg,
     )
     .unwrap();
-    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
-    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
-
-    // Cannot unlock 2bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(query_total_locked(deps.as_ref()), Uint256::from(2000000u64));
 
-    // Can unlock 1bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "custody_bluna".to_string(),
-            funds: vec![],
-            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                borrower: "addr0000".to_string(),
-                amount: Uint256::one(),
-            })
-            .unwrap(),
-        }))]
-    );
-
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "unlock_collateral"),
-            attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna"),
-        ]
-    );
-
-    //testing for unlocking more collaterals
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
-
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1u128)),
-            ("batom".to_string(), Uint256::from(1u128)),
-        ],
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(query_total_locked(deps.as_ref()), Uint256::from(3000000u64));
+
+    // the owner can raise the caps
+    let update_msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: "bluna".to_string(),
+            custody_contract: None,
+            max_ltv: None,
+            liquidation_threshold: None,
+            swap_path: None,
+            max_total_locked: Some(Uint256::from(10000000u64)),
+            max_borrower_locked: Some(Uint256::from(5000000u64)),
+            debt_ceiling: None,
+        },
+        eta: env.block.time.seconds() + 1u64,
     };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
-                })
-                .unwrap(),
-            }))
-        ]
-    );
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "unlock_collateral"),
-            attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna,1batom"),
-        ]
-    );
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_msg,
+    )
+    .unwrap();
+    let mut delayed_env = env.clone();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(query_total_locked(deps.as_ref()), Uint256::from(4000000u64));
 }
 
 #[test]
-fn liquidate_collateral() {
+fn isolated_collateral() {
     let mut deps = mock_dependencies(&[]);
-    deps.querier
-        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
 
     let info = mock_info("owner", &[]);
     let env = mock_env();
@@ -1076,49 +1200,1661 @@ fn liquidate_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
     };
 
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let batom_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
-    let bluna_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
-    // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
-        collateral_token: bluna_collat_token.clone(),
+        collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
     };
-
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
     let msg = ExecuteMsg::Whitelist {
         name: "batom".to_string(),
         symbol: "batom".to_string(),
-        collateral_token: batom_collat_token.clone(),
+        collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: Some(Uint256::from(1000000u64)),
     };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(10000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(10000000u64)),
+            ("batom".to_string(), Uint256::from(10u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    let query_borrow_limit = |deps: Deps, borrower: &str| -> Uint256 {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::BorrowLimit {
+                borrower: borrower.to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+        borrow_limit_res.borrow_limit
+    };
+
+    // 10,000,000 * 0.6
+    assert_eq!(
+        query_borrow_limit(deps.as_ref(), "addr0000"),
+        Uint256::from(6000000u64)
+    );
+
+    // the debt ceiling caps the loans of all borrowers, not the borrow limit
+    assert_eq!(
+        query_borrow_limit(deps.as_ref(), "addr0001"),
+        Uint256::from(6000006u64)
+    );
+
+    let query_isolated_debt = |deps: Deps, borrower: &str| -> IsolatedDebtResponse {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::IsolatedDebt {
+                borrower: borrower.to_string(),
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+    let query_batom_debt = |deps: Deps| -> Uint256 {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::Whitelist {
+                collateral_token: Some("batom".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+        whitelist_res.elems[0].isolated_debt
+    };
+
+    assert_eq!(
+        query_isolated_debt(deps.as_ref(), "addr0001"),
+        IsolatedDebtResponse {
+            borrower: "addr0001".to_string(),
+            isolated_collaterals: vec!["batom".to_string()],
+            debt: Uint256::zero(),
+        }
+    );
+
+    let increase_msg = |borrower: &str| ExecuteMsg::IncreaseIsolatedDebt {
+        borrower: borrower.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        increase_msg("addr0001"),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the whole live loan of the borrower is charged
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(700000u64)),
+        (&"addr0001".to_string(), &Uint256::from(600000u64)),
+    ]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("market", &[]),
+        increase_msg("addr0001"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "increase_isolated_debt"),
+            attr("borrower", "addr0001"),
+            attr("loan_value", "600000"),
+        ]
+    );
+
+    // loans of borrowers without isolated collaterals are not counted
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("market", &[]),
+        increase_msg("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("loan_value", "0"));
+    assert_eq!(query_batom_debt(deps.as_ref()), Uint256::from(600000u64));
+
+    // an existing loan is charged when locking an isolated collateral
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(10u64))],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::DebtCeilingReached(1000000u128)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(400000u64)),
+        (&"addr0001".to_string(), &Uint256::from(600001u64)),
+    ]);
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(query_batom_debt(deps.as_ref()), Uint256::from(1000000u64));
+
+    // accrued interest is counted on the next borrow
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("market", &[]),
+        increase_msg("addr0001"),
+    );
+    match res {
+        Err(ContractError::DebtCeilingReached(1000000u128)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // repayments charge the remaining loan including interest
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(400000u64)),
+        (&"addr0001".to_string(), &Uint256::from(100000u64)),
+    ]);
+    let msg = ExecuteMsg::DecreaseIsolatedDebt {
+        borrower: "addr0001".to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+    assert_eq!(query_batom_debt(deps.as_ref()), Uint256::from(500000u64));
+    assert_eq!(
+        query_isolated_debt(deps.as_ref(), "addr0001").debt,
+        Uint256::from(100000u64)
+    );
+    assert_eq!(
+        query_isolated_debt(deps.as_ref(), "addr0000").debt,
+        Uint256::from(400000u64)
+    );
+
+    // unlocking the isolated collateral releases the loan of the borrower
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(10u64))],
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(query_batom_debt(deps.as_ref()), Uint256::from(100000u64));
+    assert_eq!(
+        query_isolated_debt(deps.as_ref(), "addr0000"),
+        IsolatedDebtResponse {
+            borrower: "addr0000".to_string(),
+            isolated_collaterals: vec![],
+            debt: Uint256::zero(),
+        }
+    );
+}
+
+#[test]
+fn pause_operations() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_mode: None,
+        guardian: Some("guardian".to_string()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Lock],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause"), attr("operations", "lock")]
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: lock")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // leverage loops lock the bought collateral
+    let msg = ExecuteMsg::LeverageLoop {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        iterations: 2,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: lock")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ContinueLeverageLoop {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        iterations: 1,
+        prev_balance: Uint256::zero(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: lock")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // liquidations cannot be paused while the market lends,
+    // whatever the borrow status of the overseer
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Borrow, Operation::Liquidate],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "Market borrow must be paused while repay or liquidate is paused"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_market_pause_status(PauseStatus {
+        borrow: true,
+        ..PauseStatus::default()
+    });
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatus = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            borrow: true,
+            lock: true,
+            liquidate: true,
+            ..PauseStatus::default()
+        }
+    );
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Operation is paused: liquidate")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the owner can resume operations as well
+    let msg = ExecuteMsg::Unpause {
+        operations: vec![Operation::Lock, Operation::Liquidate, Operation::Borrow],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unpause"),
+            attr("operations", "lock,liquidate,borrow")
+        ]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatus = from_binary(&res).unwrap();
+    assert_eq!(pause_status, PauseStatus::default());
+
+    // an empty address removes the guardian
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_mode: None,
+        guardian: Some("".to_string()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Pause {
+        operations: vec![Operation::Lock],
+    };
+    match execute(deps.as_mut(), env, mock_info("guardian", &[]), msg) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn unlock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000001u64)),
+            ("batom".to_string(), Uint256::from(10000001u64)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    // cannot unlock any tokens
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599999400)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+
+    // Cannot unlock 2bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Can unlock 1bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::one(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna"),
+        ]
+    );
+
+    //testing for unlocking more collaterals
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1u128)),
+            ("batom".to_string(), Uint256::from(1u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna,1batom"),
+        ]
+    );
+}
+
+#[test]
+fn liquidate_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &(batom_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // loans between the borrow limit and the liquidation limit are safe
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // liquidation_limit = 1000 * 1000000 * 0.7 + 2000 * 10000000 * 0.7
+    // = 14,700,000,000 uusd
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidationLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let liquidation_limit_res: LiquidationLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        liquidation_limit_res.liquidation_limit,
+        Uint256::from(14700000000u64)
+    );
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(14700000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token, Uint256::from(9900000u64)),
+                (bluna_collat_token, Uint256::from(990000u64)),
+            ]
+        }
+    );
+}
+
+#[test]
+fn repay_with_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: "batom".to_string(),
+            custody_contract: None,
+            max_ltv: None,
+            liquidation_threshold: None,
+            swap_path: Some(SwapPath::Terraswap {
+                pair_contract: "pair".to_string(),
+                max_spread: Some(Decimal::percent(1)),
+            }),
+            max_total_locked: None,
+            max_borrower_locked: None,
+            debt_ceiling: None,
+        },
+        eta: env.block.time.seconds() + 1u64,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let mut delayed_env = env.clone();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let msg = ExecuteMsg::ExecuteChange { id: 1u64 };
+    let _res = execute(deps.as_mut(), delayed_env, mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Failed to repay with more than locked amount
+    let msg = ExecuteMsg::RepayWithCollateral {
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(1000001u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Sold to the liquidation contract by default
+    let msg = ExecuteMsg::RepayWithCollateral {
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(100u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SwapCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100u64),
+                    swap_contract: "liquidation".to_string(),
+                    swap_msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                        liquidator: "addr0000".to_string(),
+                        fee_address: Some(MOCK_CONTRACT_ADDR.to_string()),
+                        repay_address: Some("market".to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertBorrowerHealth {
+                    borrower: "addr0000".to_string(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_with_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collateral_token", "bluna"),
+            attr("amount", "100"),
+        ]
+    );
+
+    // Sold on the whitelisted terraswap pair
+    let msg = ExecuteMsg::RepayWithCollateral {
+        collateral_token: "batom".to_string(),
+        amount: Uint256::from(1000u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_batom".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::SwapCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000u64),
+                swap_contract: "pair".to_string(),
+                swap_msg: to_binary(&TerraswapCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: Some(Decimal::percent(1)),
+                    to: Some("market".to_string()),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        }))
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                ("bluna".to_string(), Uint256::from(999900u64)),
+                ("batom".to_string(), Uint256::from(9999000u64)),
+            ]
+        }
+    );
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 999900 * 0.6 + 2000 * 9999000 * 0.6
+    // = 12,598,740,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12598740001u64))]);
+
+    // Only the contract itself can assert the borrower health
+    let msg = ExecuteMsg::AssertBorrowerHealth {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::RepayWithCollateralTooLarge(12598740000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12598740000u64))]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "assert_borrower_health"),
+            attr("borrower", "addr0000"),
+        ]
+    );
+}
+
+#[test]
+fn native_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // native collaterals are whitelisted by their denom
+    let msg = ExecuteMsg::Whitelist {
+        name: "atom".to_string(),
+        symbol: "ATOM".to_string(),
+        collateral_token: ibc_denom.to_string(),
+        custody_contract: "custody_atom".to_string(),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: ibc_denom.to_string(),
+            custody_contract: None,
+            max_ltv: None,
+            liquidation_threshold: None,
+            swap_path: Some(SwapPath::Terraswap {
+                pair_contract: "pair".to_string(),
+                max_spread: None,
+            }),
+            max_total_locked: None,
+            max_borrower_locked: None,
+            debt_ceiling: None,
+        },
+        eta: env.block.time.seconds() + 1u64,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let mut delayed_env = env.clone();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let msg = ExecuteMsg::ExecuteChange { id: 1u64 };
+    let _res = execute(deps.as_mut(), delayed_env, mock_info("addr0000", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Whitelist {
+            collateral_token: Some(ibc_denom.to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res.elems[0].collateral_token,
+        ibc_denom.to_string()
+    );
+    assert_eq!(
+        whitelist_res.elems[0].custody_contract,
+        "custody_atom".to_string()
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(ibc_denom.to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_atom".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // native collaterals are priced by their denom
+    deps.querier.with_oracle_price(&[(
+        &(ibc_denom.to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(5000000u64));
+
+    // native collateral is offered to the pair along with the swap
+    let msg = ExecuteMsg::RepayWithCollateral {
+        collateral_token: ibc_denom.to_string(),
+        amount: Uint256::from(1000u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_atom".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::SwapCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000u64),
+                swap_contract: "pair".to_string(),
+                swap_msg: to_binary(&TerraswapExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ibc_denom.to_string(),
+                        },
+                        amount: Uint128::from(1000u128),
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some("market".to_string()),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        }))
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![(ibc_denom.to_string(), Uint256::from(999000u64))]
+    );
+}
+
+#[test]
+fn leverage_loop() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let borrower_info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), borrower_info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    // Native collateral cannot be deposited on behalf of the borrower
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower_info.clone(),
+        ExecuteMsg::LeverageLoop {
+            collateral_token:
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string(),
+            target_ltv: Decimal256::percent(50),
+            iterations: 2,
+        },
+    );
+    match res {
+        Err(ContractError::NativeCollateralNotSupported {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Collateral cannot be bought from the liquidation contract
+    let msg = ExecuteMsg::LeverageLoop {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        iterations: 2,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower_info.clone(),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::SwapPathNotSupported {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_msg = ExecuteMsg::ScheduleChange {
+        change: ConfigChange::UpdateWhitelist {
+            collateral_token: "bluna".to_string(),
+            custody_contract: None,
+            max_ltv: None,
+            liquidation_threshold: None,
+            swap_path: Some(SwapPath::Terraswap {
+                pair_contract: "pair".to_string(),
+                max_spread: None,
+            }),
+            max_total_locked: None,
+            max_borrower_locked: None,
+            debt_ceiling: None,
+        },
+        eta: env.block.time.seconds() + 1u64,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, update_msg).unwrap();
+    let mut delayed_env = env.clone();
+    delayed_env.block.time = delayed_env.block.time.plus_seconds(1u64);
+    let _res = execute(
+        deps.as_mut(),
+        delayed_env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteChange { id: 1u64 },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        borrower_info.clone(),
+        ExecuteMsg::LeverageLoop {
+            collateral_token: "bluna".to_string(),
+            target_ltv: Decimal256::percent(70),
+            iterations: 2,
+        },
+    );
+    match res {
+        Err(ContractError::TargetLtvExceedsMaxLtv(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // target_loan = 1000 * 1000000 * 0.5 = 500,000,000 uusd
+    let res = execute(deps.as_mut(), env.clone(), borrower_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(500000000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000000u128),
+                }],
+                msg: to_binary(&TerraswapExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: Uint128::from(500000000u128),
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ContinueLeverageLoop {
+                    borrower: "addr0000".to_string(),
+                    collateral_token: "bluna".to_string(),
+                    target_ltv: Decimal256::percent(50),
+                    iterations: 1,
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    // 400,000 bluna bought with the borrowed amount
+    deps.querier.with_token_balances(&[(
+        &"bluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(400000u128))],
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(500000000u64))]);
+
+    let msg = ExecuteMsg::ContinueLeverageLoop {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        iterations: 1,
+        prev_balance: Uint256::zero(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // target_loan = 1000 * 1400000 * 0.5 = 700,000,000 uusd
+    let contract_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), env.clone(), contract_info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0..3],
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "custody_bluna".to_string(),
+                    amount: Uint128::from(400000u128),
+                    msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some("addr0000".to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(400000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(200000000u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.messages[4],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::ContinueLeverageLoop {
+                borrower: "addr0000".to_string(),
+                collateral_token: "bluna".to_string(),
+                target_ltv: Decimal256::percent(50),
+                iterations: 0,
+                prev_balance: Uint256::from(400000u64),
+            })
+            .unwrap(),
+        }))
+    );
+
+    // The loop ends with the borrower health check
+    let msg = ExecuteMsg::ContinueLeverageLoop {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        iterations: 0,
+        prev_balance: Uint256::zero(),
+    };
+    let res = execute(deps.as_mut(), env, contract_info, msg).unwrap();
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::AssertBorrowerHealth {
+                borrower: "addr0000".to_string(),
+            })
+            .unwrap(),
+        }))
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(1800000u64))]
+    );
+}
+
+#[test]
+fn borrower_position() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
 
-    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(60),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let msg = ExecuteMsg::LockCollateral {
         collaterals: vec![
@@ -1147,77 +2883,389 @@ fn liquidate_collateral() {
             ),
         ),
     ]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
+    let query_position = |deps: Deps| -> BorrowerPositionResponse {
+        let res = query(
+            deps,
+            env.clone(),
+            QueryMsg::BorrowerPosition {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    // no loan, no health factor
+    let position_res = query_position(deps.as_ref());
+    assert_eq!(position_res.health_factor, None);
+    assert_eq!(
+        position_res.collaterals[0].liquidation_price_drop,
+        Decimal256::one()
+    );
+
+    // borrow_limit = 2000 * 10000000 * 0.5 + 1000 * 1000000 * 0.6
+    // = 10,600,000,000 uusd
+    // liquidation_limit = 2000 * 10000000 * 0.6 + 1000 * 1000000 * 0.7
+    // = 12,700,000,000 uusd
     deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(10160000000u64))]);
+    let position_res = query_position(deps.as_ref());
+    assert_eq!(
+        position_res,
+        BorrowerPositionResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                CollateralPositionResponse {
+                    collateral_token: batom_collat_token,
+                    amount: Uint256::from(10000000u64),
+                    price: Decimal256::from_ratio(2000u64, 1u64),
+                    frozen: false,
+                    value: Uint256::from(20000000000u64),
+                    max_ltv: Decimal256::percent(50),
+                    borrow_limit: Uint256::from(10000000000u64),
+                    liquidation_threshold: Decimal256::percent(60),
+                    // (12,700,000,000 - 10,160,000,000) / 12,000,000,000
+                    liquidation_price_drop: Decimal256::from_ratio(254u64, 1200u64),
+                },
+                CollateralPositionResponse {
+                    collateral_token: bluna_collat_token.clone(),
+                    amount: Uint256::from(1000000u64),
+                    price: Decimal256::from_ratio(1000u64, 1u64),
+                    frozen: false,
+                    value: Uint256::from(1000000000u64),
+                    max_ltv: Decimal256::percent(60),
+                    borrow_limit: Uint256::from(600000000u64),
+                    liquidation_threshold: Decimal256::percent(70),
+                    // the buffer exceeds the whole bluna liquidation value
+                    liquidation_price_drop: Decimal256::one(),
+                },
+            ],
+            borrow_limit: Uint256::from(10600000000u64),
+            liquidation_limit: Uint256::from(12700000000u64),
+            loan_value: Uint256::from(10160000000u64),
+            health_factor: Some(Decimal256::from_ratio(125u64, 100u64)),
+        }
+    );
+
+    // liquidatable positions have no price drop left
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12700000001u64))]);
+    let position_res = query_position(deps.as_ref());
+    assert_eq!(
+        position_res.collaterals[0].liquidation_price_drop,
+        Decimal256::zero()
+    );
+    assert_eq!(
+        position_res.collaterals[1].liquidation_price_drop,
+        Decimal256::zero()
+    );
+
+    // frozen prices only exclude their own share from new borrows
+    deps.querier.with_frozen_assets(&[&bluna_collat_token]);
+    let position_res = query_position(deps.as_ref());
+    assert!(position_res.collaterals[1].frozen);
+    assert_eq!(
+        position_res.liquidation_limit,
+        Uint256::from(12700000000u64)
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(10000000000u64));
+}
+
+#[test]
+fn liquidatable_borrowers() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        max_total_locked: Uint256::from(100000000000u64),
+        max_borrower_locked: None,
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001", "addr0002"] {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    // liquidation_limit = 1000 * 1000000 * 0.7 = 700,000,000 uusd
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(650000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(700000001u64)),
+        (&"addr0002".to_string(), &Uint256::zero()),
+    ]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: None,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    let first_page: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+    assert!(first_page.last_borrower.is_some());
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: first_page.last_borrower,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    let second_page: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+
+    let borrowers: Vec<LiquidatableBorrowerResponse> = first_page
+        .borrowers
+        .into_iter()
+        .chain(second_page.borrowers)
+        .collect();
+    let expected = vec![LiquidatableBorrowerResponse {
+        borrower: "addr0001".to_string(),
+        loan_value: Uint256::from(700000001u64),
+        borrow_limit: Uint256::from(600000000u64),
+        liquidation_limit: Uint256::from(700000000u64),
+        liquidation_amount: vec![("bluna".to_string(), Uint256::from(10000u64))],
+    }];
+    assert_eq!(borrowers, expected);
+
+    // frozen prices do not shield positions from liquidation
+    deps.querier.with_frozen_assets(&[&"bluna".to_string()]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let res: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+    assert_eq!(res.borrowers, expected);
 
     let msg = ExecuteMsg::LiquidateCollateral {
-        borrower: "addr0000".to_string(),
+        borrower: "addr0001".to_string(),
     };
-    let info = mock_info("addr0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _res = execute(deps.as_mut(), env, mock_info("addr0003", &[]), msg).unwrap();
+}
+
+#[test]
+fn migrate_legacy_whitelist() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        price_mode: PriceMode::Spot {},
+        min_delay: 1u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 0.3.x whitelist without liquidation threshold
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.3.1").unwrap();
+    let legacy_elem = LegacyWhitelistElem {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        custody_contract: deps.api.addr_canonicalize("custody").unwrap(),
+    };
+    let collateral_token_raw = deps.api.addr_canonicalize("bluna").unwrap();
+    bucket(deps.as_mut().storage, b"whitelist")
+        .save(collateral_token_raw.as_slice(), &legacy_elem)
+        .unwrap();
+
+    // 0.3.x config without price mode
+    let legacy_config = LegacyConfig {
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        oracle_contract: deps.api.addr_canonicalize("oracle").unwrap(),
+        market_contract: deps.api.addr_canonicalize("market").unwrap(),
+        liquidation_contract: deps.api.addr_canonicalize("liquidation").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+    };
+    singleton(deps.as_mut().storage, b"config")
+        .save(&legacy_config)
+        .unwrap();
+
+    // locked totals are summed from the borrower collaterals
+    for (borrower, amount) in [("addr0000", 1000000u64), ("addr0001", 2000000u64)] {
+        let collaterals: Tokens = vec![(collateral_token_raw.clone(), Uint256::from(amount))];
+        let borrower_raw = deps.api.addr_canonicalize(borrower).unwrap();
+        bucket(deps.as_mut().storage, b"collateral")
+            .save(borrower_raw.as_slice(), &collaterals)
+            .unwrap();
+    }
+
+    let _res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            min_delay: 86400u64,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        CONTRACT_VERSION.to_string()
+    );
+
+    // collaterals are frozen until the totals are summed
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
     match res {
-        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "Locked totals of the legacy overseer are being migrated"
+        ),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg = ExecuteMsg::MigrateTotalLocked { limit: Some(1u32) };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        res.messages,
+        res.attributes,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(100000u64),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(10000u64),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "market".to_string(),
-                funds: vec![],
-                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
-                    borrower: "addr0000".to_string(),
-                    prev_balance: Uint256::zero(),
-                })
-                .unwrap(),
-            }))
+            attr("action", "migrate_total_locked"),
+            attr("completed", "false"),
         ]
     );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_total_locked"),
+            attr("completed", "true"),
+        ]
+    );
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0002", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Locked totals are already migrated")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     let res = query(
         deps.as_ref(),
-        env,
-        QueryMsg::Collaterals {
-            borrower: "addr0000".to_string(),
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: None,
+            start_after: None,
+            limit: None,
         },
     )
     .unwrap();
-    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
     assert_eq!(
-        collaterals_res,
-        CollateralsResponse {
-            borrower: "addr0000".to_string(),
-            collaterals: vec![
-                (batom_collat_token, Uint256::from(9900000u64)),
-                (bluna_collat_token, Uint256::from(990000u64)),
-            ]
-        }
+        whitelist_res.elems,
+        vec![WhitelistResponseElem {
+            name: "bluna".to_string(),
+            symbol: "bluna".to_string(),
+            collateral_token: "bluna".to_string(),
+            custody_contract: "custody".to_string(),
+            max_ltv: Decimal256::percent(60),
+            liquidation_threshold: Decimal256::percent(60),
+            swap_path: SwapPath::LiquidationQueue {},
+            max_total_locked: None,
+            max_borrower_locked: None,
+            total_locked: Uint256::from(3000000u64),
+            debt_ceiling: None,
+            isolated_debt: Uint256::zero(),
+        }]
     );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(PriceMode::Spot {}, config_res.price_mode);
+    assert_eq!(60u64, config_res.price_timeframe);
 }
@@ -2,20 +2,23 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::Binary;
 use cw20::Cw20ReceiveMsg;
 
+use crate::pause::Operation;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
     /// owner address
     pub owner: String,
-    /// bAsset token address
+    /// bAsset token address or native denom
     pub collateral_token: String,
     /// overseer contract address
     pub overseer_contract: String,
     /// market contract address
     pub market_contract: String,
-    /// bAsset rewrad contract
+    /// bAsset rewrad contract, unused by the `none` adapter
     pub reward_contract: String,
     /// liquidation contract address
     pub liquidation_contract: String,
@@ -23,6 +26,8 @@ pub struct InstantiateMsg {
     /// it, we try to convert the reward to the `stable_denom`.
     pub stable_denom: String,
     pub basset_info: BAssetInfo,
+    /// How rewards of the collateral are claimed
+    pub reward_adapter: RewardAdapter,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,9 +42,24 @@ pub enum ExecuteMsg {
 
     /// Update config
     UpdateConfig {
-        owner: Option<String>,
         liquidation_contract: Option<String>,
+        /// Address allowed to pause operations, empty to remove it
+        guardian: Option<String>,
     },
+
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner { owner: String, expires_in: u64 },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+
+    /// Guardian or owner operation to pause operations
+    Pause { operations: Vec<Operation> },
+    /// Guardian or owner operation to resume paused operations
+    Unpause { operations: Vec<Operation> },
+
     /// Make specified amount of tokens unspendable
     LockCollateral { borrower: String, amount: Uint256 },
     /// Make specified amount of collateral tokens spendable
@@ -55,10 +75,22 @@ pub enum ExecuteMsg {
         amount: Uint256,
     },
 
+    /// Send locked collateral to `swap_contract` with `swap_msg`
+    /// as the cw20 hook, to sell it for the borrower repayment
+    SwapCollateral {
+        borrower: String,
+        amount: Uint256,
+        swap_contract: String,
+        swap_msg: Binary,
+    },
+
     ////////////////////
     /// User operations
     ////////////////////
 
+    /// Deposit native collateral sent with the message
+    DepositCollateral {},
+
     /// Withdraw spendable collateral token.
     /// If the amount is not given,
     /// return all spendable collateral
@@ -68,14 +100,17 @@ pub enum ExecuteMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    /// Deposit collateral token
-    DepositCollateral {},
+    /// Deposit collateral token,
+    /// `borrower` defaults to the token sender
+    DepositCollateral { borrower: Option<String> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Paused operations of the contract
+    PauseStatus {},
     Borrower {
         address: String,
     },
@@ -85,6 +120,27 @@ pub enum QueryMsg {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Adapter of a bLuna or bETH custody deployed
+    /// before contract version records were introduced
+    pub reward_adapter: Option<RewardAdapter>,
+}
+
+/// Reward contract interface of the collateral
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardAdapter {
+    /// Rewards claimed from a bLuna reward contract
+    #[serde(rename = "bluna")]
+    BLuna {},
+    /// Rewards claimed from a bETH reward contract
+    #[serde(rename = "beth")]
+    BEth {},
+    /// Collateral without rewards, `DistributeRewards` is a no-op
+    None {},
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
@@ -96,6 +152,8 @@ pub struct ConfigResponse {
     pub liquidation_contract: String,
     pub stable_denom: String,
     pub basset_info: BAssetInfo,
+    pub reward_adapter: RewardAdapter,
+    pub guardian: Option<String>,
 }
 
 // We define a custom struct for each query response
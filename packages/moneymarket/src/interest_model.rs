@@ -3,20 +3,90 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 
+use crate::timelock;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner: String,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
+    pub model: InterestModel,
+    /// Market pushing the epoch operations, required by the adaptive model
+    pub market_contract: Option<String>,
+    /// Minimum delay of queued changes in seconds
+    pub min_delay: u64,
+}
+
+/// Borrow rate curve applied on top of `base_rate` and `interest_multiplier`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InterestModel {
+    /// borrow_rate = utilization_ratio * interest_multiplier + base_rate
+    Linear {},
+    /// Linear up to `kink_utilization`, the utilization above the kink
+    /// is charged with `jump_multiplier` instead of `interest_multiplier`
+    Kinked {
+        kink_utilization: Decimal256,
+        jump_multiplier: Decimal256,
+    },
+    /// Interest multiplier is adjusted every epoch to steer utilization
+    /// towards `target_utilization`, the borrow rate is kept within
+    /// `min_rate` and `max_rate`
+    Adaptive {
+        target_utilization: Decimal256,
+        min_rate: Decimal256,
+        max_rate: Decimal256,
+        /// rate change per unit of utilization error, applied immediately
+        proportional_gain: Decimal256,
+        /// interest multiplier change per unit of utilization error, applied every epoch
+        integral_gain: Decimal256,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Owner operation to queue a change, executable from
+    /// `eta` which is at least `min_delay` seconds ahead
+    ScheduleChange { change: ConfigChange, eta: u64 },
+    /// Apply a queued change once its ETA has passed
+    ExecuteChange { id: u64 },
+    /// Owner operation to cancel a queued change
+    CancelChange { id: u64 },
+
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner { owner: String, expires_in: u64 },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+
+    ////////////////////
+    /// Market operations
+    ////////////////////
+    /// Update adaptive model state with the market
+    /// status of the epoch
+    ExecuteEpochOperations {
+        market_balance: Uint256,
+        total_liabilities: Decimal256,
+        total_reserves: Decimal256,
+    },
+}
+
+/// Owner changes applied through the timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum ConfigChange {
     UpdateConfig {
-        owner: Option<String>,
         base_rate: Option<Decimal256>,
         interest_multiplier: Option<Decimal256>,
+        model: Option<InterestModel>,
+        market_contract: Option<String>,
+    },
+    UpdateMinDelay {
+        min_delay: u64,
     },
 }
 
@@ -24,6 +94,12 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Changes queued through the timelock
+    PendingChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    State {},
     BorrowRate {
         market_balance: Uint256,
         total_liabilities: Decimal256,
@@ -31,12 +107,29 @@ pub enum QueryMsg {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Timelock delay of config changes, in seconds
+    pub min_delay: u64,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
+    pub model: InterestModel,
+    pub market_contract: Option<String>,
+    pub min_delay: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub interest_multiplier: Decimal256,
+    pub utilization_ratio: Decimal256,
+    pub last_updated: u64,
 }
 
 // We define a custom struct for each query response
@@ -44,3 +137,5 @@ pub struct ConfigResponse {
 pub struct BorrowRateResponse {
     pub rate: Decimal256,
 }
+
+pub type PendingChangesResponse = timelock::PendingChangesResponse<ConfigChange>;
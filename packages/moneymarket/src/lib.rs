@@ -7,8 +7,12 @@ pub mod liquidation_queue;
 pub mod market;
 pub mod oracle;
 pub mod overseer;
+pub mod ownership;
+pub mod pause;
 pub mod querier;
+pub mod timelock;
 pub mod tokens;
+pub mod version;
 
 #[cfg(test)]
 mod mock_querier;
@@ -33,7 +33,6 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     UpdateConfig {
-        owner: Option<String>,
         oracle_contract: Option<String>,
         stable_denom: Option<String>,
         safe_ratio: Option<Decimal256>,
@@ -42,6 +41,12 @@ pub enum ExecuteMsg {
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
     },
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+    DropOwnershipProposal {},
+    ClaimOwnership {},
     SubmitBid {
         collateral_token: String,
         premium_rate: Decimal256,
@@ -86,8 +91,14 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Liquidations of this contract cannot be paused, answered
+    /// like the other contracts the market checks before borrows
+    PauseStatus {},
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
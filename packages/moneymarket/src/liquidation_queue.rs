@@ -5,6 +5,8 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::Uint128;
 use cw20::Cw20ReceiveMsg;
 
+use crate::pause::Operation;
+use crate::timelock;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -30,22 +32,47 @@ pub struct InstantiateMsg {
     /// Time period that needs to pass for a bid to be activated (seconds)
     pub waiting_period: u64,
     pub overseer: String,
+    /// Minimum delay of queued changes in seconds
+    pub min_delay: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
-    UpdateConfig {
-        owner: Option<String>,
-        oracle_contract: Option<String>,
-        safe_ratio: Option<Decimal256>,
-        bid_fee: Option<Decimal256>,
-        liquidator_fee: Option<Decimal256>,
-        liquidation_threshold: Option<Uint256>,
-        price_timeframe: Option<u64>,
-        waiting_period: Option<u64>,
-        overseer: Option<String>,
+    /// Owner operation to queue a change, executable from
+    /// `eta` which is at least `min_delay` seconds ahead
+    ScheduleChange {
+        change: ConfigChange,
+        eta: u64,
+    },
+    /// Apply a queued change once its ETA has passed
+    ExecuteChange {
+        id: u64,
+    },
+    /// Owner operation to cancel a queued change
+    CancelChange {
+        id: u64,
+    },
+
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+
+    /// Guardian or owner operation to pause operations
+    Pause {
+        operations: Vec<Operation>,
+    },
+    /// Guardian or owner operation to resume paused operations
+    Unpause {
+        operations: Vec<Operation>,
     },
     /// Owner operation to whitelist a new collateral
     WhitelistCollateral {
@@ -79,6 +106,13 @@ pub enum ExecuteMsg {
         collateral_token: String,
         bids_idx: Option<Vec<Uint128>>,
     },
+    /// Custody interface to liquidate the native collateral
+    /// sent with the message
+    ExecuteBid {
+        liquidator: String, // Legacy parameter, ignored
+        fee_address: Option<String>,
+        repay_address: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -92,10 +126,39 @@ pub enum Cw20HookMsg {
     },
 }
 
+/// Owner changes applied through the timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum ConfigChange {
+    UpdateConfig {
+        oracle_contract: Option<String>,
+        safe_ratio: Option<Decimal256>,
+        bid_fee: Option<Decimal256>,
+        liquidator_fee: Option<Decimal256>,
+        liquidation_threshold: Option<Uint256>,
+        price_timeframe: Option<u64>,
+        waiting_period: Option<u64>,
+        overseer: Option<String>,
+        /// Address allowed to pause operations, empty to remove it
+        guardian: Option<String>,
+    },
+    UpdateMinDelay {
+        min_delay: u64,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Changes queued through the timelock
+    PendingChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Paused operations of the contract
+    PauseStatus {},
     LiquidationAmount {
         borrow_amount: Uint256,
         borrow_limit: Uint256,
@@ -125,6 +188,12 @@ pub enum QueryMsg {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Timelock delay of config changes, in seconds
+    pub min_delay: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
@@ -137,6 +206,8 @@ pub struct ConfigResponse {
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: String,
+    pub guardian: Option<String>,
+    pub min_delay: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -186,3 +257,5 @@ pub struct CollateralInfoResponse {
 pub struct BidPoolsResponse {
     pub bid_pools: Vec<BidPoolResponse>,
 }
+
+pub type PendingChangesResponse = timelock::PendingChangesResponse<ConfigChange>;
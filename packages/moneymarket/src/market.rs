@@ -2,14 +2,19 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Binary;
 use cw20::Cw20ReceiveMsg;
 
+use crate::pause::Operation;
+use std::fmt;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
     /// Owner address for config update
     pub owner_addr: String,
-    /// stable coin denom used to borrow & repay
+    /// Base stable coin denom, liabilities of all
+    /// registered stables are priced in this denom
     pub stable_denom: String,
     /// Anchor token code ID used to instantiate
     pub aterra_code_id: u64,
@@ -17,10 +22,19 @@ pub struct InstantiateMsg {
     pub anc_emission_rate: Decimal256,
     /// Maximum allowed borrow rate over deposited stable balance
     pub max_borrow_factor: Decimal256,
+    /// Fee rate charged on flash loan amounts, below 1
+    pub flash_loan_fee: Decimal256,
+    /// Premium added to the variable borrow rate
+    /// when a stable rate is locked
+    pub stable_rate_premium: Decimal256,
+    /// Maximum drift of a locked stable rate from the
+    /// current stable rate before it can be rebalanced
+    pub stable_rate_rebalance_threshold: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
@@ -42,22 +56,84 @@ pub enum ExecuteMsg {
         distributor_contract: String,
     },
 
+    /// Register a new stable denom with its own
+    /// aterra token and interest model
+    RegisterStable {
+        denom: String,
+        interest_model: String,
+        /// Anchor token code ID used to instantiate
+        aterra_code_id: u64,
+    },
+
+    /// Switch the interest model of a registered stable denom,
+    /// accruing its interest at the previous rate first
+    UpdateStable {
+        denom: String,
+        interest_model: String,
+    },
+
     /// Update config values
     UpdateConfig {
-        owner_addr: Option<String>,
         max_borrow_factor: Option<Decimal256>,
+        flash_loan_fee: Option<Decimal256>,
+        stable_rate_premium: Option<Decimal256>,
+        stable_rate_rebalance_threshold: Option<Decimal256>,
+        /// Interest model of the base stable denom
         interest_model: Option<String>,
         distribution_model: Option<String>,
+        /// Ceiling of the loan value of all borrowers in the base stable denom,
+        /// zero removes the ceiling
+        max_total_debt: Option<Uint256>,
+        /// Ceiling of the loan value of a single borrower in the base stable denom,
+        /// zero removes the ceiling
+        max_borrower_debt: Option<Uint256>,
+        /// Address allowed to pause operations, empty to remove it
+        guardian: Option<String>,
+    },
+
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+
+    /// Guardian or owner operation to pause operations
+    Pause {
+        operations: Vec<Operation>,
+    },
+    /// Guardian or owner operation to resume paused operations
+    Unpause {
+        operations: Vec<Operation>,
     },
 
     ////////////////////
     /// Overseer operations
     ////////////////////
-    /// Repay stable with liquidated collaterals
+    /// Repay stable with liquidated collaterals, the base stable
+    /// is swapped to repay the liabilities of the other stables
     RepayStableFromLiquidation {
         borrower: String,
         prev_balance: Uint256,
     },
+    /// Repay a liability with the base stable swapped
+    /// during a liquidation, only the market can execute
+    RepayStableFromSwap {
+        borrower: String,
+        denom: String,
+        prev_balance: Uint256,
+    },
+
+    /// Borrow base stable for a borrower on a leverage loop,
+    /// the borrowed amount is sent to the overseer
+    BorrowStableFromOverseer {
+        borrower: String,
+        borrow_amount: Uint256,
+    },
 
     /// Execute epoch operations
     /// 1. send reserve to collector contract
@@ -72,22 +148,79 @@ pub enum ExecuteMsg {
     ////////////////////
     /// User operations
     ////////////////////
-    /// Deposit stable asset to get interest
-    DepositStable {},
+    /// Deposit stable asset to get interest,
+    /// `denom` defaults to the base stable denom
+    DepositStable {
+        denom: Option<String>,
+    },
 
-    /// Borrow stable asset with collaterals in overseer contract
+    /// Borrow stable asset with collaterals in overseer contract,
+    /// `rate_mode` defaults to the rate mode of the existing loan
     BorrowStable {
         borrow_amount: Uint256,
         to: Option<String>,
+        denom: Option<String>,
+        rate_mode: Option<RateMode>,
     },
 
     /// Repay stable asset to decrease liability
-    RepayStable {},
+    RepayStable {
+        denom: Option<String>,
+    },
+
+    /// Swap the loan between the variable rate and
+    /// a stable rate locked at the current borrow rate
+    SwapRateMode {
+        denom: Option<String>,
+    },
+
+    /// Relock the stable rate of a borrower at the current
+    /// borrow rate, only allowed once the locked rate drifted
+    /// beyond the rebalance threshold
+    RebalanceStableRate {
+        borrower: String,
+        denom: Option<String>,
+    },
 
     /// Claim distributed ANC rewards
     ClaimRewards {
         to: Option<String>,
     },
+
+    /// Lend stable asset to `callback_contract` and execute
+    /// `callback_msg` on it, the amount plus the flash loan fee
+    /// must be returned to the market by the end of the callback
+    FlashLoan {
+        amount: Uint256,
+        callback_contract: String,
+        callback_msg: Binary,
+        denom: Option<String>,
+    },
+
+    /// Move a page of the borrower infos of a migrated 0.3.x
+    /// deployment, the market resumes once all are moved
+    MigrateBorrowers {
+        limit: Option<u32>,
+    },
+}
+
+/// Interest accrual mode of a loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateMode {
+    /// Accrues with the market borrow rate
+    Variable,
+    /// Accrues with the rate locked at borrow time
+    Stable,
+}
+
+impl fmt::Display for RateMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateMode::Variable => write!(f, "variable"),
+            RateMode::Stable => write!(f, "stable"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -95,34 +228,67 @@ pub enum ExecuteMsg {
 pub enum Cw20HookMsg {
     /// Return stable coins to a user
     /// according to exchange rate
-    RedeemStable {},
+    RedeemStable { denom: Option<String> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Paused operations of the contract
+    PauseStatus {},
+    /// Registered stable denoms
+    Stables {},
     State {
         block_height: Option<u64>,
+        denom: Option<String>,
     },
     EpochState {
         block_height: Option<u64>,
         distributed_interest: Option<Uint256>,
+        denom: Option<String>,
     },
     BorrowerInfo {
         borrower: String,
         block_height: Option<u64>,
+        denom: Option<String>,
     },
     BorrowerInfos {
         start_after: Option<String>,
         limit: Option<u32>,
+        denom: Option<String>,
+    },
+    /// Liabilities of all stable denoms priced
+    /// in the base stable denom
+    BorrowerLoanValue {
+        borrower: String,
+        block_height: u64,
+        block_time: u64,
+    },
+    /// Rates recorded on interest accruals,
+    /// `start_after` is a block height
+    RateHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        denom: Option<String>,
+    },
+    /// Block weighted average of the borrow rates
+    /// applied between the given heights
+    AverageBorrowRate {
+        from_height: u64,
+        to_height: u64,
+        denom: Option<String>,
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner_addr: String,
+    /// aterra token and interest model of the base stable denom
     pub aterra_contract: String,
     pub interest_model: String,
     pub distribution_model: String,
@@ -131,6 +297,27 @@ pub struct ConfigResponse {
     pub distributor_contract: String,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub flash_loan_fee: Decimal256,
+    pub stable_rate_premium: Decimal256,
+    pub stable_rate_rebalance_threshold: Decimal256,
+    /// Debt ceilings, unlimited if not set
+    pub max_total_debt: Option<Uint256>,
+    pub max_borrower_debt: Option<Uint256>,
+    pub guardian: Option<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StableResponse {
+    pub denom: String,
+    pub aterra_contract: String,
+    pub interest_model: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StablesResponse {
+    pub stables: Vec<StableResponse>,
 }
 
 // We define a custom struct for each query response
@@ -145,6 +332,10 @@ pub struct StateResponse {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    /// Part of the liabilities borrowed at stable rates
+    pub total_stable_liabilities: Decimal256,
+    /// Liability weighted average of the locked stable rates
+    pub avg_stable_rate: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -158,10 +349,14 @@ pub struct EpochStateResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfoResponse {
     pub borrower: String,
+    pub denom: String,
     pub interest_index: Decimal256,
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    pub rate_mode: RateMode,
+    /// Locked rate of stable rate loans
+    pub stable_rate: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -169,3 +364,36 @@ pub struct BorrowerInfoResponse {
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerLoanValueResponse {
+    pub borrower: String,
+    /// Sum of all liabilities in the base stable denom
+    pub loan_value: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSnapshotResponse {
+    pub block_height: u64,
+    pub borrow_rate: Decimal256,
+    pub deposit_rate: Decimal256,
+    pub exchange_rate: Decimal256,
+    pub utilization_ratio: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateHistoryResponse {
+    pub rate_history: Vec<RateSnapshotResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AverageBorrowRateResponse {
+    pub average_borrow_rate: Decimal256,
+    /// Range actually covered by the recorded history
+    pub from_height: u64,
+    pub to_height: u64,
+}
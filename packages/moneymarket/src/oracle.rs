@@ -7,21 +7,54 @@ use cosmwasm_bignumber::Decimal256;
 pub struct InstantiateMsg {
     pub owner: String,
     pub base_asset: String,
+    /// Age in seconds after which a feeder submission is ignored
+    pub price_window: u64,
+    /// Fresh submissions required to compute a price
+    pub quorum: u32,
+    /// Aggregated prices kept in the history of each asset
+    pub price_history_size: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     UpdateConfig {
-        owner: Option<String>,
+        price_window: Option<u64>,
+        quorum: Option<u32>,
+        price_history_size: Option<u64>,
     },
-    RegisterFeeder {
+
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner { owner: String, expires_in: u64 },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+    /// Add a feeder to the feeder set of an asset
+    RegisterFeeder { asset: String, feeder: String },
+    /// Remove a feeder and its submission from an asset
+    DeregisterFeeder { asset: String, feeder: String },
+    /// Deviation limits of an asset, submissions beyond them
+    /// wait for a second feeder while the asset is frozen
+    UpdateCircuitBreaker {
+        asset: String,
+        max_update_deviation: Decimal256,
+        max_window_deviation: Decimal256,
+        deviation_window: u64,
+    },
+    /// Sources of the price of an asset, the first source with a fresh
+    /// price is used. Assets without sources are priced by their feeders
+    UpdatePriceSources {
         asset: String,
-        feeder: String,
+        sources: Vec<PriceSource>,
     },
     FeedPrice {
         prices: Vec<(String, Decimal256)>, // (asset, price)
     },
+    /// Records the spot price of the terraswap pair sources of an asset,
+    /// to be called by its feeders several times within every window
+    ObservePairPrices { asset: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,14 +64,74 @@ pub enum QueryMsg {
     Feeder {
         asset: String,
     },
+    /// Ratio of the median fresh submissions of both assets
     Price {
         base: String,
         quote: String,
     },
+    /// Ratio of the time weighted average prices of both assets over
+    /// the last `window_seconds`, taken from the sources of their spot
+    /// prices where pair sources are averaged over their own window
+    TwapPrice {
+        base: String,
+        quote: String,
+        window_seconds: u64,
+    },
     Prices {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    CircuitBreaker {
+        asset: String,
+    },
+    PriceSources {
+        asset: String,
+    },
+    /// Aggregated prices of an asset in ascending time order
+    PriceHistory {
+        asset: String,
+        start_after_time: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Median of the fresh feeder submissions
+    Feeder {},
+    /// Time weighted average of the reserves ratio of a terraswap pair
+    /// of the asset and the base asset over the last completed window
+    /// of `window` seconds with at least three observations, as old as
+    /// the end of that window
+    TerraswapPair { pair: String, window: u64 },
+    /// Rate of `base` in `quote` on another oracle contract,
+    /// `quote` being the same asset as the base asset
+    Oracle {
+        contract: String,
+        base: String,
+        quote: String,
+    },
+    /// Product of the prices of other assets, e.g. bETH from
+    /// ETH and the bETH/ETH ratio, as old as its oldest component
+    CrossRate { assets: Vec<String> },
+}
+
+/// Price read by consumers of the oracle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceMode {
+    Spot {},
+    Twap { window_seconds: u64 },
+}
+
+impl Default for PriceMode {
+    fn default() -> Self {
+        PriceMode::Spot {}
+    }
 }
 
 // We define a custom struct for each query response
@@ -46,13 +139,16 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: String,
     pub base_asset: String,
+    pub price_window: u64,
+    pub quorum: u32,
+    pub price_history_size: u64,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct FeederResponse {
     pub asset: String,
-    pub feeder: String,
+    pub feeders: Vec<String>,
 }
 
 // We define a custom struct for each query response
@@ -61,6 +157,9 @@ pub struct PriceResponse {
     pub rate: Decimal256,
     pub last_updated_base: u64,
     pub last_updated_quote: u64,
+    /// Either asset has a price waiting for confirmation
+    #[serde(default)]
+    pub frozen: bool,
 }
 
 // We define a custom struct for each query response
@@ -76,3 +175,36 @@ pub struct PricesResponseElem {
 pub struct PricesResponse {
     pub prices: Vec<PricesResponseElem>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CircuitBreakerResponse {
+    pub asset: String,
+    pub max_update_deviation: Decimal256,
+    pub max_window_deviation: Decimal256,
+    pub deviation_window: u64,
+    /// Submission waiting for a second feeder
+    pub pending_price: Option<Decimal256>,
+    pub frozen: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceHistoryResponseElem {
+    pub price: Decimal256,
+    pub last_updated_time: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceHistoryResponse {
+    pub asset: String,
+    pub history: Vec<PriceHistoryResponseElem>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceSourcesResponse {
+    pub asset: String,
+    pub sources: Vec<PriceSource>,
+}
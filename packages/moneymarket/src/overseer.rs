@@ -1,8 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::oracle::PriceMode;
+use crate::pause::Operation;
+use crate::timelock;
 use crate::tokens::TokensHuman;
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Decimal;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -35,6 +39,10 @@ pub struct InstantiateMsg {
     pub anc_purchase_factor: Decimal256,
     /// Valid oracle price timeframe
     pub price_timeframe: u64,
+    /// Oracle price used to value collaterals, spot or TWAP
+    pub price_mode: PriceMode,
+    /// Minimum delay of queued changes in seconds
+    pub min_delay: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,7 +54,6 @@ pub enum ExecuteMsg {
 
     /// Update Configs
     UpdateConfig {
-        owner_addr: Option<String>,
         oracle_contract: Option<String>,
         liquidation_contract: Option<String>,
         threshold_deposit_rate: Option<Decimal256>,
@@ -55,22 +62,43 @@ pub enum ExecuteMsg {
         anc_purchase_factor: Option<Decimal256>,
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
+        price_mode: Option<PriceMode>,
+        /// Address allowed to pause operations, empty to remove it
+        guardian: Option<String>,
     },
 
+    /// Owner operation to propose a new owner, who has
+    /// `expires_in` seconds to claim the ownership
+    ProposeNewOwner { owner: String, expires_in: u64 },
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner operation to claim the ownership
+    ClaimOwnership {},
+
+    /// Guardian or owner operation to pause operations
+    Pause { operations: Vec<Operation> },
+    /// Guardian or owner operation to resume paused operations
+    Unpause { operations: Vec<Operation> },
+
     /// Create new custody contract for the given collateral token
     Whitelist {
-        name: String,             // bAsset name
-        symbol: String,           // bAsset symbol
-        collateral_token: String, // bAsset token contract
-        custody_contract: String, // bAsset custody contract
-        max_ltv: Decimal256,      // Loan To Value ratio
-    },
-    /// Update registered whitelist info
-    UpdateWhitelist {
-        collateral_token: String,         // bAsset token contract
-        custody_contract: Option<String>, // bAsset custody contract
-        max_ltv: Option<Decimal256>,      // Loan To Value ratio
+        name: String,                         // bAsset name
+        symbol: String,                       // bAsset symbol
+        collateral_token: String,             // bAsset token contract or denom
+        custody_contract: String,             // bAsset custody contract
+        max_ltv: Decimal256,                  // Loan To Value ratio
+        liquidation_threshold: Decimal256,    // LTV ratio at which loans are liquidated
+        max_total_locked: Uint256,            // Cap on the amount locked by all borrowers
+        max_borrower_locked: Option<Uint256>, // Cap on the amount locked by a borrower
+        debt_ceiling: Option<Uint256>,        // Cap on the loans backed by this isolated collateral
     },
+    /// Owner operation to queue a change, executable from
+    /// `eta` which is at least `min_delay` seconds ahead
+    ScheduleChange { change: ConfigChange, eta: u64 },
+    /// Apply a queued change once its ETA has passed
+    ExecuteChange { id: u64 },
+    /// Owner operation to cancel a queued change
+    CancelChange { id: u64 },
 
     /// Claims all staking rewards from the bAsset contracts
     /// and also do a epoch basis updates
@@ -82,6 +110,24 @@ pub enum ExecuteMsg {
         interest_buffer: Uint256,
         distributed_interest: Uint256,
     },
+    /// Check the borrower loan is within the borrow limit
+    /// after a repayment with collateral
+    AssertBorrowerHealth { borrower: String },
+    /// Market operation to charge the loan value of a borrower after
+    /// a borrow against the debt ceilings of its isolated collaterals
+    IncreaseIsolatedDebt { borrower: String },
+    /// Market operation to charge the remaining loan
+    /// value of a borrower after a repayment
+    DecreaseIsolatedDebt { borrower: String },
+    /// Deposit and lock the collateral bought on a leverage
+    /// iteration, then run the next iteration
+    ContinueLeverageLoop {
+        borrower: String,
+        collateral_token: String,
+        target_ltv: Decimal256,
+        iterations: u32,
+        prev_balance: Uint256,
+    },
 
     ////////////////////
     /// User operations
@@ -92,17 +138,77 @@ pub enum ExecuteMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Sell locked collateral through the whitelisted swap path
+    /// and repay the loan with the proceeds, the excess is
+    /// returned to the borrower
+    RepayWithCollateral {
+        collateral_token: String,
+        amount: Uint256,
+    },
+    /// Borrow stable, buy collateral through the whitelisted swap
+    /// path and lock it, up to `iterations` times or until the
+    /// loan reaches `target_ltv` of the collateral value.
+    /// Only cw20 collaterals are supported
+    LeverageLoop {
+        collateral_token: String,
+        target_ltv: Decimal256,
+        iterations: u32,
+    },
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
     LiquidateCollateral { borrower: String },
+    /// Sum the locked collaterals of a page of borrowers of a migrated
+    /// 0.3.x deployment, the overseer resumes once all are summed
+    MigrateTotalLocked { limit: Option<u32> },
+}
+
+/// Where collateral is sold on a repayment with collateral
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapPath {
+    /// Sold to the bids of the liquidation contract
+    LiquidationQueue {},
+    /// Sold on a terraswap pair against the stable denom
+    Terraswap {
+        pair_contract: String,
+        max_spread: Option<Decimal>,
+    },
+}
+
+/// Owner changes applied through the timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum ConfigChange {
+    /// Update registered whitelist info
+    UpdateWhitelist {
+        collateral_token: String,                  // bAsset token contract or denom
+        custody_contract: Option<String>,          // bAsset custody contract
+        max_ltv: Option<Decimal256>,               // Loan To Value ratio
+        liquidation_threshold: Option<Decimal256>, // LTV ratio at which loans are liquidated
+        swap_path: Option<SwapPath>,               // Path used to repay with collateral
+        max_total_locked: Option<Uint256>,         // Cap on the amount locked by all borrowers
+        max_borrower_locked: Option<Uint256>,      // Cap on the amount locked by a borrower
+        debt_ceiling: Option<Uint256>, // Cap on the loans backed by this isolated collateral
+    },
+    UpdateMinDelay {
+        min_delay: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Changes queued through the timelock
+    PendingChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Paused operations of the contract
+    PauseStatus {},
     EpochState {},
     Whitelist {
         collateral_token: Option<String>,
@@ -120,6 +226,31 @@ pub enum QueryMsg {
         borrower: String,
         block_time: Option<u64>,
     },
+    /// Isolated collaterals of a borrower and its
+    /// loans counted toward their debt ceilings
+    IsolatedDebt {
+        borrower: String,
+    },
+    /// Loan value above which the borrower gets liquidated
+    LiquidationLimit {
+        borrower: String,
+        block_time: Option<u64>,
+    },
+    /// Collaterals, limits and health of a borrower
+    BorrowerPosition {
+        borrower: String,
+    },
+    /// Liquidatable positions among a page of the collateral index
+    LiquidatableBorrowers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Timelock delay of config changes, in seconds
+    pub min_delay: u64,
 }
 
 // We define a custom struct for each query response
@@ -137,6 +268,10 @@ pub struct ConfigResponse {
     pub stable_denom: String,
     pub epoch_period: u64,
     pub price_timeframe: u64,
+    #[serde(default)]
+    pub price_mode: PriceMode,
+    pub guardian: Option<String>,
+    pub min_delay: u64,
 }
 
 // We define a custom struct for each query response
@@ -145,8 +280,19 @@ pub struct WhitelistResponseElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
     pub custody_contract: String,
     pub collateral_token: String,
+    pub swap_path: SwapPath,
+    /// Uncapped when not set
+    pub max_total_locked: Option<Uint256>,
+    pub max_borrower_locked: Option<Uint256>,
+    /// Amount locked by all borrowers
+    pub total_locked: Uint256,
+    /// Cap on the loans backed by the collateral, set on isolated collaterals
+    pub debt_ceiling: Option<Uint256>,
+    /// Loans of all borrowers counted toward the debt ceiling
+    pub isolated_debt: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -173,3 +319,67 @@ pub struct BorrowLimitResponse {
     pub borrower: String,
     pub borrow_limit: Uint256,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsolatedDebtResponse {
+    pub borrower: String,
+    /// Locked collaterals with a debt ceiling, new loans count toward them
+    pub isolated_collaterals: Vec<String>,
+    /// Loan value last charged against a debt ceiling
+    pub debt: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationLimitResponse {
+    pub borrower: String,
+    pub liquidation_limit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralPositionResponse {
+    pub collateral_token: String,
+    pub amount: Uint256,
+    pub price: Decimal256,
+    /// Borrows against the collateral are refused
+    /// while its oracle price is frozen
+    pub frozen: bool,
+    /// Collateral value in the stable denom
+    pub value: Uint256,
+    pub max_ltv: Decimal256,
+    /// Contribution of the collateral to the borrow limit
+    pub borrow_limit: Uint256,
+    pub liquidation_threshold: Decimal256,
+    /// Price drop of this collateral alone that makes the
+    /// position liquidatable, capped at 100%
+    pub liquidation_price_drop: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerPositionResponse {
+    pub borrower: String,
+    pub collaterals: Vec<CollateralPositionResponse>,
+    pub borrow_limit: Uint256,
+    pub liquidation_limit: Uint256,
+    pub loan_value: Uint256,
+    /// liquidation_limit / loan_value, none without a loan
+    pub health_factor: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowerResponse {
+    pub borrower: String,
+    pub loan_value: Uint256,
+    pub borrow_limit: Uint256,
+    pub liquidation_limit: Uint256,
+    /// Collaterals the liquidation contract would take
+    pub liquidation_amount: TokensHuman, // <(Collateral Token, Amount)>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowersResponse {
+    pub borrowers: Vec<LiquidatableBorrowerResponse>,
+    /// Last scanned borrower, the start_after of the next page
+    pub last_borrower: Option<String>,
+}
+
+pub type PendingChangesResponse = timelock::PendingChangesResponse<ConfigChange>;
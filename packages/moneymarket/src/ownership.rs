@@ -0,0 +1,73 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Api, CanonicalAddr, Env, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read};
+
+static KEY_OWNERSHIP_PROPOSAL: &[u8] = b"ownership_proposal";
+
+/// New owner proposed by the current owner, the ownership is
+/// only transferred once the proposed owner claims it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    pub owner: CanonicalAddr,
+    /// Block time in seconds after which the proposal can not be claimed
+    pub expires_at: u64,
+}
+
+pub fn read_ownership_proposal(storage: &dyn Storage) -> StdResult<OwnershipProposal> {
+    singleton_read(storage, KEY_OWNERSHIP_PROPOSAL)
+        .may_load()?
+        .ok_or_else(|| StdError::generic_err("Ownership proposal not found"))
+}
+
+/// Replace the pending proposal, if any, with `owner`.
+/// The caller is responsible for checking the sender is the owner
+pub fn propose_new_owner(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    owner: &str,
+    expires_in: u64,
+) -> StdResult<OwnershipProposal> {
+    let expires_at = env
+        .block
+        .time
+        .seconds()
+        .checked_add(expires_in)
+        .ok_or_else(|| StdError::generic_err("Ownership proposal expiry overflows"))?;
+    let proposal = OwnershipProposal {
+        owner: api.addr_canonicalize(api.addr_validate(owner)?.as_str())?,
+        expires_at,
+    };
+
+    singleton(storage, KEY_OWNERSHIP_PROPOSAL).save(&proposal)?;
+    Ok(proposal)
+}
+
+/// The caller is responsible for checking the sender is the owner
+pub fn drop_ownership_proposal(storage: &mut dyn Storage) -> StdResult<()> {
+    read_ownership_proposal(storage)?;
+    singleton::<OwnershipProposal>(storage, KEY_OWNERSHIP_PROPOSAL).remove();
+    Ok(())
+}
+
+/// Consume the pending proposal if `sender` is the proposed owner and the
+/// proposal has not expired, returning the owner to store in the config
+pub fn claim_ownership(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &CanonicalAddr,
+) -> StdResult<CanonicalAddr> {
+    let proposal = read_ownership_proposal(storage)?;
+    if proposal.owner != *sender {
+        return Err(StdError::generic_err("Sender is not the proposed owner"));
+    }
+
+    if env.block.time.seconds() > proposal.expires_at {
+        return Err(StdError::generic_err("Ownership proposal expired"));
+    }
+
+    singleton::<OwnershipProposal>(storage, KEY_OWNERSHIP_PROPOSAL).remove();
+    Ok(proposal.owner)
+}
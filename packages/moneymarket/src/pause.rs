@@ -0,0 +1,171 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use cosmwasm_std::{
+    to_binary, Api, CanonicalAddr, DepsMut, QueryRequest, StdError, StdResult, Storage, WasmQuery,
+};
+use cosmwasm_storage::{singleton, singleton_read};
+
+use crate::market::QueryMsg as MarketQueryMsg;
+
+static KEY_PAUSE_STATUS: &[u8] = b"pause_status";
+
+/// Operation the guardian can pause, each contract
+/// enforces the operations it executes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Deposit,
+    Redeem,
+    Borrow,
+    Repay,
+    Lock,
+    Unlock,
+    Liquidate,
+    SubmitBid,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Deposit => write!(f, "deposit"),
+            Operation::Redeem => write!(f, "redeem"),
+            Operation::Borrow => write!(f, "borrow"),
+            Operation::Repay => write!(f, "repay"),
+            Operation::Lock => write!(f, "lock"),
+            Operation::Unlock => write!(f, "unlock"),
+            Operation::Liquidate => write!(f, "liquidate"),
+            Operation::SubmitBid => write!(f, "submit_bid"),
+        }
+    }
+}
+
+/// Guardian set by a config update, an empty address removes it
+pub fn guardian_to_raw(api: &dyn Api, guardian: &str) -> StdResult<Option<CanonicalAddr>> {
+    if guardian.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(api.addr_canonicalize(
+        api.addr_validate(guardian)?.as_str(),
+    )?))
+}
+
+/// Paused operations of a contract, all operations
+/// are running until the guardian pauses them
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseStatus {
+    pub deposit: bool,
+    pub redeem: bool,
+    pub borrow: bool,
+    pub repay: bool,
+    pub lock: bool,
+    pub unlock: bool,
+    pub liquidate: bool,
+    pub submit_bid: bool,
+}
+
+impl PauseStatus {
+    pub fn is_paused(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::Deposit => self.deposit,
+            Operation::Redeem => self.redeem,
+            Operation::Borrow => self.borrow,
+            Operation::Repay => self.repay,
+            Operation::Lock => self.lock,
+            Operation::Unlock => self.unlock,
+            Operation::Liquidate => self.liquidate,
+            Operation::SubmitBid => self.submit_bid,
+        }
+    }
+
+    fn set_paused(&mut self, operation: Operation, paused: bool) {
+        let flag = match operation {
+            Operation::Deposit => &mut self.deposit,
+            Operation::Redeem => &mut self.redeem,
+            Operation::Borrow => &mut self.borrow,
+            Operation::Repay => &mut self.repay,
+            Operation::Lock => &mut self.lock,
+            Operation::Unlock => &mut self.unlock,
+            Operation::Liquidate => &mut self.liquidate,
+            Operation::SubmitBid => &mut self.submit_bid,
+        };
+        *flag = paused;
+    }
+}
+
+pub fn read_pause_status(storage: &dyn Storage) -> StdResult<PauseStatus> {
+    Ok(singleton_read(storage, KEY_PAUSE_STATUS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Pause or resume `operations` of the market. Loans must not be taken
+/// while they cannot be repaid or liquidated, so repay and liquidate
+/// can only be paused together with borrow
+pub fn update_pause_status(
+    storage: &mut dyn Storage,
+    operations: &[Operation],
+    paused: bool,
+) -> StdResult<PauseStatus> {
+    let mut status = read_pause_status(storage)?;
+    for operation in operations {
+        status.set_paused(*operation, paused);
+    }
+
+    if (status.repay || status.liquidate) && !status.borrow {
+        return Err(StdError::generic_err(
+            "Borrow must be paused while repay or liquidate is paused",
+        ));
+    }
+
+    singleton(storage, KEY_PAUSE_STATUS).save(&status)?;
+    Ok(status)
+}
+
+/// Pause or resume `operations` of a contract repaying or liquidating
+/// the loans of `market_contract`, where repay and liquidate can only
+/// be paused while the market borrow is paused
+pub fn update_pause_status_with_market(
+    deps: DepsMut,
+    market_contract: String,
+    operations: &[Operation],
+    paused: bool,
+) -> StdResult<PauseStatus> {
+    let mut status = read_pause_status(deps.storage)?;
+    for operation in operations {
+        status.set_paused(*operation, paused);
+    }
+
+    let pauses_loan_exit = operations
+        .iter()
+        .any(|operation| matches!(operation, Operation::Repay | Operation::Liquidate));
+    if paused && pauses_loan_exit {
+        let market_status: PauseStatus =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: market_contract,
+                msg: to_binary(&MarketQueryMsg::PauseStatus {})?,
+            }))?;
+
+        if !market_status.borrow {
+            return Err(StdError::generic_err(
+                "Market borrow must be paused while repay or liquidate is paused",
+            ));
+        }
+    }
+
+    singleton(deps.storage, KEY_PAUSE_STATUS).save(&status)?;
+    Ok(status)
+}
+
+pub fn assert_not_paused(storage: &dyn Storage, operation: Operation) -> StdResult<()> {
+    if read_pause_status(storage)?.is_paused(operation) {
+        return Err(StdError::generic_err(format!(
+            "Operation is paused: {}",
+            operation
+        )));
+    }
+
+    Ok(())
+}
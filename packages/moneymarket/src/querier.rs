@@ -6,10 +6,10 @@ use cosmwasm_std::{
     to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
     StdError, StdResult, Uint128, WasmQuery,
 };
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::TerraQuerier;
 
-use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use crate::oracle::{PriceMode, PriceResponse, QueryMsg as OracleQueryMsg};
 
 pub fn query_all_balances(deps: Deps, account_addr: Addr) -> StdResult<Vec<Coin>> {
     // load price form the oracle
@@ -36,7 +36,7 @@ pub fn query_token_balance(
     account_addr: Addr,
 ) -> StdResult<Uint256> {
     // load balance form the token contract
-    let balance: Uint128 = deps
+    let res: Cw20BalanceResponse = deps
         .querier
         .query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: contract_addr.to_string(),
@@ -44,9 +44,11 @@ pub fn query_token_balance(
                 address: account_addr.to_string(),
             })?,
         }))
-        .unwrap_or_else(|_| Uint128::zero());
+        .unwrap_or_else(|_| Cw20BalanceResponse {
+            balance: Uint128::zero(),
+        });
 
-    Ok(balance.into())
+    Ok(res.balance.into())
 }
 
 pub fn query_supply(deps: Deps, contract_addr: Addr) -> StdResult<Uint256> {
@@ -104,10 +106,37 @@ pub fn query_price(
     quote: String,
     time_contraints: Option<TimeConstraints>,
 ) -> StdResult<PriceResponse> {
+    query_price_with_mode(
+        deps,
+        oracle_addr,
+        base,
+        quote,
+        &PriceMode::Spot {},
+        time_contraints,
+    )
+}
+
+/// Query the spot or the time weighted average price
+pub fn query_price_with_mode(
+    deps: Deps,
+    oracle_addr: Addr,
+    base: String,
+    quote: String,
+    price_mode: &PriceMode,
+    time_contraints: Option<TimeConstraints>,
+) -> StdResult<PriceResponse> {
+    let msg = match price_mode {
+        PriceMode::Spot {} => OracleQueryMsg::Price { base, quote },
+        PriceMode::Twap { window_seconds } => OracleQueryMsg::TwapPrice {
+            base,
+            quote,
+            window_seconds: *window_seconds,
+        },
+    };
     let oracle_price: PriceResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: oracle_addr.to_string(),
-            msg: to_binary(&OracleQueryMsg::Price { base, quote })?,
+            msg: to_binary(&msg)?,
         }))?;
 
     if let Some(time_contraints) = time_contraints {
@@ -1,9 +1,25 @@
 use crate::mock_querier::mock_dependencies;
 use crate::oracle::PriceResponse;
+use crate::ownership::{
+    claim_ownership, drop_ownership_proposal, propose_new_owner, read_ownership_proposal,
+};
+use crate::pause::{
+    assert_not_paused, guardian_to_raw, read_pause_status, update_pause_status, Operation,
+};
 use crate::querier::{compute_tax, deduct_tax, query_price, query_tax_rate, TimeConstraints};
-use crate::tokens::{Tokens, TokensHuman, TokensMath, TokensToRaw};
+use crate::timelock::{
+    cancel_change, read_min_delay, read_pending_changes, schedule_change, store_min_delay,
+    take_ready_change, PendingChange,
+};
+use crate::tokens::{
+    collateral_to_raw, native_denom, Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw,
+};
+use crate::version::{
+    assert_migrate_version, get_contract_version, is_older_version, set_contract_version,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::mock_env;
 use cosmwasm_std::{Addr, Api, CanonicalAddr, Coin, Decimal, StdError, Uint128};
 
 #[test]
@@ -91,6 +107,7 @@ fn oracle_price_querier() {
             rate: Decimal256::from_ratio(131, 2),
             last_updated_base: 123,
             last_updated_quote: 321,
+            frozen: false,
         }
     );
 
@@ -300,3 +317,319 @@ fn token_math_invalid_token_2() {
 
     let _ = tokens_1_raw.sub(tokens_2_raw);
 }
+
+#[test]
+fn native_denom_tokens() {
+    let deps = mock_dependencies(&[]);
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
+    let tokens: TokensHuman = vec![
+        ("token1".to_string(), Uint256::from(1000000u64)),
+        (ibc_denom.to_string(), Uint256::from(2000000u64)),
+    ];
+
+    // native collaterals are keyed by their denom
+    let mut tokens_raw: Tokens = tokens.to_raw(deps.as_ref()).unwrap();
+    assert_eq!(native_denom(&tokens_raw[0].0), None);
+    assert_eq!(native_denom(&tokens_raw[1].0), Some(ibc_denom.to_string()));
+    assert_eq!(
+        tokens_raw[0].0,
+        deps.api.addr_canonicalize("token1").unwrap()
+    );
+
+    tokens_raw.add(vec![(
+        collateral_to_raw(&deps.api, ibc_denom).unwrap(),
+        Uint256::from(1000000u64),
+    )]);
+    let mut tokens_human: TokensHuman = tokens_raw.to_human(deps.as_ref()).unwrap();
+    tokens_human.sort();
+    assert_eq!(
+        tokens_human,
+        vec![
+            (ibc_denom.to_string(), Uint256::from(3000000u64)),
+            ("token1".to_string(), Uint256::from(1000000u64)),
+        ]
+    );
+
+    match collateral_to_raw(&deps.api, "Ibc Token") {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid collateral: Ibc Token"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn contract_version_checks() {
+    let mut deps = mock_dependencies(&[]);
+
+    // legacy deployment without version record
+    assert_eq!(
+        assert_migrate_version(deps.as_ref().storage, "crates.io:test", "0.3.1").unwrap(),
+        None
+    );
+    assert!(is_older_version(&None, "0.0.1").unwrap());
+
+    set_contract_version(deps.as_mut().storage, "crates.io:test", "0.3.1").unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        "0.3.1".to_string()
+    );
+
+    // upgrade and same version are allowed
+    assert_eq!(
+        assert_migrate_version(deps.as_ref().storage, "crates.io:test", "0.10.0").unwrap(),
+        Some("0.3.1".to_string())
+    );
+    assert_eq!(
+        assert_migrate_version(deps.as_ref().storage, "crates.io:test", "0.3.1").unwrap(),
+        Some("0.3.1".to_string())
+    );
+    assert!(is_older_version(&Some("0.3.1".to_string()), "0.10.0").unwrap());
+    assert!(!is_older_version(&Some("0.3.1".to_string()), "0.3.1").unwrap());
+
+    // pre-release and build suffixes are ignored
+    assert!(is_older_version(&Some("0.3.1-rc1".to_string()), "0.4.0").unwrap());
+    assert!(!is_older_version(&Some("0.4.0-rc1".to_string()), "0.4.0").unwrap());
+    assert!(!is_older_version(&Some("0.4.0+build.1".to_string()), "0.4.0").unwrap());
+    assert_eq!(
+        is_older_version(&Some("0.4.x".to_string()), "0.4.0"),
+        Err(StdError::generic_err("Invalid version: 0.4.x"))
+    );
+
+    // a release candidate upgrades to its release
+    set_contract_version(deps.as_mut().storage, "crates.io:test", "0.3.1-rc1").unwrap();
+    assert_eq!(
+        assert_migrate_version(deps.as_ref().storage, "crates.io:test", "0.3.1").unwrap(),
+        Some("0.3.1-rc1".to_string())
+    );
+    set_contract_version(deps.as_mut().storage, "crates.io:test", "0.3.1").unwrap();
+
+    // downgrades and other contracts are rejected
+    match assert_migrate_version(deps.as_ref().storage, "crates.io:test", "0.2.9") {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot downgrade contract from 0.3.1 to 0.2.9".to_string()
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    match assert_migrate_version(deps.as_ref().storage, "crates.io:other", "0.3.1") {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot migrate crates.io:test to crates.io:other".to_string()
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn pause_status() {
+    let mut deps = mock_dependencies(&[]);
+
+    // all operations run until paused
+    assert!(!read_pause_status(deps.as_ref().storage)
+        .unwrap()
+        .is_paused(Operation::Borrow));
+    assert_not_paused(deps.as_ref().storage, Operation::Deposit).unwrap();
+
+    let status = update_pause_status(
+        deps.as_mut().storage,
+        &[Operation::Deposit, Operation::SubmitBid],
+        true,
+    )
+    .unwrap();
+    assert!(status.deposit && status.submit_bid);
+    assert_eq!(
+        assert_not_paused(deps.as_ref().storage, Operation::Deposit),
+        Err(StdError::generic_err("Operation is paused: deposit"))
+    );
+
+    // repay and liquidate cannot be paused while borrow runs
+    for operation in [Operation::Repay, Operation::Liquidate] {
+        assert_eq!(
+            update_pause_status(deps.as_mut().storage, &[operation], true),
+            Err(StdError::generic_err(
+                "Borrow must be paused while repay or liquidate is paused"
+            ))
+        );
+    }
+    update_pause_status(
+        deps.as_mut().storage,
+        &[Operation::Borrow, Operation::Repay, Operation::Liquidate],
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        update_pause_status(deps.as_mut().storage, &[Operation::Borrow], false),
+        Err(StdError::generic_err(
+            "Borrow must be paused while repay or liquidate is paused"
+        ))
+    );
+
+    let status = update_pause_status(
+        deps.as_mut().storage,
+        &[
+            Operation::Deposit,
+            Operation::Borrow,
+            Operation::Repay,
+            Operation::Liquidate,
+        ],
+        false,
+    )
+    .unwrap();
+    assert_eq!(status, read_pause_status(deps.as_ref().storage).unwrap());
+    assert!(status.submit_bid && !status.deposit && !status.borrow);
+
+    // an empty address removes the guardian
+    assert_eq!(guardian_to_raw(&deps.api, "").unwrap(), None);
+    assert_eq!(
+        guardian_to_raw(&deps.api, "guardian").unwrap(),
+        Some(deps.api.addr_canonicalize("guardian").unwrap())
+    );
+}
+
+#[test]
+fn ownership_transfer() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+    let new_owner = deps.api.addr_canonicalize("owner0001").unwrap();
+
+    assert_eq!(
+        claim_ownership(deps.as_mut().storage, &env, &new_owner),
+        Err(StdError::generic_err("Ownership proposal not found"))
+    );
+
+    assert_eq!(
+        propose_new_owner(&mut deps.storage, &deps.api, &env, "owner0001", u64::MAX),
+        Err(StdError::generic_err("Ownership proposal expiry overflows"))
+    );
+
+    let proposal =
+        propose_new_owner(&mut deps.storage, &deps.api, &env, "owner0001", 100u64).unwrap();
+    assert_eq!(proposal.expires_at, env.block.time.seconds() + 100u64);
+    assert_eq!(
+        read_ownership_proposal(deps.as_ref().storage).unwrap(),
+        proposal
+    );
+
+    let other = deps.api.addr_canonicalize("addr0000").unwrap();
+    assert_eq!(
+        claim_ownership(deps.as_mut().storage, &env, &other),
+        Err(StdError::generic_err("Sender is not the proposed owner"))
+    );
+
+    env.block.time = env.block.time.plus_seconds(101u64);
+    assert_eq!(
+        claim_ownership(deps.as_mut().storage, &env, &new_owner),
+        Err(StdError::generic_err("Ownership proposal expired"))
+    );
+
+    drop_ownership_proposal(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        drop_ownership_proposal(deps.as_mut().storage),
+        Err(StdError::generic_err("Ownership proposal not found"))
+    );
+
+    propose_new_owner(&mut deps.storage, &deps.api, &env, "owner0001", 100u64).unwrap();
+    assert_eq!(
+        claim_ownership(deps.as_mut().storage, &env, &new_owner).unwrap(),
+        new_owner
+    );
+    assert_eq!(
+        read_ownership_proposal(deps.as_ref().storage),
+        Err(StdError::generic_err("Ownership proposal not found"))
+    );
+}
+
+#[test]
+fn timelocked_changes() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    assert_eq!(
+        read_min_delay(deps.as_ref().storage),
+        Err(StdError::generic_err("Minimum delay not set"))
+    );
+    assert_eq!(
+        schedule_change(deps.as_mut().storage, &env, "first".to_string(), now),
+        Err(StdError::generic_err("Minimum delay not set"))
+    );
+
+    // the earliest ETA saturates instead of overflowing
+    store_min_delay(deps.as_mut().storage, u64::MAX).unwrap();
+    assert_eq!(
+        schedule_change(
+            deps.as_mut().storage,
+            &env,
+            "first".to_string(),
+            u64::MAX - 1
+        ),
+        Err(StdError::generic_err(format!(
+            "ETA must be at least {} seconds ahead",
+            u64::MAX
+        )))
+    );
+
+    store_min_delay(deps.as_mut().storage, 100u64).unwrap();
+    assert_eq!(read_min_delay(deps.as_ref().storage).unwrap(), 100u64);
+
+    assert_eq!(
+        schedule_change(deps.as_mut().storage, &env, "first".to_string(), now + 99),
+        Err(StdError::generic_err(
+            "ETA must be at least 100 seconds ahead"
+        ))
+    );
+    assert_eq!(
+        schedule_change(deps.as_mut().storage, &env, "first".to_string(), now + 100),
+        Ok(1u64)
+    );
+    assert_eq!(
+        schedule_change(deps.as_mut().storage, &env, "second".to_string(), now + 200),
+        Ok(2u64)
+    );
+
+    let pending = read_pending_changes::<String>(deps.as_ref().storage, None, None).unwrap();
+    assert_eq!(
+        pending.changes,
+        vec![
+            PendingChange {
+                id: 1u64,
+                eta: now + 100,
+                change: "first".to_string(),
+            },
+            PendingChange {
+                id: 2u64,
+                eta: now + 200,
+                change: "second".to_string(),
+            },
+        ]
+    );
+    let pending = read_pending_changes::<String>(deps.as_ref().storage, Some(1u64), None).unwrap();
+    assert_eq!(pending.changes.len(), 1);
+    assert_eq!(pending.changes[0].id, 2u64);
+
+    assert_eq!(
+        take_ready_change::<String>(deps.as_mut().storage, &env, 1u64),
+        Err(StdError::generic_err(format!(
+            "Change is not executable before {}",
+            now + 100
+        )))
+    );
+
+    env.block.time = env.block.time.plus_seconds(100u64);
+    assert_eq!(
+        take_ready_change::<String>(deps.as_mut().storage, &env, 1u64),
+        Ok("first".to_string())
+    );
+    assert_eq!(
+        take_ready_change::<String>(deps.as_mut().storage, &env, 1u64),
+        Err(StdError::generic_err("Pending change not found: 1"))
+    );
+
+    cancel_change::<String>(deps.as_mut().storage, 2u64).unwrap();
+    assert_eq!(
+        cancel_change::<String>(deps.as_mut().storage, 2u64),
+        Err(StdError::generic_err("Pending change not found: 2"))
+    );
+    let pending = read_pending_changes::<String>(deps.as_ref().storage, None, None).unwrap();
+    assert!(pending.changes.is_empty());
+}
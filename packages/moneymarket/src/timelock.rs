@@ -0,0 +1,140 @@
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Env, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+
+static KEY_MIN_DELAY: &[u8] = b"timelock_min_delay";
+static KEY_LAST_CHANGE_ID: &[u8] = b"timelock_last_change_id";
+static PREFIX_PENDING_CHANGE: &[u8] = b"timelock_pending_change";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Change queued by the owner, anyone can execute it once
+/// the block time reaches `eta`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChange<T> {
+    pub id: u64,
+    pub eta: u64,
+    pub change: T,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangesResponse<T> {
+    pub changes: Vec<PendingChange<T>>,
+}
+
+/// A zero delay would let the owner apply changes
+/// in the same block they are queued
+pub fn store_min_delay(storage: &mut dyn Storage, min_delay: u64) -> StdResult<()> {
+    if min_delay == 0 {
+        return Err(StdError::generic_err("Minimum delay must be positive"));
+    }
+
+    singleton(storage, KEY_MIN_DELAY).save(&min_delay)
+}
+
+/// Deployments store their delay on instantiate or migrate,
+/// a missing delay is never read as zero
+pub fn read_min_delay(storage: &dyn Storage) -> StdResult<u64> {
+    singleton_read(storage, KEY_MIN_DELAY)
+        .may_load()?
+        .ok_or_else(|| StdError::generic_err("Minimum delay not set"))
+}
+
+/// Queue `change` to be executable from `eta`, which must be at least
+/// the minimum delay ahead of the current block time. Returns the change id
+pub fn schedule_change<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    change: T,
+    eta: u64,
+) -> StdResult<u64> {
+    let min_delay = read_min_delay(storage)?;
+    if eta < env.block.time.seconds().saturating_add(min_delay) {
+        return Err(StdError::generic_err(format!(
+            "ETA must be at least {} seconds ahead",
+            min_delay
+        )));
+    }
+
+    let id = singleton_read::<u64>(storage, KEY_LAST_CHANGE_ID)
+        .may_load()?
+        .unwrap_or_default()
+        + 1;
+    singleton(storage, KEY_LAST_CHANGE_ID).save(&id)?;
+
+    Bucket::new(storage, PREFIX_PENDING_CHANGE)
+        .save(&id.to_be_bytes(), &PendingChange { id, eta, change })?;
+
+    Ok(id)
+}
+
+pub fn read_pending_change<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    id: u64,
+) -> StdResult<PendingChange<T>> {
+    ReadonlyBucket::new(storage, PREFIX_PENDING_CHANGE)
+        .may_load(&id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Pending change not found: {}", id)))
+}
+
+/// Remove the change `id` from the queue if its ETA has passed
+/// and return it to be applied by the contract
+pub fn take_ready_change<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+) -> StdResult<T> {
+    let pending: PendingChange<T> = read_pending_change(storage, id)?;
+    if env.block.time.seconds() < pending.eta {
+        return Err(StdError::generic_err(format!(
+            "Change is not executable before {}",
+            pending.eta
+        )));
+    }
+
+    Bucket::<PendingChange<T>>::new(storage, PREFIX_PENDING_CHANGE).remove(&id.to_be_bytes());
+    Ok(pending.change)
+}
+
+/// The caller is responsible for checking the sender is the owner
+pub fn cancel_change<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    id: u64,
+) -> StdResult<()> {
+    read_pending_change::<T>(storage, id)?;
+    Bucket::<PendingChange<T>>::new(storage, PREFIX_PENDING_CHANGE).remove(&id.to_be_bytes());
+    Ok(())
+}
+
+pub fn read_pending_changes<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingChangesResponse<T>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    let changes = ReadonlyBucket::new(storage, PREFIX_PENDING_CHANGE)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<PendingChange<T>>>>()?;
+
+    Ok(PendingChangesResponse { changes })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    })
+}
@@ -1,5 +1,9 @@
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{CanonicalAddr, Deps, StdError, StdResult};
+use cosmwasm_std::{Api, CanonicalAddr, Deps, StdError, StdResult};
+
+/// Tokens are keyed by the canonical address of a cw20 collateral
+/// or by the denom of a native collateral behind this prefix
+const NATIVE_DENOM_PREFIX: &[u8] = b"native:";
 
 pub type Token = (CanonicalAddr, Uint256);
 pub type TokenHuman = (String, Uint256);
@@ -129,7 +133,7 @@ impl TokensToHuman for Tokens {
     fn to_human(&self, deps: Deps) -> StdResult<TokensHuman> {
         let collaterals: TokensHuman = self
             .iter()
-            .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
+            .map(|c| Ok((collateral_to_human(deps.api, &c.0)?, c.1)))
             .collect::<StdResult<TokensHuman>>()?;
         Ok(collaterals)
     }
@@ -139,8 +143,50 @@ impl TokensToRaw for TokensHuman {
     fn to_raw(&self, deps: Deps) -> StdResult<Tokens> {
         let collaterals: Tokens = self
             .iter()
-            .map(|c| Ok((deps.api.addr_canonicalize(c.0.as_str())?, c.1)))
+            .map(|c| Ok((collateral_to_raw(deps.api, c.0.as_str())?, c.1)))
             .collect::<StdResult<Tokens>>()?;
         Ok(collaterals)
     }
 }
+
+/// Key of a collateral, either a cw20 token address or,
+/// when it is not a valid address, a native denom
+pub fn collateral_to_raw(api: &dyn Api, collateral: &str) -> StdResult<CanonicalAddr> {
+    if api.addr_validate(collateral).is_ok() {
+        return api.addr_canonicalize(collateral);
+    }
+
+    if !is_valid_denom(collateral) {
+        return Err(StdError::generic_err(format!(
+            "Invalid collateral: {}",
+            collateral
+        )));
+    }
+
+    Ok(CanonicalAddr::from(
+        [NATIVE_DENOM_PREFIX, collateral.as_bytes()].concat(),
+    ))
+}
+
+pub fn collateral_to_human(api: &dyn Api, collateral: &CanonicalAddr) -> StdResult<String> {
+    match native_denom(collateral) {
+        Some(denom) => Ok(denom),
+        None => Ok(api.addr_humanize(collateral)?.to_string()),
+    }
+}
+
+/// Denom of a native collateral key, `None` for cw20 collaterals
+pub fn native_denom(collateral: &CanonicalAddr) -> Option<String> {
+    collateral
+        .as_slice()
+        .strip_prefix(NATIVE_DENOM_PREFIX)
+        .map(|denom| String::from_utf8_lossy(denom).to_string())
+}
+
+// same rules as the cosmos sdk, e.g. `uluna` or `ibc/27394FB0...`
+fn is_valid_denom(denom: &str) -> bool {
+    let mut chars = denom.chars();
+    (3..=128).contains(&denom.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
@@ -0,0 +1,89 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read};
+
+/// Storage key shared with the cw2 spec, so external tooling
+/// can read the contract name and version of a deployment
+static KEY_CONTRACT_INFO: &[u8] = b"contract_info";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    /// Crate name of the contract, e.g. `crates.io:moneymarket-market`
+    pub contract: String,
+    /// Crate version of the contract, in `major.minor.patch` format
+    pub version: String,
+}
+
+pub fn set_contract_version(
+    storage: &mut dyn Storage,
+    contract: &str,
+    version: &str,
+) -> StdResult<()> {
+    singleton(storage, KEY_CONTRACT_INFO).save(&ContractVersion {
+        contract: contract.to_string(),
+        version: version.to_string(),
+    })
+}
+
+pub fn get_contract_version(storage: &dyn Storage) -> StdResult<ContractVersion> {
+    singleton_read(storage, KEY_CONTRACT_INFO).load()
+}
+
+/// Check the stored version record before running a migration.
+/// Returns the stored version, or `None` when the contract was
+/// deployed before version records were introduced.
+/// Errors when the stored record belongs to another contract or
+/// when the migration would downgrade the contract.
+pub fn assert_migrate_version(
+    storage: &dyn Storage,
+    contract: &str,
+    version: &str,
+) -> StdResult<Option<String>> {
+    let stored: ContractVersion = match singleton_read(storage, KEY_CONTRACT_INFO).may_load()? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    if stored.contract != contract {
+        return Err(StdError::generic_err(format!(
+            "Cannot migrate {} to {}",
+            stored.contract, contract
+        )));
+    }
+
+    if parse_version(&stored.version)? > parse_version(version)? {
+        return Err(StdError::generic_err(format!(
+            "Cannot downgrade contract from {} to {}",
+            stored.version, version
+        )));
+    }
+
+    Ok(Some(stored.version))
+}
+
+/// Returns true when `version` is older than `target`.
+/// Legacy deployments without a version record (`None`)
+/// are older than any version.
+pub fn is_older_version(version: &Option<String>, target: &str) -> StdResult<bool> {
+    match version {
+        Some(version) => Ok(parse_version(version)? < parse_version(target)?),
+        None => Ok(true),
+    }
+}
+
+/// Pre-release and build suffixes (`0.4.0-rc1`, `0.4.0+build`)
+/// are ignored, so they compare equal to their release
+fn parse_version(version: &str) -> StdResult<Vec<u64>> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|v| {
+            v.parse::<u64>()
+                .map_err(|_| StdError::generic_err(format!("Invalid version: {}", version)))
+        })
+        .collect()
+}